@@ -0,0 +1,505 @@
+use crate::core::qrgen::{QrCode, QrCodeEcc, Version};
+use crate::core::renderer::{
+    ModuleContext, ModuleShapeProvider, QrGrid, custom_module_shape, icon::hidden_modules,
+    module_shape, png::skia_path,
+};
+use crate::models::{Color, QrConfig, QrImage, Symbology};
+use std::fmt;
+use tiny_skia::{FillRule, Paint, Pixmap, Transform};
+
+/// Contrast ratio below which scanners are unlikely to tell the modules apart.
+const MIN_CONTRAST: f64 = 2.5;
+/// Contrast ratio recommended for reliable scanning in poor lighting.
+const GOOD_CONTRAST: f64 = 4.0;
+/// Quiet zone required by the QR code specification, in modules.
+const MIN_QUIET_ZONE: u32 = 4;
+/// Quiet zone required around Micro QR and rMQR codes, in modules.
+const MIN_MICRO_QUIET_ZONE: u32 = 2;
+/// Share of its cell a module should fill to survive blur and small print sizes.
+const GOOD_FILL: f64 = 0.55;
+
+/// How serious a finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The code may scan poorly with some scanners or conditions.
+    Warning,
+    /// The code is unlikely to scan at all.
+    Critical,
+}
+
+/// A finding about something that makes the code harder to scan.
+#[derive(Debug, Clone)]
+pub struct ScanWarning {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for ScanWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// The estimated readability of a styled QR code.
+#[derive(Debug, Clone)]
+pub struct ScanReport {
+    /// Score from 0 (unreadable) to 100 (no issues found).
+    pub score: u8,
+    pub warnings: Vec<ScanWarning>,
+}
+
+impl ScanReport {
+    /// Whether no finding makes the code unlikely to scan.
+    pub fn is_readable(&self) -> bool {
+        self.warnings
+            .iter()
+            .all(|w| w.severity != Severity::Critical)
+    }
+}
+
+/// Estimates how readable the symbol is once rendered with the given configuration.
+/// Checks the contrast of every foreground color against the background, the codewords
+/// hidden by the icon (given by `coverage`) against what error correction can repair,
+/// the finder patterns it covers, the quiet zone and how much of its cell the module
+/// shape fills.
+pub fn analyze(
+    grid: &dyn QrGrid,
+    coverage: Option<&IconCoverage>,
+    config: &QrConfig,
+) -> ScanReport {
+    let mut penalty = 0u32;
+    let mut warnings = Vec::new();
+    let mut warn = |severity: Severity, cost: u32, message: String| {
+        penalty += cost;
+        warnings.push(ScanWarning { severity, message });
+    };
+
+    // Contrast, for every stop of a gradient and the finder colors, as seen over
+    // the background, itself seen over white if it can be seen through
+    let background = config.background.over(Color::WHITE);
+    let corner_styles = [
+        &config.finder_top_left,
+        &config.finder_top_right,
+        &config.finder_bottom_left,
+        &config.finder_bottom_right,
+    ];
+    let colors = config
+        .foreground
+        .iter()
+        .chain(&config.finder_foreground)
+        .map(|stop| stop.color)
+        .chain(config.finder_color)
+        .chain(config.finder_eye_color)
+        .chain(
+            corner_styles
+                .into_iter()
+                .flatten()
+                .flat_map(|style| style.color.into_iter().chain(style.eye_color)),
+        );
+    for color in colors {
+        let foreground = color.over(background);
+        // Rounded down, so that a ratio just below a threshold is not shown as reaching it
+        let ratio = (contrast_ratio(foreground, background) * 100.0).floor() / 100.0;
+        if ratio < MIN_CONTRAST {
+            warn(
+                Severity::Critical,
+                60,
+                format!(
+                    "Foreground color {} has a contrast ratio of {:.2}:1 with the background {}, \
+                     scanners need at least {}:1",
+                    color, ratio, config.background, MIN_CONTRAST
+                ),
+            );
+        } else if ratio < GOOD_CONTRAST {
+            warn(
+                Severity::Warning,
+                15,
+                format!(
+                    "Foreground color {} has a low contrast ratio of {:.2}:1 with the background {}, \
+                     {}:1 or more is recommended",
+                    color, ratio, config.background, GOOD_CONTRAST
+                ),
+            );
+        }
+        if relative_luminance(foreground) > relative_luminance(background) {
+            warn(
+                Severity::Warning,
+                10,
+                format!(
+                    "Foreground color {} is lighter than the background, \
+                     some scanners cannot read inverted codes",
+                    color
+                ),
+            );
+        }
+    }
+
+    // Codewords hidden by the icon, against the errors each block can correct
+    if let Some(coverage) = coverage {
+        let (worst, covered) = coverage.worst_block();
+        let usage = covered as f64 / coverage.correctable.max(1) as f64;
+        let detail = format!(
+            "it covers {} codewords of block {}, which can correct {} \
+             with the {:?} error correction level",
+            covered,
+            worst + 1,
+            coverage.correctable,
+            coverage.ecl
+        );
+        if usage > 1.0 {
+            warn(
+                Severity::Critical,
+                60,
+                format!(
+                    "The icon hides more than error correction can repair: {}",
+                    detail
+                ),
+            );
+        } else if usage > 0.75 {
+            warn(
+                Severity::Warning,
+                20,
+                format!(
+                    "The icon uses most of the error correction, leaving little room for damage: {}",
+                    detail
+                ),
+            );
+        }
+    }
+
+    // Finder patterns covered by the icon
+    if let Some(aspect) = icon_aspect_ratio(config) {
+        let (width, height) = (grid.width(), grid.height());
+        let hidden = hidden_modules(width, height, aspect, config);
+        let on_finder =
+            (0..width * height).any(|i| hidden[i] && grid.is_finder(i % width, i / width));
+        if on_finder {
+            warn(
+                Severity::Critical,
+                60,
+                "The icon covers a finder pattern, which scanners need to locate the code"
+                    .to_string(),
+            );
+        }
+    }
+
+    let min_quiet_zone = match config.symbology {
+        Symbology::Qr => MIN_QUIET_ZONE,
+        Symbology::Micro | Symbology::Rmqr => MIN_MICRO_QUIET_ZONE,
+    };
+    if config.quiet_zone < min_quiet_zone {
+        warn(
+            Severity::Warning,
+            (min_quiet_zone - config.quiet_zone) * 5,
+            format!(
+                "The quiet zone is {} modules wide, scanners expect at least {}",
+                config.quiet_zone, min_quiet_zone
+            ),
+        );
+    }
+
+    let fill = module_fill(grid, config);
+    if fill < GOOD_FILL {
+        warn(
+            Severity::Warning,
+            10,
+            format!(
+                "The module shape only fills {:.0}% of each module on average, \
+                 which may not scan when printed small or blurred",
+                fill * 100.0
+            ),
+        );
+    }
+
+    ScanReport {
+        score: 100u32.saturating_sub(penalty) as u8,
+        warnings,
+    }
+}
+
+/// Relative luminance of an sRGB color, as defined by WCAG 2.
+fn relative_luminance(Color { r, g, b, .. }: Color) -> f64 {
+    let linear = |c: u8| {
+        let c = f64::from(c) / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+/// WCAG contrast ratio of two colors, from 1 (same luminance) to 21 (black on white).
+fn contrast_ratio(a: Color, b: Color) -> f64 {
+    let (a, b) = (relative_luminance(a), relative_luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// Width divided by height of the configured icon, if there is one that can be loaded.
+pub fn icon_aspect_ratio(config: &QrConfig) -> Option<f64> {
+    let loaded;
+    let image = match (&config.image, &config.icon) {
+        (Some(image), _) => image,
+        (None, Some(path)) => {
+            loaded = QrImage::load_from_path(path).ok()?;
+            &loaded
+        }
+        (None, None) => return None,
+    };
+    image.aspect_ratio()
+}
+
+/// The part of a QR code hidden by the icon, laid out as the renderers draw it.
+/// A module counts as hidden when its center is under the visible part of the icon,
+/// or when any part of it is under the cleared area or the plate around the icon.
+#[derive(Debug, Clone)]
+pub struct IconCoverage {
+    /// Error correction level of the symbol.
+    pub ecl: QrCodeEcc,
+    /// Number of modules hidden, function modules included.
+    pub modules: usize,
+    /// Number of codewords with hidden modules, for each block.
+    pub codewords: Vec<usize>,
+    /// Number of wrong codewords that error correction can repair in each block,
+    /// leaving out the codewords kept for misdecode protection.
+    pub correctable: usize,
+}
+
+impl IconCoverage {
+    /// Computes what an icon with the given aspect ratio (width divided by height) hides
+    /// in a QR code of the given version and error correction level, with the excavation
+    /// and plate of the configuration.
+    pub fn new(version: Version, ecl: QrCodeEcc, aspect: f64, config: &QrConfig) -> Self {
+        let size = usize::from(version.value()) * 4 + 17;
+        let hidden = hidden_modules(size, size, aspect, config);
+
+        let locations = QrCode::codeword_locations(version, ecl);
+        let mut covered: Vec<Vec<bool>> = QrCode::block_lengths(version, ecl)
+            .iter()
+            .map(|&len| vec![false; len])
+            .collect();
+        let mut modules = 0;
+        for y in 0..size {
+            for x in 0..size {
+                if !hidden[y * size + x] {
+                    continue;
+                }
+                modules += 1;
+                if let Some((block, index)) = locations[y * size + x] {
+                    covered[block][index] = true;
+                }
+            }
+        }
+
+        Self {
+            ecl,
+            modules,
+            codewords: covered
+                .iter()
+                .map(|block| block.iter().filter(|&&c| c).count())
+                .collect(),
+            correctable: (QrCode::ecc_codewords_per_block(version, ecl)
+                - QrCode::misdecode_protection_codewords(version, ecl))
+                / 2,
+        }
+    }
+
+    /// Returns the index of the block with the most hidden codewords, and their number.
+    pub fn worst_block(&self) -> (usize, usize) {
+        self.codewords
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by_key(|&(_, covered)| covered)
+            .unwrap_or((0, 0))
+    }
+
+    /// Whether error correction can repair all the hidden codewords.
+    pub fn is_recoverable(&self) -> bool {
+        self.worst_block().1 <= self.correctable
+    }
+}
+
+/// Average share of its cell that a dark module drawn with the module shape fills,
+/// measured by rendering each module with its neighbours, since some shapes
+/// join up with the modules next to them.
+fn module_fill(grid: &dyn QrGrid, config: &QrConfig) -> f64 {
+    let shape: &dyn ModuleShapeProvider = match &config.module_provider {
+        Some(provider) => provider.as_ref(),
+        None => &config.shape,
+    };
+    let mut by_context = [None; 256];
+    let (mut total, mut count) = (0.0, 0usize);
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            let drawn = if config.module_provider.is_some() {
+                custom_module_shape(grid, config, x, y).is_some()
+            } else {
+                module_shape(grid, config, x, y) == Some(config.shape)
+            };
+            if !grid.is_dark(x, y) || !drawn {
+                continue;
+            }
+            let ctx = grid.module_context(x, y);
+            let key = usize::from(ctx.top)
+                | usize::from(ctx.bottom) << 1
+                | usize::from(ctx.left) << 2
+                | usize::from(ctx.right) << 3
+                | usize::from(ctx.top_left) << 4
+                | usize::from(ctx.top_right) << 5
+                | usize::from(ctx.bottom_left) << 6
+                | usize::from(ctx.bottom_right) << 7;
+            total += *by_context[key].get_or_insert_with(|| cell_fill(shape, &ctx));
+            count += 1;
+        }
+    }
+    if count == 0 {
+        1.0
+    } else {
+        total / count as f64
+    }
+}
+
+/// Share of its cell that a module of the given shape fills.
+fn cell_fill(shape: &dyn ModuleShapeProvider, ctx: &ModuleContext) -> f64 {
+    const CELL: u32 = 64;
+    let Some(mut pixmap) = Pixmap::new(CELL, CELL) else {
+        return 1.0;
+    };
+    let Some(path) = skia_path(&shape.module_path(0.0, 0.0, CELL as f32, ctx)) else {
+        return 0.0;
+    };
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(0, 0, 0, 255);
+    pixmap.fill_path(
+        &path,
+        &paint,
+        FillRule::Winding,
+        Transform::identity(),
+        None,
+    );
+    let coverage: f64 = pixmap
+        .pixels()
+        .iter()
+        .map(|p| f64::from(p.alpha()) / 255.0)
+        .sum();
+    coverage / f64::from(CELL * CELL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::decoder::decode_pixmap;
+    use crate::core::qrgen::{MicroQrCode, QrSegment};
+    use crate::core::renderer::png::render_qr;
+    use crate::core::rmqr::RmqrCode;
+    use image::{DynamicImage, Rgba, RgbaImage};
+
+    const TEXT: &str = "https://example.com/analysis";
+
+    fn icon_config(icon_size: f32) -> QrConfig {
+        let icon = RgbaImage::from_pixel(64, 64, Rgba([0, 0, 0, 255]));
+        QrConfig {
+            image: Some(QrImage::Raster(DynamicImage::ImageRgba8(icon))),
+            icon_size,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn contrast_ratio_is_rounded_down() {
+        let qr = QrCode::encode_text(TEXT, QrCodeEcc::Medium).unwrap();
+        let config = QrConfig {
+            foreground: vec!["#78808A".parse::<Color>().unwrap().into()],
+            ..Default::default()
+        };
+        let report = analyze(&qr, None, &config);
+        assert_eq!(report.warnings.len(), 1);
+        assert!(
+            report.warnings[0].message.contains("3.99:1"),
+            "{}",
+            report.warnings[0]
+        );
+    }
+
+    #[test]
+    fn analyses_micro_and_rmqr_codes() {
+        let segs = QrSegment::make_segments("12345");
+        let micro = MicroQrCode::encode_segments(&segs, QrCodeEcc::Low).unwrap();
+        let rmqr =
+            RmqrCode::encode_segments_advanced(&segs, QrCodeEcc::Medium, None, false).unwrap();
+        for (grid, symbology) in [
+            (&micro as &dyn QrGrid, Symbology::Micro),
+            (&rmqr, Symbology::Rmqr),
+        ] {
+            let config = QrConfig {
+                symbology,
+                quiet_zone: 2,
+                ..Default::default()
+            };
+            assert!(analyze(grid, None, &config).warnings.is_empty());
+
+            let config = QrConfig {
+                symbology,
+                foreground: vec!["#DDDDDD".parse::<Color>().unwrap().into()],
+                ..Default::default()
+            };
+            assert!(!analyze(grid, None, &config).is_readable());
+        }
+    }
+
+    #[test]
+    fn icon_coverage_matches_decoding() {
+        for (ecl, icon_size) in [(QrCodeEcc::High, 0.25), (QrCodeEcc::Medium, 0.2)] {
+            let qr = QrCode::encode_segments_advanced(
+                &QrSegment::make_segments(TEXT),
+                ecl,
+                Version::new(5),
+                Version::new(5),
+                None,
+                false,
+            )
+            .unwrap();
+            let config = icon_config(icon_size);
+            let coverage = IconCoverage::new(qr.version(), ecl, 1.0, &config);
+            assert!(coverage.is_recoverable());
+
+            let decoded = decode_pixmap(&render_qr(&qr, &config, 4.0).unwrap()).unwrap();
+            assert_eq!(decoded.text, TEXT);
+            assert!(decoded.corrected > 0);
+            assert!(decoded.corrected <= coverage.codewords.iter().sum());
+        }
+
+        let qr = QrCode::encode_segments_advanced(
+            &QrSegment::make_segments(TEXT),
+            QrCodeEcc::Low,
+            Version::new(5),
+            Version::new(5),
+            None,
+            false,
+        )
+        .unwrap();
+        let config = icon_config(0.3);
+        let coverage = IconCoverage::new(qr.version(), QrCodeEcc::Low, 1.0, &config);
+        assert!(!coverage.is_recoverable());
+        assert!(decode_pixmap(&render_qr(&qr, &config, 4.0).unwrap()).is_err());
+    }
+
+    #[test]
+    fn leaves_out_misdecode_protection_codewords() {
+        let correctable = |version: u8, ecl: QrCodeEcc| {
+            IconCoverage::new(Version::new(version), ecl, 1.0, &icon_config(0.2)).correctable
+        };
+        // ISO/IEC 18004 Table 9: (e - p) / 2 for e error correction codewords per block
+        assert_eq!(correctable(1, QrCodeEcc::Low), (7 - 3) / 2);
+        assert_eq!(correctable(1, QrCodeEcc::Medium), (10 - 2) / 2);
+        assert_eq!(correctable(1, QrCodeEcc::Quartile), (13 - 1) / 2);
+        assert_eq!(correctable(1, QrCodeEcc::High), (17 - 1) / 2);
+        assert_eq!(correctable(2, QrCodeEcc::Low), (10 - 2) / 2);
+        assert_eq!(correctable(2, QrCodeEcc::Medium), 16 / 2);
+        assert_eq!(correctable(3, QrCodeEcc::Low), (15 - 1) / 2);
+        assert_eq!(correctable(3, QrCodeEcc::Medium), 26 / 2);
+        assert_eq!(correctable(4, QrCodeEcc::Low), 20 / 2);
+    }
+}
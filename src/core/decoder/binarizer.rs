@@ -0,0 +1,175 @@
+use image::DynamicImage;
+use tiny_skia::Pixmap;
+
+/// A grayscale image, with transparent pixels composited over white.
+pub struct LumaImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl LumaImage {
+    /// Converts a rendered pixmap, whose colors are premultiplied by alpha.
+    pub fn from_pixmap(pixmap: &Pixmap) -> Self {
+        let pixels = pixmap
+            .pixels()
+            .iter()
+            .map(|p| {
+                let white = 255 - p.alpha();
+                luma(
+                    p.red().saturating_add(white),
+                    p.green().saturating_add(white),
+                    p.blue().saturating_add(white),
+                )
+            })
+            .collect();
+        Self {
+            width: pixmap.width() as usize,
+            height: pixmap.height() as usize,
+            pixels,
+        }
+    }
+
+    /// Converts any image, whose colors are not premultiplied by alpha.
+    pub fn from_image(image: &DynamicImage) -> Self {
+        let rgba = image.to_rgba8();
+        let pixels = rgba
+            .pixels()
+            .map(|p| {
+                let [r, g, b, a] = p.0.map(u32::from);
+                let over_white = |c: u32| ((c * a + 255 * (255 - a)) / 255) as u8;
+                luma(over_white(r), over_white(g), over_white(b))
+            })
+            .collect();
+        Self {
+            width: rgba.width() as usize,
+            height: rgba.height() as usize,
+            pixels,
+        }
+    }
+}
+
+/// Computes the perceived brightness of a color.
+fn luma(r: u8, g: u8, b: u8) -> u8 {
+    ((u32::from(r) * 299 + u32::from(g) * 587 + u32::from(b) * 114) / 1000) as u8
+}
+
+/// A black and white image, where `true` is dark.
+pub struct BitMatrix {
+    pub width: usize,
+    pub height: usize,
+    bits: Vec<bool>,
+}
+
+impl BitMatrix {
+    /// Returns whether the pixel is dark. Pixels out of bounds are light.
+    pub fn get(&self, x: i64, y: i64) -> bool {
+        x >= 0
+            && y >= 0
+            && (x as usize) < self.width
+            && (y as usize) < self.height
+            && self.bits[y as usize * self.width + x as usize]
+    }
+
+    /// Swaps dark and light, for codes drawn light on a dark background.
+    pub fn invert(&mut self) {
+        for bit in &mut self.bits {
+            *bit = !*bit;
+        }
+    }
+}
+
+/// Ways of separating dark from light pixels.
+#[derive(Clone, Copy)]
+pub enum Binarizer {
+    /// A single threshold for the whole image, chosen with Otsu's method.
+    /// Works best for flat colors.
+    Global,
+    /// A threshold per pixel from the mean of its neighbourhood.
+    /// Copes with gradients and uneven lighting.
+    Local,
+}
+
+impl Binarizer {
+    pub fn binarize(self, image: &LumaImage) -> BitMatrix {
+        let bits = match self {
+            Binarizer::Global => {
+                let threshold = otsu_threshold(&image.pixels);
+                image.pixels.iter().map(|&p| p <= threshold).collect()
+            }
+            Binarizer::Local => local_threshold(image),
+        };
+        BitMatrix {
+            width: image.width,
+            height: image.height,
+            bits,
+        }
+    }
+}
+
+/// Finds the threshold that maximises the variance between the dark and light classes.
+fn otsu_threshold(pixels: &[u8]) -> u8 {
+    let mut histogram = [0u64; 256];
+    for &p in pixels {
+        histogram[usize::from(p)] += 1;
+    }
+    let total = pixels.len() as f64;
+    let sum: f64 = histogram
+        .iter()
+        .enumerate()
+        .map(|(i, &n)| i as f64 * n as f64)
+        .sum();
+
+    let mut best = (0.0, 127u8);
+    let mut dark_count = 0.0;
+    let mut dark_sum = 0.0;
+    for (i, &n) in histogram.iter().enumerate() {
+        dark_count += n as f64;
+        dark_sum += i as f64 * n as f64;
+        let light_count = total - dark_count;
+        if dark_count == 0.0 || light_count == 0.0 {
+            continue;
+        }
+        let dark_mean = dark_sum / dark_count;
+        let light_mean = (sum - dark_sum) / light_count;
+        let variance = dark_count * light_count * (dark_mean - light_mean).powi(2);
+        if variance > best.0 {
+            best = (variance, i as u8);
+        }
+    }
+    best.1
+}
+
+/// Marks pixels darker than the mean of the square window around them.
+/// The window is large enough to cover a finder pattern's center in most images.
+fn local_threshold(image: &LumaImage) -> Vec<bool> {
+    let (width, height) = (image.width, image.height);
+    let radius = (width.max(height) / 16).max(4);
+
+    // Summed-area table with a leading row and column of zeros
+    let stride = width + 1;
+    let mut integral = vec![0u64; stride * (height + 1)];
+    for y in 0..height {
+        let mut row_sum = 0u64;
+        for x in 0..width {
+            row_sum += u64::from(image.pixels[y * width + x]);
+            integral[(y + 1) * stride + x + 1] = integral[y * stride + x + 1] + row_sum;
+        }
+    }
+
+    let mut bits = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let (top, bottom) = (y.saturating_sub(radius), (y + radius + 1).min(height));
+        for x in 0..width {
+            let (left, right) = (x.saturating_sub(radius), (x + radius + 1).min(width));
+            let sum = integral[bottom * stride + right] + integral[top * stride + left]
+                - integral[top * stride + right]
+                - integral[bottom * stride + left];
+            let area = ((bottom - top) * (right - left)) as u64;
+            // Small margin so that noise in flat areas stays light
+            let pixel = u64::from(image.pixels[y * width + x]);
+            bits.push((pixel + 4) * area < sum);
+        }
+    }
+    bits
+}
@@ -0,0 +1,292 @@
+use crate::core::eci;
+use crate::core::qrgen::{QrCodeEcc, QrSegmentMode, Version};
+use encoding_rs::SHIFT_JIS;
+
+use super::StructuredAppend;
+
+const ECC_LEVELS: [QrCodeEcc; 4] = [
+    QrCodeEcc::Low,
+    QrCodeEcc::Medium,
+    QrCodeEcc::Quartile,
+    QrCodeEcc::High,
+];
+
+/// Computes the 15-bit format information for an error correction level and mask,
+/// as drawn in the symbol.
+fn format_bits(ecl: QrCodeEcc, mask: u8) -> u32 {
+    let data = u32::from(ecl.format_bits() << 3 | mask);
+    let mut rem = data;
+    for _ in 0..10 {
+        rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+    }
+    (data << 10 | rem) ^ 0x5412
+}
+
+/// Computes the 18-bit version information, present from version 7 on.
+fn version_bits(version: u8) -> u32 {
+    let data = u32::from(version);
+    let mut rem = data;
+    for _ in 0..12 {
+        rem = (rem << 1) ^ ((rem >> 11) * 0x1F25);
+    }
+    data << 12 | rem
+}
+
+/// Reads the bits at the given positions, the first position being the least significant bit.
+fn read_bits(
+    modules: &[bool],
+    size: usize,
+    positions: impl Iterator<Item = (usize, usize)>,
+) -> u32 {
+    positions.enumerate().fold(0, |bits, (i, (x, y))| {
+        bits | u32::from(modules[y * size + x]) << i
+    })
+}
+
+/// Decodes the error correction level and mask from the two copies of the format information,
+/// correcting up to 3 wrong bits.
+pub fn read_format(modules: &[bool], size: usize) -> Result<(QrCodeEcc, u8), String> {
+    let first = read_bits(
+        modules,
+        size,
+        (0..6)
+            .map(|i| (8, i))
+            .chain([(8, 7), (8, 8), (7, 8)])
+            .chain((9..15).map(|i| (14 - i, 8))),
+    );
+    let second = read_bits(
+        modules,
+        size,
+        (0..8)
+            .map(|i| (size - 1 - i, 8))
+            .chain((8..15).map(|i| (8, size - 15 + i))),
+    );
+
+    ECC_LEVELS
+        .iter()
+        .flat_map(|&ecl| (0..8).map(move |mask| (ecl, mask)))
+        .map(|(ecl, mask)| {
+            let bits = format_bits(ecl, mask);
+            let distance = (bits ^ first)
+                .count_ones()
+                .min((bits ^ second).count_ones());
+            (distance, ecl, mask)
+        })
+        .min_by_key(|&(distance, _, _)| distance)
+        .filter(|&(distance, _, _)| distance <= 3)
+        .map(|(_, ecl, mask)| (ecl, mask))
+        .ok_or_else(|| "Unreadable format information".into())
+}
+
+/// Decodes the version from the two copies of the version information of a symbol
+/// from version 7 on, correcting up to 3 wrong bits.
+pub fn decode_version(first: u32, second: u32) -> Option<u8> {
+    (7..=40)
+        .map(|version| {
+            let bits = version_bits(version);
+            let distance = (bits ^ first)
+                .count_ones()
+                .min((bits ^ second).count_ones());
+            (distance, version)
+        })
+        .min_by_key(|&(distance, _)| distance)
+        .filter(|&(distance, _)| distance <= 3)
+        .map(|(_, version)| version)
+}
+
+/// Reads big-endian bit fields from the data codewords.
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl BitReader<'_> {
+    fn remaining(&self) -> usize {
+        self.data.len() * 8 - self.position
+    }
+
+    fn read(&mut self, len: u8) -> Result<u32, String> {
+        if usize::from(len) > self.remaining() {
+            return Err("Data ends in the middle of a segment".into());
+        }
+        let mut value = 0;
+        for _ in 0..len {
+            let bit = self.data[self.position >> 3] >> (7 - (self.position & 7)) & 1;
+            value = value << 1 | u32::from(bit);
+            self.position += 1;
+        }
+        Ok(value)
+    }
+}
+
+/// The content of the data codewords.
+pub struct Payload {
+    pub text: String,
+    pub eci: Option<u32>,
+    pub gs1: bool,
+    pub structured_append: Option<StructuredAppend>,
+}
+
+/// Collects the decoded text, buffering byte mode data until its character set is known.
+struct TextBuilder {
+    text: String,
+    bytes: Vec<u8>,
+    eci: Option<u32>,
+}
+
+impl TextBuilder {
+    /// Decodes the buffered bytes. Without an ECI designator the standard says ISO-8859-1,
+    /// but many encoders write UTF-8, so valid UTF-8 is read as such.
+    fn flush(&mut self) {
+        if self.bytes.is_empty() {
+            return;
+        }
+        let decoded = match self.eci {
+            Some(value) => eci::decode(&self.bytes, value),
+            None => String::from_utf8(self.bytes.clone()).ok(),
+        };
+        let decoded =
+            decoded.unwrap_or_else(|| self.bytes.iter().map(|&b| char::from(b)).collect());
+        self.text.push_str(&decoded);
+        self.bytes.clear();
+    }
+
+    fn push_str(&mut self, s: &str) {
+        self.flush();
+        self.text.push_str(s);
+    }
+}
+
+/// Parses the segments in the data codewords of a symbol of the given version.
+pub fn parse(data: &[u8], version: Version) -> Result<Payload, String> {
+    const ALPHANUMERIC: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+    let mut reader = BitReader { data, position: 0 };
+    let mut text = TextBuilder {
+        text: String::new(),
+        bytes: Vec::new(),
+        eci: None,
+    };
+    let mut first_eci = None;
+    let mut gs1 = false;
+    let mut structured_append = None;
+
+    // A terminator, or fewer bits than a mode indicator, ends the data
+    while reader.remaining() >= 4 {
+        let mode = match reader.read(4)? {
+            0 => break,
+            0x1 => QrSegmentMode::Numeric,
+            0x2 => QrSegmentMode::Alphanumeric,
+            0x4 => QrSegmentMode::Byte,
+            0x8 => QrSegmentMode::Kanji,
+            0x7 => QrSegmentMode::Eci,
+            0x3 => QrSegmentMode::StructuredAppend,
+            0x5 => QrSegmentMode::Fnc1First,
+            0x9 => QrSegmentMode::Fnc1Second,
+            bits => return Err(format!("Unknown segment mode {:#x}", bits)),
+        };
+        let count_bits = mode.num_char_count_bits(version);
+        let count = reader.read(count_bits)? as usize;
+
+        match mode {
+            QrSegmentMode::Numeric => {
+                let mut digits = String::with_capacity(count);
+                let mut left = count;
+                while left > 0 {
+                    let take = left.min(3);
+                    let value = reader.read([0, 4, 7, 10][take])?;
+                    if value >= 10u32.pow(take as u32) {
+                        return Err("Invalid numeric segment".into());
+                    }
+                    digits.push_str(&format!("{:0width$}", value, width = take));
+                    left -= take;
+                }
+                text.push_str(&digits);
+            }
+            QrSegmentMode::Alphanumeric => {
+                let mut chars = Vec::with_capacity(count);
+                let mut left = count;
+                while left > 0 {
+                    let take = left.min(2);
+                    let value = reader.read(if take == 2 { 11 } else { 6 })? as usize;
+                    let pair = if take == 2 {
+                        vec![value / 45, value % 45]
+                    } else {
+                        vec![value]
+                    };
+                    for index in pair {
+                        chars.push(
+                            *ALPHANUMERIC
+                                .get(index)
+                                .ok_or("Invalid alphanumeric segment")?,
+                        );
+                    }
+                    left -= take;
+                }
+                let mut segment = String::from_utf8(chars).unwrap_or_default();
+                if gs1 {
+                    // In FNC1 mode "%" stands for a group separator and "%%" for a percent sign
+                    segment = segment
+                        .replace("%%", "\u{0}")
+                        .replace('%', "\u{1D}")
+                        .replace('\u{0}', "%");
+                }
+                text.push_str(&segment);
+            }
+            QrSegmentMode::Byte => {
+                for _ in 0..count {
+                    text.bytes.push(reader.read(8)? as u8);
+                }
+            }
+            QrSegmentMode::Kanji => {
+                let mut bytes = Vec::with_capacity(count * 2);
+                for _ in 0..count {
+                    let value = reader.read(13)?;
+                    let packed = (value / 0xC0) << 8 | (value % 0xC0);
+                    let sjis = if packed < 0x1F00 {
+                        packed + 0x8140
+                    } else {
+                        packed + 0xC140
+                    };
+                    bytes.extend_from_slice(&(sjis as u16).to_be_bytes());
+                }
+                let (kanji, _, _) = SHIFT_JIS.decode(&bytes);
+                text.push_str(&kanji);
+            }
+            QrSegmentMode::Eci => {
+                let value = match reader.read(8)? {
+                    first if first & 0x80 == 0 => first,
+                    first if first & 0xC0 == 0x80 => (first & 0x3F) << 8 | reader.read(8)?,
+                    first if first & 0xE0 == 0xC0 => (first & 0x1F) << 16 | reader.read(16)?,
+                    _ => return Err("Invalid ECI designator".into()),
+                };
+                text.flush();
+                text.eci = Some(value);
+                first_eci.get_or_insert(value);
+            }
+            QrSegmentMode::StructuredAppend => {
+                let index = reader.read(4)? as u8;
+                let total = reader.read(4)? as u8 + 1;
+                let parity = reader.read(8)? as u8;
+                structured_append = Some(StructuredAppend {
+                    index,
+                    total,
+                    parity,
+                });
+            }
+            QrSegmentMode::Fnc1First => gs1 = true,
+            QrSegmentMode::Fnc1Second => {
+                // The application indicator is not part of the data
+                reader.read(8)?;
+            }
+        }
+    }
+
+    text.flush();
+    Ok(Payload {
+        text: text.text,
+        eci: first_eci,
+        gs1,
+        structured_append,
+    })
+}
@@ -0,0 +1,519 @@
+use super::binarizer::BitMatrix;
+
+/// A point in image pixel coordinates.
+#[derive(Clone, Copy, Debug)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    fn distance(self, other: Point) -> f64 {
+        (self.x - other.x).hypot(self.y - other.y)
+    }
+
+    /// Returns the unit vector pointing towards another point.
+    fn direction(self, towards: Point) -> (f64, f64) {
+        let length = self.distance(towards);
+        ((towards.x - self.x) / length, (towards.y - self.y) / length)
+    }
+}
+
+/// A possible finder pattern, located by its center and estimated module size in pixels.
+#[derive(Clone, Copy, Debug)]
+pub struct FinderCandidate {
+    pub center: Point,
+    pub module: f64,
+    /// How many scan lines crossed the pattern, a measure of confidence.
+    count: u32,
+}
+
+/// The three finder patterns of a symbol, in the order top left, top right and bottom left.
+pub struct FinderTriple {
+    pub top_left: FinderCandidate,
+    pub top_right: FinderCandidate,
+    pub bottom_left: FinderCandidate,
+}
+
+impl FinderTriple {
+    /// Estimates the symbol's width in modules from the distance between the finder patterns.
+    pub fn estimate_size(&self, bits: &BitMatrix) -> f64 {
+        let (tl, tr, bl) = (self.top_left, self.top_right, self.bottom_left);
+        let (right, down) = (
+            tl.center.direction(tr.center),
+            tl.center.direction(bl.center),
+        );
+        let across = tl.center.distance(tr.center)
+            / ((tl.module_along(bits, right) + tr.module_along(bits, right)) / 2.0);
+        let down = tl.center.distance(bl.center)
+            / ((tl.module_along(bits, down) + bl.module_along(bits, down)) / 2.0);
+        (across + down) / 2.0 + 7.0
+    }
+
+    /// Reads the two copies of the version information, next to the top right and bottom left
+    /// finder patterns. Their position relative to the finder patterns is the same for all
+    /// versions, so they can be read before the size of the symbol is known.
+    pub fn read_version_bits(&self, bits: &BitMatrix) -> (u32, u32) {
+        let (tl, tr, bl) = (self.top_left, self.top_right, self.bottom_left);
+        let (right, down) = (
+            tl.center.direction(tr.center),
+            tl.center.direction(bl.center),
+        );
+        let read = |finder: FinderCandidate, offset: &dyn Fn(u32) -> (f64, f64)| {
+            let right = finder.step(bits, right);
+            let down = finder.step(bits, down);
+            (0..18).fold(0, |value, i| {
+                let (dx, dy) = offset(i);
+                let x = finder.center.x + dx * right.0 + dy * down.0;
+                let y = finder.center.y + dx * right.1 + dy * down.1;
+                value | u32::from(bits.get(x.floor() as i64, y.floor() as i64)) << i
+            })
+        };
+        let near_top_right = read(tr, &|i| (f64::from(i % 3) - 7.0, f64::from(i / 3) - 3.0));
+        let near_bottom_left = read(bl, &|i| (f64::from(i / 3) - 3.0, f64::from(i % 3) - 7.0));
+        (near_top_right, near_bottom_left)
+    }
+}
+
+impl FinderCandidate {
+    /// Measures the module size along a direction. This is more accurate than the
+    /// horizontal and vertical runs of the scan when the symbol is rotated.
+    fn module_along(&self, bits: &BitMatrix, (dx, dy): (f64, f64)) -> f64 {
+        let limit = self.module * 7.0;
+        let forwards = edge_distance(bits, self.center, (dx, dy), limit);
+        let backwards = edge_distance(bits, self.center, (-dx, -dy), limit);
+        match (forwards, backwards) {
+            (Some(a), Some(b)) => (a + b) / 7.0,
+            _ => self.module,
+        }
+    }
+
+    /// Returns the step of one module, in pixels, along a direction.
+    fn step(&self, bits: &BitMatrix, (dx, dy): (f64, f64)) -> (f64, f64) {
+        let module = self.module_along(bits, (dx, dy));
+        (dx * module, dy * module)
+    }
+}
+
+/// Walks from the center of a finder pattern through its dark center, light ring and dark ring,
+/// and returns the distance to its outer edge, which is 3.5 modules.
+fn edge_distance(bits: &BitMatrix, center: Point, (dx, dy): (f64, f64), limit: f64) -> Option<f64> {
+    let mut transitions = 0;
+    let mut dark = true;
+    let mut t = 0.0;
+    while t <= limit {
+        let (x, y) = (center.x + dx * t, center.y + dy * t);
+        if bits.get(x.floor() as i64, y.floor() as i64) != dark {
+            if t == 0.0 {
+                return None;
+            }
+            transitions += 1;
+            dark = !dark;
+            if transitions == 3 {
+                return Some(t);
+            }
+        }
+        t += 1.0;
+    }
+    None
+}
+
+/// Tests whether five run lengths have the 1:1:3:1:1 proportions of a finder pattern.
+fn is_finder_ratio(counts: &[usize; 5]) -> bool {
+    let total: usize = counts.iter().sum();
+    if total < 7 || counts.contains(&0) {
+        return false;
+    }
+    let module = total as f64 / 7.0;
+    let variance = module / 2.0;
+    counts.iter().enumerate().all(|(i, &count)| {
+        let expected = if i == 2 { 3.0 } else { 1.0 };
+        (count as f64 - module * expected).abs() < variance * expected
+    })
+}
+
+/// Measures the five runs of a finder pattern along a line through a dark pixel.
+/// Returns the run lengths and the offset from the pixel to the center of the middle run,
+/// or None if the line does not cross a pattern shorter than `limit` pixels.
+fn measure_runs(
+    bits: &BitMatrix,
+    x: i64,
+    y: i64,
+    (dx, dy): (i64, i64),
+    limit: usize,
+) -> Option<([usize; 5], f64)> {
+    let inside = |i: i64| {
+        let (px, py) = (x + i * dx, y + i * dy);
+        px >= 0 && py >= 0 && (px as usize) < bits.width && (py as usize) < bits.height
+    };
+    let dark = |i: i64| bits.get(x + i * dx, y + i * dy);
+    if !dark(0) {
+        return None;
+    }
+
+    let mut counts = [0usize; 5];
+    let mut center_runs = [0usize; 2];
+    // Walks from the center outwards through the dark center, light ring and dark ring
+    for (side, (step, runs)) in [(-1, [2, 1, 0]), (1, [2, 3, 4])].into_iter().enumerate() {
+        let mut i = if step < 0 { 0 } else { 1 };
+        for (run, want_dark) in runs.into_iter().zip([true, false, true]) {
+            while inside(i) && dark(i) == want_dark {
+                counts[run] += 1;
+                if counts[run] > limit {
+                    return None;
+                }
+                i += step;
+            }
+        }
+        center_runs[side] = counts[2] - center_runs.iter().sum::<usize>();
+    }
+    let [backwards, forwards] = center_runs;
+    let total: usize = counts.iter().sum();
+    if !is_finder_ratio(&counts) || total > limit {
+        return None;
+    }
+    Some((counts, 1.0 + (forwards as f64 - backwards as f64) / 2.0))
+}
+
+/// Scans the image for finder patterns, the 1:1:3:1:1 dark and light runs
+/// in both directions around the three corners of a QR code.
+pub fn find_finder_patterns(bits: &BitMatrix) -> Vec<FinderCandidate> {
+    let mut candidates: Vec<FinderCandidate> = Vec::new();
+
+    for y in 0..bits.height {
+        let mut counts = [0usize; 5];
+        let mut state = 0;
+        for x in 0..=bits.width {
+            // The end of the row counts as a light pixel to close the last run
+            let dark = x < bits.width && bits.get(x as i64, y as i64);
+            if dark {
+                if state % 2 == 1 {
+                    state += 1;
+                }
+                counts[state] += 1;
+            } else if state % 2 == 1 {
+                counts[state] += 1;
+            } else if state == 4 {
+                if is_finder_ratio(&counts) {
+                    check_candidate(bits, &counts, x, y, &mut candidates);
+                }
+                // Keep the last dark, light and dark runs as the start of the next pattern
+                counts = [counts[2], counts[3], counts[4], 1, 0];
+                state = 3;
+            } else if counts[state] > 0 {
+                state += 1;
+                counts[state] += 1;
+            }
+        }
+    }
+
+    candidates.retain(|c| c.count >= 2);
+    candidates.sort_by_key(|c| std::cmp::Reverse(c.count));
+    candidates
+}
+
+/// Confirms a pattern found on a row by crossing it vertically and horizontally through its
+/// center, and records it in the candidates.
+fn check_candidate(
+    bits: &BitMatrix,
+    counts: &[usize; 5],
+    end: usize,
+    y: usize,
+    candidates: &mut Vec<FinderCandidate>,
+) {
+    let total: usize = counts.iter().sum();
+    let limit = total * 2;
+    let similar = |other: usize| 5 * other.abs_diff(total) < 2 * total;
+
+    let center_x = (end - counts[4] - counts[3]) as f64 - counts[2] as f64 / 2.0;
+    let Some((vertical, offset)) = measure_runs(bits, center_x as i64, y as i64, (0, 1), limit)
+    else {
+        return;
+    };
+    if !similar(vertical.iter().sum()) {
+        return;
+    }
+    let center_y = y as f64 + offset;
+    let Some((horizontal, offset)) =
+        measure_runs(bits, center_x as i64, center_y as i64, (1, 0), limit)
+    else {
+        return;
+    };
+    if !similar(horizontal.iter().sum()) {
+        return;
+    }
+    let center_x = center_x.floor() + offset;
+    let module = (vertical.iter().sum::<usize>() + horizontal.iter().sum::<usize>()) as f64 / 14.0;
+
+    let center = Point {
+        x: center_x,
+        y: center_y,
+    };
+    if let Some(existing) = candidates.iter_mut().find(|c| {
+        c.center.distance(center) <= c.module * 2.0
+            && (c.module - module).abs() <= c.module.max(module) / 2.0
+    }) {
+        let n = f64::from(existing.count);
+        existing.center.x = (existing.center.x * n + center.x) / (n + 1.0);
+        existing.center.y = (existing.center.y * n + center.y) / (n + 1.0);
+        existing.module = (existing.module * n + module) / (n + 1.0);
+        existing.count += 1;
+    } else {
+        candidates.push(FinderCandidate {
+            center,
+            module,
+            count: 1,
+        });
+    }
+}
+
+/// Picks the sets of three candidates that best form the corners of a square symbol,
+/// ordered from the most to the least likely.
+pub fn select_triples(candidates: &[FinderCandidate]) -> Vec<FinderTriple> {
+    let candidates = &candidates[..candidates.len().min(12)];
+    let mut triples: Vec<(f64, FinderTriple)> = Vec::new();
+
+    for i in 0..candidates.len() {
+        for j in i + 1..candidates.len() {
+            for k in j + 1..candidates.len() {
+                let abc = [candidates[i], candidates[j], candidates[k]];
+                let (min, max) = abc.iter().fold((f64::MAX, 0.0f64), |(min, max), c| {
+                    (min.min(c.module), max.max(c.module))
+                });
+                if max > min * 1.5 {
+                    continue;
+                }
+
+                // The top left corner is opposite the longest side
+                let corner = (0..3)
+                    .max_by(|&a, &b| {
+                        let side =
+                            |v: usize| abc[(v + 1) % 3].center.distance(abc[(v + 2) % 3].center);
+                        side(a).total_cmp(&side(b))
+                    })
+                    .unwrap_or(0);
+                let top_left = abc[corner];
+                let (mut top_right, mut bottom_left) =
+                    (abc[(corner + 1) % 3], abc[(corner + 2) % 3]);
+
+                let leg1 = top_left.center.distance(top_right.center);
+                let leg2 = top_left.center.distance(bottom_left.center);
+                let hypotenuse = top_right.center.distance(bottom_left.center);
+                let module = (min + max) / 2.0;
+                if leg1.min(leg2) < module * 12.0 || leg1.max(leg2) > module * 180.0 {
+                    continue;
+                }
+                let score = (leg1 - leg2).abs() / leg1.max(leg2)
+                    + (hypotenuse.powi(2) - leg1.powi(2) - leg2.powi(2)).abs() / hypotenuse.powi(2);
+                if score > 0.5 {
+                    continue;
+                }
+
+                // With the y axis pointing down, top right to bottom left turns clockwise
+                let cross = (top_right.center.x - top_left.center.x)
+                    * (bottom_left.center.y - top_left.center.y)
+                    - (top_right.center.y - top_left.center.y)
+                        * (bottom_left.center.x - top_left.center.x);
+                if cross < 0.0 {
+                    std::mem::swap(&mut top_right, &mut bottom_left);
+                }
+                triples.push((
+                    score,
+                    FinderTriple {
+                        top_left,
+                        top_right,
+                        bottom_left,
+                    },
+                ));
+            }
+        }
+    }
+
+    triples.sort_by(|a, b| a.0.total_cmp(&b.0));
+    triples.into_iter().take(3).map(|(_, t)| t).collect()
+}
+
+/// A projective transform from module coordinates in the symbol to image pixels.
+pub struct Transform([f64; 8]);
+
+impl Transform {
+    /// Maps a point given in modules, where (0, 0) is the top left corner of the symbol.
+    pub fn map(&self, x: f64, y: f64) -> Point {
+        let h = &self.0;
+        let w = h[6] * x + h[7] * y + 1.0;
+        Point {
+            x: (h[0] * x + h[1] * y + h[2]) / w,
+            y: (h[3] * x + h[4] * y + h[5]) / w,
+        }
+    }
+
+    /// Builds the affine transform that places the finder pattern centers of a symbol
+    /// `size` modules wide, assuming no perspective distortion.
+    fn affine(finders: &FinderTriple, size: usize) -> Self {
+        let span = size as f64 - 7.0;
+        let (tl, tr, bl) = (
+            finders.top_left.center,
+            finders.top_right.center,
+            finders.bottom_left.center,
+        );
+        let (ax, ay) = ((tr.x - tl.x) / span, (tr.y - tl.y) / span);
+        let (bx, by) = ((bl.x - tl.x) / span, (bl.y - tl.y) / span);
+        Self([
+            ax,
+            bx,
+            tl.x - 3.5 * (ax + bx),
+            ay,
+            by,
+            tl.y - 3.5 * (ay + by),
+            0.0,
+            0.0,
+        ])
+    }
+
+    /// Solves for the projective transform mapping four module points to four image points.
+    fn from_points(modules: [(f64, f64); 4], image: [Point; 4]) -> Option<Self> {
+        // Each correspondence gives two linear equations in the eight unknowns
+        let mut rows = [[0.0f64; 9]; 8];
+        for (i, ((x, y), p)) in modules.into_iter().zip(image).enumerate() {
+            rows[2 * i] = [x, y, 1.0, 0.0, 0.0, 0.0, -x * p.x, -y * p.x, p.x];
+            rows[2 * i + 1] = [0.0, 0.0, 0.0, x, y, 1.0, -x * p.y, -y * p.y, p.y];
+        }
+
+        // Gaussian elimination with partial pivoting
+        for col in 0..8 {
+            let pivot =
+                (col..8).max_by(|&a, &b| rows[a][col].abs().total_cmp(&rows[b][col].abs()))?;
+            if rows[pivot][col].abs() < 1e-9 {
+                return None;
+            }
+            rows.swap(col, pivot);
+            let pivot_row = rows[col];
+            for (i, row) in rows.iter_mut().enumerate() {
+                if i != col {
+                    let factor = row[col] / pivot_row[col];
+                    for (value, pivot_value) in row.iter_mut().zip(pivot_row).skip(col) {
+                        *value -= factor * pivot_value;
+                    }
+                }
+            }
+        }
+        let mut h = [0.0f64; 8];
+        for (i, value) in h.iter_mut().enumerate() {
+            *value = rows[i][8] / rows[i][i];
+        }
+        Some(Self(h))
+    }
+}
+
+/// Computes the transform for a symbol `size` modules wide. Symbols from version 2 on
+/// have an alignment pattern near the bottom right corner, which gives a fourth point
+/// to correct perspective distortion; otherwise the symbol is taken as a parallelogram.
+pub fn locate(bits: &BitMatrix, finders: &FinderTriple, size: usize) -> Transform {
+    let affine = Transform::affine(finders, size);
+    if size < 25 {
+        return affine;
+    }
+
+    let corner = size as f64 - 6.5;
+    let Some(alignment) = find_alignment_pattern(bits, &affine, corner) else {
+        return affine;
+    };
+    let modules = [
+        (3.5, 3.5),
+        (size as f64 - 3.5, 3.5),
+        (3.5, size as f64 - 3.5),
+        (corner, corner),
+    ];
+    let image = [
+        finders.top_left.center,
+        finders.top_right.center,
+        finders.bottom_left.center,
+        alignment,
+    ];
+    Transform::from_points(modules, image).unwrap_or(affine)
+}
+
+/// Searches around the expected position of the alignment pattern centered at module
+/// (`corner`, `corner`) for the spot where the 5x5 pattern matches best.
+fn find_alignment_pattern(bits: &BitMatrix, affine: &Transform, corner: f64) -> Option<Point> {
+    let expected = affine.map(corner, corner);
+    let origin = affine.map(0.0, 0.0);
+    let step_x = affine.map(1.0, 0.0);
+    let step_y = affine.map(0.0, 1.0);
+    let right = (step_x.x - origin.x, step_x.y - origin.y);
+    let down = (step_y.x - origin.x, step_y.y - origin.y);
+    let module = right.0.hypot(right.1).max(down.0.hypot(down.1));
+
+    let score = |center: Point| {
+        let mut score = 0;
+        for j in -2i32..=2 {
+            for i in -2i32..=2 {
+                let x = center.x + f64::from(i) * right.0 + f64::from(j) * down.0;
+                let y = center.y + f64::from(i) * right.1 + f64::from(j) * down.1;
+                let want_dark = i.abs().max(j.abs()) != 1;
+                if bits.get(x.floor() as i64, y.floor() as i64) == want_dark {
+                    score += 1;
+                }
+            }
+        }
+        score
+    };
+
+    // Coarse search in steps of a third of a module, then every pixel around the best spot.
+    // Perspective moves the pattern further from where the finder patterns place it in
+    // larger symbols, so the search widens until the pattern is found.
+    let step = (module / 3.0).max(1.0);
+    [4.0, 8.0, 16.0].into_iter().find_map(|modules| {
+        let reach = (modules * module / step).ceil() as i32;
+        let (_, coarse) = best_spots(expected, reach, step, &score);
+        let (best, fine) = best_spots(coarse, step.ceil() as i32, 1.0, &score);
+        (best >= 24).then_some(fine)
+    })
+}
+
+/// Scores the points of a square grid around the center, and returns the best score and
+/// the middle of the points that have it. A pattern matches over a small area, and its
+/// middle is the most accurate position.
+fn best_spots(center: Point, reach: i32, step: f64, score: &dyn Fn(Point) -> i32) -> (i32, Point) {
+    let mut best = -1;
+    let mut spots: Vec<Point> = Vec::new();
+    for dy in -reach..=reach {
+        for dx in -reach..=reach {
+            let spot = Point {
+                x: center.x + f64::from(dx) * step,
+                y: center.y + f64::from(dy) * step,
+            };
+            let value = score(spot);
+            if value > best {
+                best = value;
+                spots.clear();
+            }
+            if value == best {
+                spots.push(spot);
+            }
+        }
+    }
+    let n = spots.len() as f64;
+    let (sx, sy) = spots
+        .iter()
+        .fold((0.0, 0.0), |(sx, sy), p| (sx + p.x, sy + p.y));
+    (
+        best,
+        Point {
+            x: sx / n,
+            y: sy / n,
+        },
+    )
+}
+
+/// Reads the modules of a symbol `size` modules wide by sampling the center of each one.
+pub fn sample_grid(bits: &BitMatrix, transform: &Transform, size: usize) -> Vec<bool> {
+    let mut modules = Vec::with_capacity(size * size);
+    for y in 0..size {
+        for x in 0..size {
+            let p = transform.map(x as f64 + 0.5, y as f64 + 0.5);
+            modules.push(bits.get(p.x.floor() as i64, p.y.floor() as i64));
+        }
+    }
+    modules
+}
@@ -0,0 +1,319 @@
+use crate::core::qrgen::{Mask, QrCode, QrCodeEcc, ReedSolomonDecoder, Version};
+use image::DynamicImage;
+use tiny_skia::Pixmap;
+
+mod binarizer;
+mod bitstream;
+mod detector;
+
+use binarizer::{Binarizer, LumaImage};
+use detector::FinderTriple;
+
+/// Length in pixels of the longer side of rasterised SVG documents, enough for the
+/// 177 modules of the largest symbols and their quiet zone at several pixels per module.
+const SVG_RASTER_SIZE: f32 = 2000.0;
+
+/// The result of decoding a QR code from an image.
+#[derive(Debug, Clone)]
+pub struct DecodedQr {
+    /// The decoded text. Byte mode data is read in the character set of the
+    /// ECI designator before it, or as UTF-8 (falling back to ISO-8859-1) without one.
+    pub text: String,
+    pub version: Version,
+    pub ecl: QrCodeEcc,
+    pub mask: Mask,
+    /// The first ECI designator in the data, if any.
+    pub eci: Option<u32>,
+    /// Whether the data starts with an FNC1 indicator, marking a GS1 element string.
+    /// Group separators in the text end variable-length fields.
+    pub gs1: bool,
+    /// Position in a sequence of linked symbols, if the symbol is part of one.
+    pub structured_append: Option<StructuredAppend>,
+    /// Number of codewords that had to be repaired by error correction.
+    pub corrected: usize,
+}
+
+/// The Structured Append header of a symbol in a sequence of linked symbols.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StructuredAppend {
+    /// Position of the symbol in the sequence, starting at 0.
+    pub index: u8,
+    /// Number of symbols in the sequence.
+    pub total: u8,
+    /// Parity of the data of the whole sequence.
+    pub parity: u8,
+}
+
+/// Decodes the QR code in a rendered pixmap.
+/// Only QR codes (Model 2) can be decoded: Micro QR and rMQR codes, which have a
+/// single finder pattern, are reported as an unsupported symbology.
+pub fn decode_pixmap(pixmap: &Pixmap) -> Result<DecodedQr, String> {
+    decode_luma(&LumaImage::from_pixmap(pixmap))
+}
+
+/// Decodes the QR code in an image.
+pub fn decode_image(image: &DynamicImage) -> Result<DecodedQr, String> {
+    decode_luma(&LumaImage::from_image(image))
+}
+
+/// Decodes the QR code in an SVG document, rasterised so that its longer side is
+/// `SVG_RASTER_SIZE` pixels, whatever its declared size.
+pub fn decode_svg(data: &[u8]) -> Result<DecodedQr, String> {
+    let tree = resvg::usvg::Tree::from_data(data, &resvg::usvg::Options::default())
+        .map_err(|e| format!("Invalid SVG: {}", e))?;
+    let size = tree.size();
+    let longer = size.width().max(size.height());
+    if !longer.is_finite() || longer <= 0.0 {
+        return Err("SVG has no size to rasterise".into());
+    }
+    let scale = SVG_RASTER_SIZE / longer;
+    let mut pixmap = Pixmap::new(
+        (size.width() * scale).ceil().max(1.0) as u32,
+        (size.height() * scale).ceil().max(1.0) as u32,
+    )
+    .ok_or("SVG is too large to rasterise")?;
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+    decode_pixmap(&pixmap)
+}
+
+/// Tries each binarizer, on dark-on-light and then on light-on-dark codes,
+/// and returns the first successful decoding.
+fn decode_luma(image: &LumaImage) -> Result<DecodedQr, String> {
+    let mut last_error = None;
+    let mut lone_finder = false;
+    for inverted in [false, true] {
+        for binarizer in [Binarizer::Global, Binarizer::Local] {
+            let mut bits = binarizer.binarize(image);
+            if inverted {
+                bits.invert();
+            }
+            let candidates = detector::find_finder_patterns(&bits);
+            lone_finder |= candidates.len() == 1;
+            for finders in detector::select_triples(&candidates) {
+                match decode_symbol(&bits, &finders) {
+                    Ok(decoded) => return Ok(decoded),
+                    Err(e) => last_error = Some(e),
+                }
+            }
+        }
+    }
+
+    Err(match last_error {
+        Some(e) => format!("Found a QR code but could not decode it: {}", e),
+        None if lone_finder => "Found a single finder pattern, as in Micro QR and rMQR codes: \
+            these symbologies are not supported, only QR codes can be decoded"
+            .into(),
+        None => "No QR code found in the image".into(),
+    })
+}
+
+/// Decodes the symbol located by the finder patterns. The version estimated from
+/// their spacing may be off by one or two, so the nearby versions are tried as well,
+/// after the version read from the symbol itself, from version 7 on.
+fn decode_symbol(bits: &binarizer::BitMatrix, finders: &FinderTriple) -> Result<DecodedQr, String> {
+    let estimate = ((finders.estimate_size(bits) - 17.0) / 4.0).round() as i32;
+    let (first, second) = finders.read_version_bits(bits);
+    let mut versions: Vec<u8> = bitstream::decode_version(first, second)
+        .into_iter()
+        .collect();
+    for delta in [0, -1, 1, -2, 2] {
+        let version = estimate + delta;
+        if (1..=40).contains(&version) && !versions.contains(&(version as u8)) {
+            versions.push(version as u8);
+        }
+    }
+
+    let mut last_error = String::from("Invalid symbol size");
+    for version in versions {
+        let version = Version::new(version);
+        let size = usize::from(version.value()) * 4 + 17;
+        let transform = detector::locate(bits, finders, size);
+        let modules = detector::sample_grid(bits, &transform, size);
+        match decode_modules(&modules, version) {
+            Ok(decoded) => return Ok(decoded),
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+/// Decodes the sampled modules of a symbol of the given version.
+fn decode_modules(modules: &[bool], version: Version) -> Result<DecodedQr, String> {
+    let size = usize::from(version.value()) * 4 + 17;
+    let (ecl, mask) = bitstream::read_format(modules, size)?;
+    let mask = Mask::new(mask);
+
+    let (blocks, ecc_len) = QrCode::read_codeword_blocks(version, ecl, mask, modules);
+    let decoder = ReedSolomonDecoder::new(ecc_len);
+    let mut data = Vec::new();
+    let mut corrected = 0;
+    for mut block in blocks {
+        corrected += decoder
+            .decode(&mut block, &[])
+            .map_err(|e| e.to_string())?
+            .len();
+        data.extend_from_slice(&block[..block.len() - ecc_len]);
+    }
+
+    let payload = bitstream::parse(&data, version)?;
+    Ok(DecodedQr {
+        text: payload.text,
+        version,
+        ecl,
+        mask,
+        eci: payload.eci,
+        gs1: payload.gs1,
+        structured_append: payload.structured_append,
+        corrected,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::qrgen::{MicroQrCode, MicroVersion, QrSegment};
+    use crate::core::renderer::png::render_qr;
+    use crate::core::renderer::svg::render_svg;
+    use crate::core::rmqr::RmqrCode;
+    use crate::models::QrConfig;
+
+    const ECLS: [QrCodeEcc; 4] = [
+        QrCodeEcc::Low,
+        QrCodeEcc::Medium,
+        QrCodeEcc::Quartile,
+        QrCodeEcc::High,
+    ];
+
+    fn round_trip(segs: &[QrSegment], version: u8, ecl: QrCodeEcc, mask: u8) -> DecodedQr {
+        let version = Version::new(version);
+        let qr = QrCode::encode_segments_advanced(
+            segs,
+            ecl,
+            version,
+            version,
+            Some(Mask::new(mask)),
+            false,
+        )
+        .unwrap();
+        let pixmap = render_qr(&qr, &QrConfig::default(), 4.0).unwrap();
+        let decoded = decode_pixmap(&pixmap).unwrap();
+        assert_eq!(decoded.version, version);
+        assert_eq!(decoded.ecl, ecl);
+        assert_eq!(decoded.mask, Mask::new(mask));
+        assert_eq!(decoded.corrected, 0);
+        decoded
+    }
+
+    #[test]
+    fn round_trips_versions_and_levels() {
+        for (i, version) in [1, 2, 6, 7, 14, 22, 32, 40].into_iter().enumerate() {
+            for ecl in ECLS {
+                let decoded = round_trip(
+                    &QrSegment::make_segments("HELLO 42"),
+                    version,
+                    ecl,
+                    i as u8 % 8,
+                );
+                assert_eq!(decoded.text, "HELLO 42");
+            }
+        }
+    }
+
+    #[test]
+    fn round_trips_masks() {
+        for mask in 0..8 {
+            let decoded = round_trip(
+                &QrSegment::make_segments("https://example.com/?q=mask"),
+                3,
+                QrCodeEcc::Medium,
+                mask,
+            );
+            assert_eq!(decoded.text, "https://example.com/?q=mask");
+        }
+    }
+
+    #[test]
+    fn round_trips_segment_modes() {
+        let cases = [
+            (vec![QrSegment::make_numeric("0123456789")], "0123456789"),
+            (
+                vec![QrSegment::make_alphanumeric("AB-12 $%*+./:")],
+                "AB-12 $%*+./:",
+            ),
+            (vec![QrSegment::make_bytes("héllo".as_bytes())], "héllo"),
+            (vec![QrSegment::make_kanji("漢字テスト")], "漢字テスト"),
+            (
+                vec![
+                    QrSegment::make_alphanumeric("ABC"),
+                    QrSegment::make_numeric("1234567"),
+                    QrSegment::make_bytes(b"xyz"),
+                ],
+                "ABC1234567xyz",
+            ),
+        ];
+        for (segs, text) in cases {
+            assert_eq!(round_trip(&segs, 4, QrCodeEcc::Quartile, 5).text, text);
+        }
+    }
+
+    #[test]
+    fn round_trips_eci_and_structured_append() {
+        let segs = [
+            QrSegment::make_structured_append(1, 3, 0x5A),
+            QrSegment::make_eci(3),
+            QrSegment::make_bytes(&[0x63, 0x61, 0x66, 0xE9]),
+        ];
+        let decoded = round_trip(&segs, 2, QrCodeEcc::Low, 2);
+        assert_eq!(decoded.text, "café");
+        assert_eq!(decoded.eci, Some(3));
+        assert_eq!(
+            decoded.structured_append,
+            Some(StructuredAppend {
+                index: 1,
+                total: 3,
+                parity: 0x5A,
+            })
+        );
+    }
+
+    #[test]
+    fn rasterises_svg_at_a_bounded_size() {
+        let qr = QrCode::encode_text("HELLO SVG", QrCodeEcc::Medium).unwrap();
+        let svg = render_svg(&qr, &QrConfig::default(), 25.0).unwrap();
+        let side = (qr.size() + 8) * 25;
+        let declared = format!(r#"width="{0}" height="{0}""#, side);
+        assert!(svg.contains(&declared));
+        for size in [20, 100_000] {
+            let resized = svg.replace(&declared, &format!(r#"width="{0}" height="{0}""#, size));
+            assert_eq!(decode_svg(resized.as_bytes()).unwrap().text, "HELLO SVG");
+        }
+    }
+
+    #[test]
+    fn rejects_micro_and_rmqr_codes() {
+        let segs = QrSegment::make_segments("HELLO");
+        let micro = MicroQrCode::encode_segments_advanced(
+            &segs,
+            QrCodeEcc::Low,
+            MicroVersion::MIN,
+            MicroVersion::MAX,
+            None,
+            false,
+        )
+        .unwrap();
+        let rmqr =
+            RmqrCode::encode_segments_advanced(&segs, QrCodeEcc::Medium, None, false).unwrap();
+        let config = QrConfig::default();
+        for pixmap in [
+            render_qr(&micro, &config, 4.0).unwrap(),
+            render_qr(&rmqr, &config, 4.0).unwrap(),
+        ] {
+            let error = decode_pixmap(&pixmap).unwrap_err();
+            assert!(error.contains("not supported"), "{}", error);
+        }
+    }
+}
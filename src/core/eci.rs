@@ -0,0 +1,117 @@
+//! Extended Channel Interpretation (ECI) character sets.
+//!
+//! An ECI designator tells scanners which character set the byte mode data uses.
+//! Without one, scanners are supposed to assume ISO-8859-1.
+
+use encoding_rs::Encoding;
+
+/// ECI assignment value designating Shift JIS, the character set of kanji mode.
+pub const SHIFT_JIS: u32 = 20;
+
+/// ECI assignment value designating UTF-8.
+pub const UTF8: u32 = 26;
+
+/// Largest ECI assignment value that can be encoded in a designator.
+pub const MAX: u32 = 999_999;
+
+/// ECI assignment values and the labels of the character sets they designate.
+/// ECI 3 comes before ECI 1 so that ISO-8859-1 resolves to the current designator.
+static CHARSETS: [(u32, &str); 28] = [
+    (3, "iso-8859-1"),
+    (1, "iso-8859-1"),
+    (4, "iso-8859-2"),
+    (5, "iso-8859-3"),
+    (6, "iso-8859-4"),
+    (7, "iso-8859-5"),
+    (8, "iso-8859-6"),
+    (9, "iso-8859-7"),
+    (10, "iso-8859-8"),
+    (11, "iso-8859-9"),
+    (12, "iso-8859-10"),
+    (13, "iso-8859-11"),
+    (15, "iso-8859-13"),
+    (16, "iso-8859-14"),
+    (17, "iso-8859-15"),
+    (18, "iso-8859-16"),
+    (20, "shift_jis"),
+    (21, "windows-1250"),
+    (22, "windows-1251"),
+    (23, "windows-1252"),
+    (24, "windows-1256"),
+    (25, "utf-16be"),
+    (26, "utf-8"),
+    (27, "us-ascii"),
+    (28, "big5"),
+    (29, "gb2312"),
+    (30, "euc-kr"),
+    (32, "gb18030"),
+];
+
+/// Returns the ECI assignment value for a character set label, ignoring case.
+/// Common aliases like "latin1", "utf8" or "sjis" are accepted as well.
+pub fn from_label(label: &str) -> Option<u32> {
+    let label = label.trim().to_ascii_lowercase();
+    let canonical = match label.as_str() {
+        "latin1" | "iso8859-1" | "iso_8859-1" => "iso-8859-1",
+        "utf8" => "utf-8",
+        "sjis" | "shift-jis" => "shift_jis",
+        "ascii" => "us-ascii",
+        "utf-16" | "utf16be" => "utf-16be",
+        "gbk" => "gb2312",
+        other => other,
+    };
+    CHARSETS
+        .iter()
+        .find(|(_, name)| *name == canonical)
+        .map(|(eci, _)| *eci)
+}
+
+/// Returns the label of the character set designated by the ECI assignment value, if known.
+pub fn label(eci: u32) -> Option<&'static str> {
+    CHARSETS
+        .iter()
+        .find(|(value, _)| *value == eci)
+        .map(|(_, name)| *name)
+}
+
+/// Encodes the text in the character set designated by the ECI assignment value.
+/// Text for unknown values is kept as UTF-8.
+/// Returns None if the text has characters the character set cannot represent.
+pub fn encode(text: &str, eci: u32) -> Option<Vec<u8>> {
+    match label(eci) {
+        None | Some("utf-8") => Some(text.as_bytes().to_vec()),
+        // encoding_rs treats ISO-8859-1 as windows-1252, so the mapping is done by hand
+        Some("iso-8859-1") => text.chars().map(|c| u8::try_from(c).ok()).collect(),
+        Some("us-ascii") => text
+            .chars()
+            .map(|c| c.is_ascii().then_some(c as u8))
+            .collect(),
+        // encoding_rs only decodes UTF-16
+        Some("utf-16be") => Some(text.encode_utf16().flat_map(u16::to_be_bytes).collect()),
+        Some(name) => {
+            let encoding = Encoding::for_label(name.as_bytes())?;
+            let (bytes, _, unmappable) = encoding.encode(text);
+            (!unmappable).then(|| bytes.into_owned())
+        }
+    }
+}
+
+/// Decodes bytes from the character set designated by the ECI assignment value.
+/// Bytes for unknown values are read as UTF-8.
+/// Returns None if the bytes are not valid in the character set.
+pub fn decode(bytes: &[u8], eci: u32) -> Option<String> {
+    match label(eci) {
+        None | Some("utf-8") => String::from_utf8(bytes.to_vec()).ok(),
+        Some("iso-8859-1") => Some(bytes.iter().map(|&b| char::from(b)).collect()),
+        Some("us-ascii") => bytes
+            .iter()
+            .map(|&b| b.is_ascii().then_some(char::from(b)))
+            .collect(),
+        Some(name) => {
+            let encoding = Encoding::for_label(name.as_bytes())?;
+            encoding
+                .decode_without_bom_handling_and_without_replacement(bytes)
+                .map(|text| text.into_owned())
+        }
+    }
+}
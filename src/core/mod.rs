@@ -1,51 +1,466 @@
 use crate::core::renderer::{
-    QrRenderer, eps::EpsRenderer, pdf::PdfRenderer, png::PngRenderer, svg::SvgRenderer,
+    QrGrid, QrRenderer, eps::EpsRenderer, icon::ExcavatedGrid, pdf::PdfRenderer, png::PngRenderer,
+    svg::SvgRenderer,
 };
 use crate::{
-    core::qrgen::{Mask, QrCode, QrSegment, Version},
-    models::{OutputFormat, QrItem},
+    core::analysis::{IconCoverage, ScanReport},
+    core::qrgen::{
+        Mask, MicroQrCode, MicroVersion, QrCode, QrSegment, QrSegmentMode, SegmentOptions, Version,
+    },
+    core::rmqr::{RMQR_HEIGHTS, RmqrCode},
+    models::{Charset, Excavation, OutputFormat, QrConfig, QrItem, Symbology},
 };
 
+pub mod analysis;
+pub mod decoder;
+pub mod eci;
 pub mod qrgen;
 pub mod renderer;
+pub mod rmqr;
 
 pub use qrgen::QrCodeEcc;
 
 /// Generates a QR code from the given data and configuration.
 /// Returns the appropriate renderer containing the generated QR code.
 pub fn generate_qr<T: QrItem>(item: &T) -> Result<Box<dyn QrRenderer>, String> {
+    check_item(item)?;
     let content = item.to_string();
     let config = item.config();
 
-    let segments = QrSegment::make_segments(&content);
+    match config.symbology {
+        Symbology::Qr => {
+            let qr = encode_qr_code(&content, config, item.is_gs1());
 
-    let qr = QrCode::encode_segments_advanced(
-        &segments,
+            let qr = match qr {
+                Ok(code) => code,
+                Err(e) => return Err(format!("Failed to generate QR code: {}", e)),
+            };
+
+            build_renderer(&qr, config)
+        }
+        Symbology::Micro => build_renderer(&encode_micro_qr_code(&content, config)?, config),
+        Symbology::Rmqr => build_renderer(&encode_rmqr_code(&content, config)?, config),
+    }
+}
+
+/// Generates a sequence of linked QR codes (Structured Append) from the given data and configuration.
+/// The content is split into as few symbols as possible, up to 16, each no larger than the
+/// configured maximum version. Content that fits in a single symbol yields a single regular QR code.
+/// Returns one renderer per symbol, in sequence order.
+pub fn generate_qr_sequence<T: QrItem>(item: &T) -> Result<Vec<Box<dyn QrRenderer>>, String> {
+    let config = item.config();
+    encode_qr_sequence(item)?
+        .iter()
+        .map(|qr| build_renderer(qr, config))
+        .collect()
+}
+
+/// Encodes the item as a Structured Append sequence, see `generate_qr_sequence`.
+fn encode_qr_sequence<T: QrItem>(item: &T) -> Result<Vec<QrCode>, String> {
+    check_item(item)?;
+    let content = item.to_string();
+    let config = item.config();
+
+    if config.symbology != Symbology::Qr {
+        return Err("Structured Append is only supported for regular QR codes".into());
+    }
+    if item.is_gs1() {
+        return Err("Structured Append is not supported for GS1 data".into());
+    }
+
+    let max_version = config.max_version.map(Version::new).unwrap_or(Version::MAX);
+    let mut last_error = match encode_qr_code(&content, config, false) {
+        Ok(qr) => return Ok(vec![qr]),
+        Err(e) => e,
+    };
+
+    let icon = analysis::icon_aspect_ratio(config);
+    let chars: Vec<char> = content.chars().collect();
+    for total in 2..=16usize {
+        if total > chars.len() {
+            break;
+        }
+        // Split into parts of (almost) equal length, always on character boundaries
+        let parts: Result<Vec<Vec<QrSegment>>, String> = (0..total)
+            .map(|i| {
+                let part: String = chars[i * chars.len() / total..(i + 1) * chars.len() / total]
+                    .iter()
+                    .collect();
+                make_qr_segments(&part, config, false, true)
+            })
+            .collect();
+        let parts = match parts {
+            Ok(parts) => parts,
+            Err(e) => {
+                last_error = e;
+                continue;
+            }
+        };
+        // The parity covers the message as encoded, in whichever modes and character sets
+        let message: Vec<u8> = parts
+            .iter()
+            .flatten()
+            .flat_map(QrSegment::message_bytes)
+            .collect();
+        let parity = QrSegment::structured_append_parity(&message);
+
+        // With an icon, every symbol needs enough error correction to recover what it hides
+        for ecl in ecc_levels(config, icon.is_some()) {
+            match QrCode::encode_structured_append(
+                &parts,
+                parity,
+                ecl,
+                Version::MIN,
+                max_version,
+                config.mask.map(Mask::new),
+                config.boost_error_correction,
+            ) {
+                Ok(codes)
+                    if icon.is_none_or(|aspect| {
+                        codes.iter().all(|qr| {
+                            IconCoverage::new(
+                                qr.version(),
+                                qr.error_correction_level(),
+                                aspect,
+                                config,
+                            )
+                            .is_recoverable()
+                        })
+                    }) =>
+                {
+                    return Ok(codes);
+                }
+                Ok(_) => last_error = icon_error(config),
+                Err(e) => {
+                    last_error = e.to_string();
+                    break;
+                }
+            }
+        }
+    }
+
+    Err(format!(
+        "Failed to generate QR code sequence, the data does not fit in 16 symbols: {}",
+        last_error
+    ))
+}
+
+/// Estimates how well the code generated from the item will scan once styled.
+/// Fails like generation when the content does not fit in a single symbol.
+pub fn analyze_qr<T: QrItem>(item: &T) -> Result<ScanReport, String> {
+    check_item(item)?;
+    let content = item.to_string();
+    let config = item.config();
+    Ok(match config.symbology {
+        Symbology::Qr => analyze_qr_code(&encode_qr_code(&content, config, item.is_gs1())?, config),
+        Symbology::Micro => {
+            analysis::analyze(&encode_micro_qr_code(&content, config)?, None, config)
+        }
+        Symbology::Rmqr => analysis::analyze(&encode_rmqr_code(&content, config)?, None, config),
+    })
+}
+
+/// Estimates how well each symbol of the sequence generated from the item by
+/// `generate_qr_sequence` will scan once styled.
+pub fn analyze_qr_sequence<T: QrItem>(item: &T) -> Result<Vec<ScanReport>, String> {
+    Ok(encode_qr_sequence(item)?
+        .iter()
+        .map(|qr| analyze_qr_code(qr, item.config()))
+        .collect())
+}
+
+/// Analyses a regular QR code, including the codewords hidden by the icon.
+fn analyze_qr_code(qr: &QrCode, config: &QrConfig) -> ScanReport {
+    let coverage = analysis::icon_aspect_ratio(config)
+        .map(|aspect| IconCoverage::new(qr.version(), qr.error_correction_level(), aspect, config));
+    analysis::analyze(qr, coverage.as_ref(), config)
+}
+
+/// Validates the item's data and checks that its options can be combined.
+fn check_item<T: QrItem>(item: &T) -> Result<(), String> {
+    item.validate()?;
+    let config = item.config();
+    let eci = matches!(config.charset, Charset::Utf8 | Charset::Eci(_));
+
+    if config.symbology != Symbology::Qr && eci {
+        return Err("ECI designators are only supported by regular QR codes".into());
+    }
+    if item.is_gs1() && config.symbology != Symbology::Qr {
+        return Err("GS1 data is only supported by regular QR codes".into());
+    }
+    if config.symbology != Symbology::Qr && (config.icon.is_some() || config.image.is_some()) {
+        return Err("Icons are only supported by regular QR codes".into());
+    }
+    if item.is_gs1() && eci {
+        return Err("GS1 QR codes cannot use ECI designators".into());
+    }
+    if !(0.0..=10.0).contains(&config.icon_padding) {
+        return Err(format!(
+            "Icon padding must be between 0 and 10 modules, got {}",
+            config.icon_padding
+        ));
+    }
+    if !(0.05..=0.5).contains(&config.icon_size) {
+        return Err(format!(
+            "Icon size must be between 0.05 and 0.5, got {}",
+            config.icon_size
+        ));
+    }
+    if !(0.0..=10.0).contains(&config.icon_radius) {
+        return Err(format!(
+            "Icon radius must be between 0 and 10 modules, got {}",
+            config.icon_radius
+        ));
+    }
+    let corner_styles = [
+        &config.finder_top_left,
+        &config.finder_top_right,
+        &config.finder_bottom_left,
+        &config.finder_bottom_right,
+    ];
+    for style in corner_styles.into_iter().flatten() {
+        if style.rotate % 90 != 0 || style.rotate >= 360 {
+            return Err(format!(
+                "Finder rotation must be 0, 90, 180 or 270, got {}",
+                style.rotate
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Encodes the content as a single regular QR code, using the optimal mix of segment modes.
+/// With an icon, the lowest error correction level from the configured one up that can still
+/// recover the codewords hidden by the icon is chosen, in a larger version only if needed.
+fn encode_qr_code(content: &str, config: &QrConfig, gs1: bool) -> Result<QrCode, String> {
+    let max_version = config.max_version.map(Version::new).unwrap_or(Version::MAX);
+    let segments = make_qr_segments(content, config, gs1, false)?;
+    let encode = |ecl: QrCodeEcc, min_version: Version| {
+        QrCode::encode_segments_advanced(
+            &segments,
+            ecl,
+            min_version,
+            max_version,
+            config.mask.map(Mask::new),
+            config.boost_error_correction,
+        )
+        .map_err(|e| e.to_string())
+    };
+
+    let smallest = encode(config.ecl, Version::MIN)?;
+    let Some(aspect) = analysis::icon_aspect_ratio(config) else {
+        return Ok(smallest);
+    };
+
+    // The smallest symbol holding the data at each level, which also fits in all larger versions
+    let mut candidates = vec![smallest];
+    for &ecl in &ecc_levels(config, true)[1..] {
+        match encode(ecl, Version::MIN) {
+            Ok(qr) => candidates.push(qr),
+            Err(_) => break,
+        }
+    }
+    for version in candidates[0].version().value()..=max_version.value() {
+        let version = Version::new(version);
+        for candidate in &candidates {
+            let ecl = candidate.error_correction_level();
+            if candidate.version() > version
+                || !IconCoverage::new(version, ecl, aspect, config).is_recoverable()
+            {
+                continue;
+            }
+            let qr = if candidate.version() == version {
+                candidate.clone()
+            } else {
+                encode(ecl, version)?
+            };
+            // Boosting may have raised the level further, which has its own block structure
+            if IconCoverage::new(qr.version(), qr.error_correction_level(), aspect, config)
+                .is_recoverable()
+            {
+                return Ok(qr);
+            }
+        }
+    }
+
+    Err(icon_error(config))
+}
+
+/// Encodes the content as a Micro QR code, in the smallest version that holds it.
+fn encode_micro_qr_code(content: &str, config: &QrConfig) -> Result<MicroQrCode, String> {
+    if config.ecl == QrCodeEcc::High {
+        return Err("Micro QR codes do not support the high error correction level".into());
+    }
+    if let Some(version) = config.max_version
+        && version > MicroVersion::MAX.value()
+    {
+        return Err(format!(
+            "Micro QR code version must be between 1 and 4, got {}",
+            version
+        ));
+    }
+    if let Some(mask) = config.mask
+        && mask > 3
+    {
+        return Err(format!(
+            "Micro QR code mask must be between 0 and 3, got {}",
+            mask
+        ));
+    }
+
+    MicroQrCode::encode_segments_advanced(
+        &QrSegment::make_segments(content),
         config.ecl,
-        Version::MIN,
-        if let Some(version) = config.max_version {
-            Version::new(version)
-        } else {
-            Version::MAX
-        },
-        if let Some(mask) = config.mask {
-            Some(Mask::new(mask))
-        } else {
-            None
-        },
+        MicroVersion::MIN,
+        config
+            .max_version
+            .map(MicroVersion::new)
+            .unwrap_or(MicroVersion::MAX),
+        config.mask.map(Mask::new),
         config.boost_error_correction,
-    );
+    )
+    .map_err(|e| format!("Failed to generate Micro QR code: {}", e))
+}
+
+/// Encodes the content as an rMQR code, in the smallest size that holds it.
+fn encode_rmqr_code(content: &str, config: &QrConfig) -> Result<RmqrCode, String> {
+    if let Some(height) = config.rmqr_height
+        && !RMQR_HEIGHTS.contains(&height)
+    {
+        return Err(format!(
+            "rMQR code height must be one of 7, 9, 11, 13, 15 or 17, got {}",
+            height
+        ));
+    }
+    if config.max_version.is_some() {
+        return Err(
+            "rMQR codes have no version numbers, use the height to limit their size".into(),
+        );
+    }
+    if config.mask.is_some() {
+        return Err("rMQR codes always use the same mask, it cannot be chosen".into());
+    }
 
-    let qr = match qr {
-        Ok(code) => code,
-        Err(e) => return Err(format!("Failed to generate QR code: {}", e)),
+    RmqrCode::encode_segments_advanced(
+        &QrSegment::make_segments(content),
+        config.ecl,
+        config.rmqr_height,
+        config.boost_error_correction,
+    )
+    .map_err(|e| format!("Failed to generate rMQR code: {}", e))
+}
+
+/// Error correction levels to try, from the configured one up to the highest when `raise` is set.
+fn ecc_levels(config: &QrConfig, raise: bool) -> Vec<QrCodeEcc> {
+    [
+        QrCodeEcc::Low,
+        QrCodeEcc::Medium,
+        QrCodeEcc::Quartile,
+        QrCodeEcc::High,
+    ]
+    .into_iter()
+    .filter(|&ecl| ecl == config.ecl || (raise && ecl > config.ecl))
+    .collect()
+}
+
+/// The error for an icon that hides more codewords than any allowed error correction level
+/// and version can recover.
+fn icon_error(config: &QrConfig) -> String {
+    format!(
+        "The icon hides more codewords than error correction can recover at the {:?} level or \
+         above{}, use a smaller icon or less data",
+        config.ecl,
+        config
+            .max_version
+            .map(|v| format!(" up to version {}", v))
+            .unwrap_or_default()
+    )
+}
+
+/// Splits the content into the optimal mix of segments for a regular QR code,
+/// starting with an ECI designator when the configured character set calls for one,
+/// or with an FNC1 indicator for GS1 element strings. For a part of a Structured Append
+/// sequence, the segments are chosen to fit with the header that is added before them.
+fn make_qr_segments(
+    content: &str,
+    config: &QrConfig,
+    gs1: bool,
+    sequence: bool,
+) -> Result<Vec<QrSegment>, String> {
+    let max_version = config.max_version.map(Version::new).unwrap_or(Version::MAX);
+    let make_segments = |eci_value: Option<u32>| {
+        let encode_bytes = |text: &str| match eci_value {
+            Some(value) => eci::encode(text, value).unwrap_or_default(),
+            None => text.as_bytes().to_vec(),
+        };
+        // The header takes as many bits whatever its values, which are only known later
+        let header = sequence.then(|| QrSegment::make_structured_append(0, 2, 0));
+        let options = SegmentOptions {
+            prefix: header
+                .into_iter()
+                .chain(eci_value.map(QrSegment::make_eci))
+                .chain(gs1.then(QrSegment::make_fnc1_first))
+                .collect(),
+            encodebytes: &encode_bytes,
+            allowkanji: eci_value.is_none_or(|value| value == eci::SHIFT_JIS),
+            fnc1: gs1,
+        };
+        QrSegment::make_segments_optimally_with(
+            content,
+            config.ecl,
+            Version::MIN,
+            max_version,
+            &options,
+        )
+        .map(|segments| segments[usize::from(sequence)..].to_vec())
     };
 
+    let segments = match config.charset {
+        Charset::None => make_segments(None),
+        Charset::Utf8 => make_segments(Some(eci::UTF8)),
+        Charset::Auto => {
+            let segments = make_segments(None).map_err(|e| e.to_string())?;
+            if segments.iter().any(has_non_ascii_bytes) {
+                make_segments(Some(eci::UTF8))
+            } else {
+                Ok(segments)
+            }
+        }
+        Charset::Eci(value) => {
+            if eci::encode(content, value).is_none() {
+                return Err(format!(
+                    "The text cannot be represented in the {} character set",
+                    eci::label(value).unwrap_or("selected")
+                ));
+            }
+            make_segments(Some(value))
+        }
+    };
+
+    segments.map_err(|e| e.to_string())
+}
+
+/// Tests whether the segment holds byte mode data outside of ASCII,
+/// which scanners would read as ISO-8859-1 without an ECI designator.
+fn has_non_ascii_bytes(segment: &QrSegment) -> bool {
+    segment.mode() == QrSegmentMode::Byte && segment.data().chunks(8).any(|byte| byte[0])
+}
+
+/// Creates the renderer matching the configured output format for the given grid.
+fn build_renderer(grid: &dyn QrGrid, config: &QrConfig) -> Result<Box<dyn QrRenderer>, String> {
+    let excavated;
+    let grid = match analysis::icon_aspect_ratio(config) {
+        Some(aspect) if config.excavate != Excavation::None => {
+            excavated = ExcavatedGrid::new(grid, aspect, config);
+            &excavated as &dyn QrGrid
+        }
+        _ => grid,
+    };
     let renderer: Box<dyn QrRenderer> = match config.format {
-        OutputFormat::Svg => Box::new(SvgRenderer::new(&qr, config)?),
-        OutputFormat::Eps => Box::new(EpsRenderer::new(&qr, config)?),
-        OutputFormat::Pdf => Box::new(PdfRenderer::new(&qr, config)?),
-        _ => Box::new(PngRenderer::new(&qr, config)?),
+        OutputFormat::Svg => Box::new(SvgRenderer::new(grid, config)?),
+        OutputFormat::Eps => Box::new(EpsRenderer::new(grid, config)?),
+        OutputFormat::Pdf => Box::new(PdfRenderer::new(grid, config)?),
+        _ => Box::new(PngRenderer::new(grid, config)?),
     };
 
     Ok(renderer)
@@ -53,7 +53,7 @@
 //! 
 //! # Examples
 //! 
-//! ```
+//! ```ignore
 //! extern crate qrcodegen;
 //! use qrcodegen::Mask;
 //! use qrcodegen::QrCode;
@@ -64,7 +64,7 @@
 //! 
 //! Simple operation:
 //! 
-//! ```
+//! ```ignore
 //! let qr = QrCode::encode_text("Hello, world!",
 //!     QrCodeEcc::Medium).unwrap();
 //! let svg = to_svg_string(&qr, 4);  // See qrcodegen-demo
@@ -72,7 +72,7 @@
 //! 
 //! Manual operation:
 //! 
-//! ```
+//! ```ignore
 //! let text: &str = "3141592653589793238462643383";
 //! let segs = QrSegment::make_segments(text);
 //! let qr = QrCode::encode_segments_advanced(&segs, QrCodeEcc::High,
@@ -88,6 +88,8 @@
 #![forbid(unsafe_code)]
 use std::convert::TryFrom;
 
+use encoding_rs::SHIFT_JIS;
+
 use clap::ValueEnum;
 
 
@@ -277,6 +279,44 @@ impl QrCode {
 	}
 	
 	
+	/// Returns a Structured Append sequence of QR Codes, one for each of the given parts,
+	/// with the given encoding parameters. Each part is a list of segments holding one
+	/// piece of the message, and parity is the `QrSegment::structured_append_parity()`
+	/// of the whole message. A Structured Append header is prepended to every part.
+	/// 
+	/// Each symbol gets the smallest version within the given range that fits its part,
+	/// as in `encode_segments_advanced()`.
+	/// 
+	/// Returns `Err` without encoding anything if any part is too long to fit in the largest
+	/// version at the given ECC level. Panics if there are more than 16 parts or none at all.
+	pub fn encode_structured_append(parts: &[Vec<QrSegment>], parity: u8, ecl: QrCodeEcc,
+			minversion: Version, maxversion: Version, mask: Option<Mask>, boostecl: bool)
+			-> Result<Vec<Self>,DataTooLong> {
+		
+		assert!((1 ..= 16).contains(&parts.len()), "Structured Append supports 1 to 16 symbols");
+		let total = parts.len() as u8;
+		let symbolsegs: Vec<Vec<QrSegment>> = parts.iter().enumerate().map(|(i, part)| {
+			let mut segs = vec![QrSegment::make_structured_append(i as u8, total, parity)];
+			segs.extend_from_slice(part);
+			segs
+		}).collect();
+		
+		// Check that every part fits before spending time on masking
+		let datacapacitybits: usize = QrCode::get_num_data_codewords(maxversion, ecl) * 8;
+		for segs in &symbolsegs {
+			match QrSegment::get_total_bits(segs, maxversion) {
+				None => return Err(DataTooLong::SegmentTooLong),
+				Some(n) if n > datacapacitybits => return Err(DataTooLong::DataOverCapacity(n, datacapacitybits)),
+				Some(_) => {},
+			}
+		}
+		
+		symbolsegs.iter().map(|segs|
+			QrCode::encode_segments_advanced(segs, ecl, minversion, maxversion, mask, boostecl)
+		).collect()
+	}
+	
+	
 	/*---- Constructor (low level) ----*/
 	
 	/// Creates a new QR Code with the given version number,
@@ -375,6 +415,163 @@ impl QrCode {
 	}
 	
 	
+	/*---- Low-level codeword layout, for decoders and analysis ----*/
+	
+	/// Reads the codewords from a grid of modules (row-major, `false` = light, `true` = dark)
+	/// with the given version, error correction level and mask, undoing the mask and the interleaving.
+	/// 
+	/// Returns the blocks, each holding its data codewords followed by its error correction
+	/// codewords, together with the number of error correction codewords per block.
+	/// The codewords are returned as read, without any error correction applied.
+	pub(crate) fn read_codeword_blocks(ver: Version, ecl: QrCodeEcc, mask: Mask, modules: &[bool]) -> (Vec<Vec<u8>>, usize) {
+		let size = usize::from(ver.value()) * 4 + 17;
+		assert_eq!(modules.len(), size * size, "Illegal argument");
+		let mut grid = Self {
+			version: ver,
+			size: size as i32,
+			mask,
+			errorcorrectionlevel: ecl,
+			modules   : vec![false; size * size],
+			isfunction: vec![false; size * size],
+		};
+		grid.draw_function_patterns();
+		grid.modules.copy_from_slice(modules);
+		grid.apply_mask(mask);
+	
+		// Do the same zigzag scan as draw_codewords(), ignoring the remainder bits
+		let rawcodewords: usize = QrCode::get_num_raw_data_modules(ver) / 8;
+		let mut data = vec![0u8; rawcodewords];
+		let mut i: usize = 0;
+		let mut right: i32 = grid.size - 1;
+		while right >= 1 {
+			if right == 6 {
+				right = 5;
+			}
+			for vert in 0 .. grid.size {
+				for j in 0 .. 2 {
+					let x: i32 = right - j;
+					let upward: bool = (right + 1) & 2 == 0;
+					let y: i32 = if upward { grid.size - 1 - vert } else { vert };
+					if !grid.isfunction[(y * grid.size + x) as usize] && i < rawcodewords * 8 {
+						data[i >> 3] |= u8::from(grid.module(x, y)) << (7 - (i & 7));
+						i += 1;
+					}
+				}
+			}
+			right -= 2;
+		}
+	
+		// Undo the interleaving done by add_ecc_and_interleave()
+		let numblocks: usize = QrCode::table_get(&NUM_ERROR_CORRECTION_BLOCKS, ver, ecl);
+		let blockecclen: usize = QrCode::table_get(&ECC_CODEWORDS_PER_BLOCK  , ver, ecl);
+		let numshortblocks: usize = numblocks - rawcodewords % numblocks;
+		let shortblocklen: usize = rawcodewords / numblocks;
+		let mut blocks = vec![Vec::<u8>::with_capacity(shortblocklen + 1); numblocks];
+		let mut k: usize = 0;
+		for i in 0 ..= shortblocklen {
+			for (j, block) in blocks.iter_mut().enumerate() {
+				if i != shortblocklen - blockecclen || j >= numshortblocks {
+					block.push(data[k]);
+					k += 1;
+				}
+			}
+		}
+		debug_assert_eq!(k, rawcodewords);
+		(blocks, blockecclen)
+	}
+	
+	
+	/// Returns where each module's bit is stored in a QR Code of the given version and error
+	/// correction level, in row-major order: the block and the index of the codeword in that block
+	/// (data codewords first, then error correction codewords), or `None` for function modules
+	/// and remainder bits. The layout doesn't depend on the data or the mask.
+	/// 
+	/// This tells which codewords are damaged when part of the symbol is covered, e.g. by a logo.
+	pub fn codeword_locations(ver: Version, ecl: QrCodeEcc) -> Vec<Option<(usize, usize)>> {
+		let size: usize = usize::from(ver.value()) * 4 + 17;
+		let numblocks: usize = QrCode::table_get(&NUM_ERROR_CORRECTION_BLOCKS, ver, ecl);
+		let blockecclen: usize = QrCode::table_get(&ECC_CODEWORDS_PER_BLOCK  , ver, ecl);
+		let rawcodewords: usize = QrCode::get_num_raw_data_modules(ver) / 8;
+		let numshortblocks: usize = numblocks - rawcodewords % numblocks;
+		let shortblocklen: usize = rawcodewords / numblocks;
+		
+		// Same order as add_ecc_and_interleave()
+		let mut interleaved = Vec::<(usize, usize)>::with_capacity(rawcodewords);
+		for i in 0 ..= shortblocklen {
+			for j in 0 .. numblocks {
+				if i != shortblocklen - blockecclen || j >= numshortblocks {
+					// Short blocks have no codeword at the padding position
+					let index: usize = if j < numshortblocks && i > shortblocklen - blockecclen { i - 1 } else { i };
+					interleaved.push((j, index));
+				}
+			}
+		}
+		
+		// Same zigzag scan as draw_codewords(), on a grid with the function modules marked
+		let mut grid = Self {
+			version: ver,
+			size: size as i32,
+			mask: Mask::new(0),
+			errorcorrectionlevel: ecl,
+			modules   : vec![false; size * size],
+			isfunction: vec![false; size * size],
+		};
+		grid.draw_function_patterns();
+		let mut result = vec![None; size * size];
+		let mut i: usize = 0;
+		let mut right: i32 = grid.size - 1;
+		while right >= 1 {
+			if right == 6 {
+				right = 5;
+			}
+			for vert in 0 .. grid.size {
+				for j in 0 .. 2 {
+					let x: i32 = right - j;
+					let upward: bool = (right + 1) & 2 == 0;
+					let y: i32 = if upward { grid.size - 1 - vert } else { vert };
+					let index = (y * grid.size + x) as usize;
+					if !grid.isfunction[index] && i < rawcodewords * 8 {
+						result[index] = Some(interleaved[i >> 3]);
+						i += 1;
+					}
+				}
+			}
+			right -= 2;
+		}
+		result
+	}
+	
+	
+	/// Returns the number of error correction codewords in each block of a QR Code
+	/// of the given version and error correction level.
+	pub fn ecc_codewords_per_block(ver: Version, ecl: QrCodeEcc) -> usize {
+		QrCode::table_get(&ECC_CODEWORDS_PER_BLOCK, ver, ecl)
+	}
+	
+	
+	/// Returns the number of error correction codewords in each block that are kept to guard
+	/// against misdecoding rather than to correct errors, in the smallest QR Code versions
+	/// (ISO/IEC 18004 Table 9, p). The rest correct up to half their number of errors.
+	pub fn misdecode_protection_codewords(ver: Version, ecl: QrCodeEcc) -> usize {
+		match (ver.value(), ecl) {
+			(1, QrCodeEcc::Low) => 3,
+			(1, QrCodeEcc::Medium) | (2, QrCodeEcc::Low) => 2,
+			(1, _) | (3, QrCodeEcc::Low) => 1,
+			_ => 0,
+		}
+	}
+	
+	
+	/// Returns the number of codewords, data and error correction, in each block
+	/// of a QR Code of the given version and error correction level.
+	pub fn block_lengths(ver: Version, ecl: QrCodeEcc) -> Vec<usize> {
+		let numblocks: usize = QrCode::table_get(&NUM_ERROR_CORRECTION_BLOCKS, ver, ecl);
+		let rawcodewords: usize = QrCode::get_num_raw_data_modules(ver) / 8;
+		let numshortblocks: usize = numblocks - rawcodewords % numblocks;
+		(0 .. numblocks).map(|i| rawcodewords / numblocks + usize::from(i >= numshortblocks)).collect()
+	}
+	
+	
 	/*---- Private helper methods for constructor: Drawing function modules ----*/
 	
 	// Reads this object's version field, and draws and marks all function modules.
@@ -694,10 +891,10 @@ impl QrCode {
 	
 	/*---- Private helper functions ----*/
 	
-	// Returns an ascending list of positions of alignment patterns for this version number.
-	// Each position is in the range [0,177), and are used on both the x and y axes.
-	// This could be implemented as lookup table of 40 variable-length lists of unsigned bytes.
-	fn get_alignment_pattern_positions(&self) -> Vec<i32> {
+	/// Returns an ascending list of positions of alignment patterns for this version number.
+	/// Each position is in the range [0,177), and are used on both the x and y axes.
+	/// This could be implemented as lookup table of 40 variable-length lists of unsigned bytes.
+	pub fn get_alignment_pattern_positions(&self) -> Vec<i32> {
 		let ver = i32::from(self.version.value());
 		if ver == 1 {
 			vec![]
@@ -749,7 +946,7 @@ impl QrCode {
 	
 	// Returns a Reed-Solomon ECC generator polynomial for the given degree. This could be
 	// implemented as a lookup table over all possible parameter values, instead of as an algorithm.
-	fn reed_solomon_compute_divisor(degree: usize) -> Vec<u8> {
+	pub(crate) fn reed_solomon_compute_divisor(degree: usize) -> Vec<u8> {
 		assert!((1 ..= 255).contains(&degree), "Degree out of range");
 		// Polynomial coefficients are stored from highest to lowest power, excluding the leading term which is always 1.
 		// For example the polynomial x^3 + 255x^2 + 8x + 93 is stored as the uint8 array [255, 8, 93].
@@ -775,7 +972,7 @@ impl QrCode {
 	
 	
 	// Returns the Reed-Solomon error correction codeword for the given data and divisor polynomials.
-	fn reed_solomon_compute_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+	pub(crate) fn reed_solomon_compute_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
 		let mut result = vec![0u8; divisor.len()];
 		for b in data {  // Polynomial division
 			let factor: u8 = b ^ result.remove(0);
@@ -889,159 +1086,700 @@ static NUM_ERROR_CORRECTION_BLOCKS: [[i8; 41]; 4] = [
 
 
 
-/*---- QrCodeEcc functionality ----*/
-
-/// The error correction level in a QR Code symbol.
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, ValueEnum)]
-#[cfg_attr(feature = "batch", derive(serde::Serialize, serde::Deserialize))]
-pub enum QrCodeEcc {
-	/// The QR Code can tolerate about  7% erroneous codewords.
-	Low     ,
-	/// The QR Code can tolerate about 15% erroneous codewords.
-	Medium  ,
-	/// The QR Code can tolerate about 25% erroneous codewords.
-	Quartile,
-	/// The QR Code can tolerate about 30% erroneous codewords.
-	High    ,
-}
-
-
-impl QrCodeEcc {
-	
-	// Returns an unsigned 2-bit integer (in the range 0 to 3).
-	fn ordinal(self) -> usize {
-		use QrCodeEcc::*;
-		match self {
-			Low      => 0,
-			Medium   => 1,
-			Quartile => 2,
-			High     => 3,
-		}
-	}
-	
-	
-	// Returns an unsigned 2-bit integer (in the range 0 to 3).
-	fn format_bits(self) -> u8 {
-		use QrCodeEcc::*;
-		match self {
-			Low      => 1,
-			Medium   => 0,
-			Quartile => 3,
-			High     => 2,
-		}
-	}
-	
-}
-
-
-
-/*---- QrSegment functionality ----*/
+/*---- MicroQrCode functionality ----*/
 
-/// A segment of character/binary/control data in a QR Code symbol.
+/// A Micro QR Code symbol, which is a smaller variant of the QR Code with a single finder pattern.
 /// 
-/// Instances of this struct are immutable.
+/// Instances of this struct represent an immutable square grid of dark and light cells.
+/// The struct and impl cover the Micro QR Code specification of ISO/IEC 18004, supporting
+/// versions M1 to M4 (sizes 11 to 17), the error correction levels L, M and Q (Q only in M4),
+/// and the numeric, alphanumeric, byte and kanji encoding modes (not all in every version).
 /// 
-/// The mid-level way to create a segment is to take the payload data
-/// and call a static factory function such as `QrSegment::make_numeric()`.
-/// The low-level way to create a segment is to custom-make the bit buffer
-/// and call the `QrSegment::new()` constructor with appropriate values.
+/// Ways to create a Micro QR Code object:
 /// 
-/// This segment struct imposes no length restrictions, but QR Codes have restrictions.
-/// Even in the most favorable conditions, a QR Code can only hold 7089 characters of data.
-/// Any segment longer than this is meaningless for the purpose of generating QR Codes.
+/// - High level: Take the payload data and call `MicroQrCode::encode_text()` or `MicroQrCode::encode_binary()`.
+/// - Mid level: Custom-make the list of segments and call
+///   `MicroQrCode::encode_segments()` or `MicroQrCode::encode_segments_advanced()`.
+/// - Low level: Custom-make the array of data codeword bytes (including segment headers and
+///   final padding, excluding error correction codewords), supply the appropriate version
+///   number, and call the `MicroQrCode::encode_codewords()` constructor.
 #[derive(Clone, PartialEq, Eq)]
-pub struct QrSegment {
+pub struct MicroQrCode {
 	
-	// The mode indicator of this segment. Accessed through mode().
-	mode: QrSegmentMode,
+	// Scalar parameters:
 	
-	// The length of this segment's unencoded data. Measured in characters for
-	// numeric/alphanumeric/kanji mode, bytes for byte mode, and 0 for ECI mode.
-	// Not the same as the data's bit length. Accessed through num_chars().
-	numchars: usize,
+	// The version number of this Micro QR Code, which is between 1 and 4 (inclusive).
+	version: MicroVersion,
 	
-	// The data bits of this segment. Accessed through data().
-	data: Vec<bool>,
+	// The width and height of this Micro QR Code, measured in modules, between
+	// 11 and 17 (inclusive). This is equal to version * 2 + 9.
+	size: i32,
+	
+	// The error correction level used in this Micro QR Code. Never High.
+	errorcorrectionlevel: QrCodeEcc,
+	
+	// The index of the mask pattern used in this Micro QR Code, which is between 0 and 3 (inclusive).
+	mask: Mask,
+	
+	// Grids of modules/pixels, with dimensions of size*size:
+	
+	// The modules of this Micro QR Code (false = light, true = dark).
+	// Immutable after constructor finishes. Accessed through get_module().
+	modules: Vec<bool>,
+	
+	// Indicates function modules that are not subjected to masking. Discarded when constructor finishes.
+	isfunction: Vec<bool>,
 	
 }
 
 
-impl QrSegment {
+impl MicroQrCode {
 	
-	/*---- Static factory functions (mid level) ----*/
+	/*---- Static factory functions (high level) ----*/
 	
-	/// Returns a segment representing the given binary data encoded in byte mode.
+	/// Returns a Micro QR Code representing the given Unicode text string at the given error correction level.
 	/// 
-	/// All input byte slices are acceptable.
+	/// The smallest possible Micro QR Code version is automatically chosen for the output. The ECC level
+	/// of the result may be higher than the ecl argument if it can be done without increasing the version.
 	/// 
-	/// Any text string can be converted to UTF-8 bytes and encoded as a byte mode segment.
-	pub fn make_bytes(data: &[u8]) -> Self {
-		let mut bb = BitBuffer(Vec::with_capacity(data.len().checked_mul(8).unwrap()));
-		for &b in data {
-			bb.append_bits(u32::from(b), 8);
-		}
-		QrSegment::new(QrSegmentMode::Byte, data.len(), bb.0)
+	/// Returns a wrapped `MicroQrCode` if successful, or `Err` if the data is too long
+	/// to fit in any version at the given ECC level (which is always the case for `High`).
+	pub fn encode_text(text: &str, ecl: QrCodeEcc) -> Result<Self,DataTooLong> {
+		let segs: Vec<QrSegment> = QrSegment::make_segments(text);
+		MicroQrCode::encode_segments(&segs, ecl)
 	}
 	
 	
-	/// Returns a segment representing the given string of decimal digits encoded in numeric mode.
+	/// Returns a Micro QR Code representing the given binary data at the given error correction level.
 	/// 
-	/// Panics if the string contains non-digit characters.
-	pub fn make_numeric(text: &str) -> Self {
-		assert!(text.bytes().all(|b: u8| b.is_ascii_digit()), "String contains non-numeric characters");
-		let mut bb = BitBuffer(Vec::with_capacity(
-			text.len().checked_mul(3).unwrap().checked_add(text.len().div_ceil(3)).unwrap()));
-		for chunk in text.as_bytes().chunks(3) {
-			let data: u32 = chunk.iter().fold(0u32,
-				|acc, &b| acc * 10 + u32::from(b - b'0'));
-			bb.append_bits(data, (chunk.len() as u8) * 3 + 1);
-		}
-		QrSegment::new(QrSegmentMode::Numeric, text.len(), bb.0)
+	/// This function always encodes using the binary segment mode, which is unavailable in M1 and M2.
+	/// The maximum number of bytes allowed is 15.
+	/// 
+	/// Returns a wrapped `MicroQrCode` if successful, or `Err` if the
+	/// data is too long to fit in any version at the given ECC level.
+	pub fn encode_binary(data: &[u8], ecl: QrCodeEcc) -> Result<Self,DataTooLong> {
+		let segs: [QrSegment; 1] = [QrSegment::make_bytes(data)];
+		MicroQrCode::encode_segments(&segs, ecl)
 	}
 	
 	
-	/// Returns a segment representing the given text string encoded in alphanumeric mode.
+	/*---- Static factory functions (mid level) ----*/
+	
+	/// Returns a Micro QR Code representing the given segments at the given error correction level.
 	/// 
-	/// The characters allowed are: 0 to 9, A to Z (uppercase only), space,
-	/// dollar, percent, asterisk, plus, hyphen, period, slash, colon.
+	/// The smallest possible Micro QR Code version is automatically chosen for the output. The ECC level
+	/// of the result may be higher than the ecl argument if it can be done without increasing the version.
 	/// 
-	/// Panics if the string contains non-encodable characters.
-	pub fn make_alphanumeric(text: &str) -> Self {
-		let mut bb = BitBuffer(Vec::with_capacity(
-			text.len().checked_mul(5).unwrap().checked_add(text.len().div_ceil(2)).unwrap()));
-		for chunk in text.as_bytes().chunks(2) {
-			let data: u32 = chunk.iter().fold(0u32, |acc, &b| acc * 45 + u32::try_from(
-				ALPHANUMERIC_CHARSET.find(char::from(b)).expect("String contains unencodable characters in alphanumeric mode")).unwrap());
-			bb.append_bits(data, (chunk.len() as u8) * 5 + 1);
-		}
-		QrSegment::new(QrSegmentMode::Alphanumeric, text.len(), bb.0)
+	/// Returns a wrapped `MicroQrCode` if successful, or `Err` if the
+	/// data is too long to fit in any version at the given ECC level.
+	pub fn encode_segments(segs: &[QrSegment], ecl: QrCodeEcc) -> Result<Self,DataTooLong> {
+		MicroQrCode::encode_segments_advanced(segs, ecl, MicroVersion::MIN, MicroVersion::MAX, None, true)
 	}
 	
 	
-	/// Returns a list of zero or more segments to represent the given Unicode text string.
+	/// Returns a Micro QR Code representing the given segments with the given encoding parameters.
 	/// 
-	/// The result may use various segment modes and switch
-	/// modes to optimize the length of the bit stream.
-	pub fn make_segments(text: &str) -> Vec<Self> {
-		if text.is_empty() {
-			vec![]
-		} else {
-			vec![
-				if QrSegment::is_numeric(text) {
-					QrSegment::make_numeric(text)
-				} else if QrSegment::is_alphanumeric(text) {
-					QrSegment::make_alphanumeric(text)
-				} else {
-					QrSegment::make_bytes(text.as_bytes())
+	/// The smallest possible version within the given range that supports the given ECC
+	/// level is automatically chosen for the output. Iff boostecl is `true`, then the ECC
+	/// level of the result may be higher than the ecl argument if it can be done without
+	/// increasing the version. The mask number is either between 0 to 3 (inclusive) to
+	/// force that mask, or `None` to automatically choose an appropriate mask.
+	/// 
+	/// Segments in a mode that a version doesn't support (such as byte mode in M1 and M2, or
+	/// ECI in any version) make that version unsuitable. Returns a wrapped `MicroQrCode` if
+	/// successful, or `Err` if the data doesn't fit in any version in the given range.
+	pub fn encode_segments_advanced(segs: &[QrSegment], mut ecl: QrCodeEcc,
+			minversion: MicroVersion, maxversion: MicroVersion, mask: Option<Mask>, boostecl: bool)
+			-> Result<Self,DataTooLong> {
+		
+		assert!(minversion <= maxversion, "Invalid value");
+		assert!(mask.is_none_or(|m| m.value() <= 3), "Mask value out of range");
+		
+		// Find the minimal version number to use
+		let mut version: MicroVersion = minversion;
+		let datausedbits: usize = loop {
+			let datacapacitybits: usize = MicroQrCode::get_num_data_bits(version, ecl).unwrap_or(0);
+			let dataused: Option<usize> = QrSegment::get_micro_total_bits(segs, version);
+			if MicroQrCode::get_num_data_bits(version, ecl).is_some() && dataused.is_some_and(|n| n <= datacapacitybits) {
+				break dataused.unwrap();  // This version number is found to be suitable
+			} else if version >= maxversion {  // All versions in the range could not fit the given data
+				// A mode that even the largest allowed version lacks can't be made to fit
+				if let Some(seg) = segs.iter().find(|seg| seg.mode.num_micro_char_count_bits(maxversion).is_none()) {
+					return Err(DataTooLong::UnsupportedMode(seg.mode, format!("M{}", maxversion.value())));
 				}
-			]
-		}
-	}
-	
-	
-	/// Returns a segment representing an Extended Channel Interpretation
-	/// (ECI) designator with the given assignment value.
-	pub fn make_eci(assignval: u32) -> Self {
+				return Err(match dataused {
+					None => DataTooLong::SegmentTooLong,
+					Some(n) => DataTooLong::DataOverCapacity(n, datacapacitybits),
+				});
+			} else {
+				version = MicroVersion::new(version.value() + 1);
+			}
+		};
+		
+		// Increase the error correction level while the data still fits in the current version number
+		for &newecl in &[QrCodeEcc::Medium, QrCodeEcc::Quartile] {  // From low to high
+			if boostecl && newecl > ecl && MicroQrCode::get_num_data_bits(version, newecl).is_some_and(|n| datausedbits <= n) {
+				ecl = newecl;
+			}
+		}
+		
+		// Concatenate all segments to create the data bit string
+		let modebits: u8 = version.value() - 1;
+		let mut bb = BitBuffer(Vec::new());
+		for seg in segs {
+			bb.append_bits(seg.mode.micro_mode_bits().unwrap(), modebits);
+			bb.append_bits(u32::try_from(seg.numchars).unwrap(), seg.mode.num_micro_char_count_bits(version).unwrap());
+			bb.0.extend_from_slice(&seg.data);
+		}
+		debug_assert_eq!(bb.0.len(), datausedbits);
+		
+		// Add terminator, which may be truncated if capacity is reached
+		let datacapacitybits: usize = MicroQrCode::get_num_data_bits(version, ecl).unwrap();
+		debug_assert!(bb.0.len() <= datacapacitybits);
+		let numzerobits: usize = std::cmp::min(usize::from(modebits) * 2 + 3, datacapacitybits - bb.0.len());
+		bb.append_bits(0, u8::try_from(numzerobits).unwrap());
+		
+		// Pad up to a byte, or up to the end of the final 4-bit codeword in M1 and M3
+		let numzerobits: usize = std::cmp::min(bb.0.len().wrapping_neg() & 7, datacapacitybits - bb.0.len());
+		bb.append_bits(0, u8::try_from(numzerobits).unwrap());
+		
+		// Pad with alternating bytes until data capacity is reached, then fill a trailing half codeword
+		for &padbyte in [0xEC, 0x11].iter().cycle() {
+			if bb.0.len() + 8 > datacapacitybits {
+				break;
+			}
+			bb.append_bits(padbyte, 8);
+		}
+		let numzerobits: usize = datacapacitybits - bb.0.len();
+		bb.append_bits(0, u8::try_from(numzerobits).unwrap());
+		
+		// Pack bits into bytes in big endian; a final 4-bit codeword occupies the high nibble
+		let mut datacodewords = vec![0u8; bb.0.len().div_ceil(8)];
+		for (i, &bit) in bb.0.iter().enumerate() {
+			datacodewords[i >> 3] |= u8::from(bit) << (7 - (i & 7));
+		}
+		
+		// Create the Micro QR Code object
+		Ok(MicroQrCode::encode_codewords(version, ecl, &datacodewords, mask))
+	}
+	
+	
+	/*---- Constructor (low level) ----*/
+	
+	/// Creates a new Micro QR Code with the given version number,
+	/// error correction level, data codeword bytes, and mask number.
+	/// 
+	/// In M1 and M3 the final data codeword is only 4 bits long and is taken from the high
+	/// nibble of the last byte. Panics if the version doesn't support the ECC level, or
+	/// if the number of data codewords is wrong.
+	/// 
+	/// This is a low-level API that most users should not use directly.
+	/// A mid-level API is the `encode_segments()` function.
+	pub fn encode_codewords(ver: MicroVersion, ecl: QrCodeEcc, datacodewords: &[u8], mut msk: Option<Mask>) -> Self {
+		// Initialize fields
+		let size = usize::from(ver.value()) * 2 + 9;
+		let mut result = Self {
+			version: ver,
+			size: size as i32,
+			mask: Mask::new(0),  // Dummy value
+			errorcorrectionlevel: ecl,
+			modules   : vec![false; size * size],  // Initially all light
+			isfunction: vec![false; size * size],
+		};
+		
+		// Compute ECC, draw modules
+		result.draw_function_patterns();
+		let allbits: Vec<bool> = result.add_ecc(datacodewords);
+		result.draw_codewords(&allbits);
+		
+		// Do masking
+		if msk.is_none() {  // Automatically choose best mask
+			let mut maxscore = i32::MIN;
+			for i in 0u8 .. 4 {
+				let i = Mask::new(i);
+				result.apply_mask(i);
+				let score: i32 = result.get_mask_score();
+				if score > maxscore {
+					msk = Some(i);
+					maxscore = score;
+				}
+				result.apply_mask(i);  // Undoes the mask due to XOR
+			}
+		}
+		let msk: Mask = msk.unwrap();
+		result.mask = msk;
+		result.apply_mask(msk);  // Apply the final choice of mask
+		result.draw_format_bits(msk);
+		
+		result.isfunction.clear();
+		result.isfunction.shrink_to_fit();
+		result
+	}
+	
+	
+	/*---- Public methods ----*/
+	
+	/// Returns this Micro QR Code's version, in the range [1, 4].
+	pub fn version(&self) -> MicroVersion {
+		self.version
+	}
+	
+	
+	/// Returns this Micro QR Code's size, in the range [11, 17].
+	pub fn size(&self) -> i32 {
+		self.size
+	}
+	
+	
+	/// Returns this Micro QR Code's error correction level.
+	pub fn error_correction_level(&self) -> QrCodeEcc {
+		self.errorcorrectionlevel
+	}
+	
+	
+	/// Returns this Micro QR Code's mask, in the range [0, 3].
+	pub fn mask(&self) -> Mask {
+		self.mask
+	}
+	
+	
+	/// Returns the color of the module (pixel) at the given coordinates,
+	/// which is `false` for light or `true` for dark.
+	/// 
+	/// The top left corner has the coordinates (x=0, y=0). If the given
+	/// coordinates are out of bounds, then `false` (light) is returned.
+	pub fn get_module(&self, x: i32, y: i32) -> bool {
+		(0 .. self.size).contains(&x) && (0 .. self.size).contains(&y) && self.module(x, y)
+	}
+	
+	
+	// Returns the color of the module at the given coordinates, which must be in bounds.
+	fn module(&self, x: i32, y: i32) -> bool {
+		self.modules[(y * self.size + x) as usize]
+	}
+	
+	
+	// Returns a mutable reference to the module's color at the given coordinates, which must be in bounds.
+	fn module_mut(&mut self, x: i32, y: i32) -> &mut bool {
+		&mut self.modules[(y * self.size + x) as usize]
+	}
+	
+	
+	/*---- Private helper methods for constructor: Drawing function modules ----*/
+	
+	// Draws and marks all function modules: the single finder pattern with its separator,
+	// the timing patterns along the top row and left column, and the reserved format area.
+	fn draw_function_patterns(&mut self) {
+		// Draw horizontal and vertical timing patterns
+		let size: i32 = self.size;
+		for i in 0 .. size {
+			self.set_function_module(i, 0, i % 2 == 0);
+			self.set_function_module(0, i, i % 2 == 0);
+		}
+		
+		// Draw the finder pattern with its separator (overwrites some timing modules)
+		for dy in -4 ..= 4 {
+			for dx in -4 ..= 4 {
+				let xx: i32 = 3 + dx;
+				let yy: i32 = 3 + dy;
+				if (0 .. size).contains(&xx) && (0 .. size).contains(&yy) {
+					let dist: i32 = std::cmp::max(dx.abs(), dy.abs());  // Chebyshev/infinity norm
+					self.set_function_module(xx, yy, dist != 2 && dist != 4);
+				}
+			}
+		}
+		
+		// Reserve the format area
+		self.draw_format_bits(Mask::new(0));  // Dummy mask value; overwritten later in the constructor
+	}
+	
+	
+	// Draws the single copy of the format bits (with its own error correction code)
+	// based on the given mask and this object's version and error correction level.
+	fn draw_format_bits(&mut self, mask: Mask) {
+		// Calculate error correction code and pack bits
+		let bits: u32 = {
+			// Symbol number is uint3, mask is uint2
+			let symbol: u8 = match self.version.value() {
+				1 => 0,
+				v => (v - 1) * 2 - 1 + (self.errorcorrectionlevel.ordinal() as u8),
+			};
+			let data = u32::from(symbol << 2 | mask.value());
+			let mut rem: u32 = data;
+			for _ in 0 .. 10 {
+				rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+			}
+			(data << 10 | rem) ^ 0x4445  // uint15
+		};
+		debug_assert_eq!(bits >> 15, 0);
+		
+		// Draw the only copy
+		for i in 0 .. 8 {
+			self.set_function_module(8, i + 1, get_bit(bits, i));
+		}
+		for i in 8 .. 15 {
+			self.set_function_module(15 - i, 8, get_bit(bits, i));
+		}
+	}
+	
+	
+	// Sets the color of a module and marks it as a function module.
+	// Only used by the constructor. Coordinates must be in bounds.
+	fn set_function_module(&mut self, x: i32, y: i32, isdark: bool) {
+		*self.module_mut(x, y) = isdark;
+		self.isfunction[(y * self.size + x) as usize] = true;
+	}
+	
+	
+	/*---- Private helper methods for constructor: Codewords and masking ----*/
+	
+	// Returns the bit sequence of the given data codewords followed by their error correction
+	// codewords. Micro QR Codes have a single block, so no interleaving is needed, but the
+	// final data codeword of M1 and M3 only contributes its high 4 bits.
+	fn add_ecc(&self, data: &[u8]) -> Vec<bool> {
+		let ver: MicroVersion = self.version;
+		let ecl: QrCodeEcc = self.errorcorrectionlevel;
+		let databits: usize = MicroQrCode::get_num_data_bits(ver, ecl).expect("ECC level unsupported in this version");
+		assert_eq!(data.len(), databits.div_ceil(8), "Illegal argument");
+		
+		let ecclen: usize = MicroQrCode::table_get(&MICRO_ECC_CODEWORDS, ver, ecl) as usize;
+		let rsdiv: Vec<u8> = QrCode::reed_solomon_compute_divisor(ecclen);
+		let ecc: Vec<u8> = QrCode::reed_solomon_compute_remainder(data, &rsdiv);
+		
+		let mut result = Vec::<bool>::with_capacity(databits + ecclen * 8);
+		result.extend((0 .. databits).map(|i| get_bit(u32::from(data[i >> 3]), 7 - ((i as i32) & 7))));
+		for &b in &ecc {
+			result.extend((0 .. 8).rev().map(|i| get_bit(u32::from(b), i)));
+		}
+		result
+	}
+	
+	
+	// Draws the given bit sequence (data and error correction) onto the entire data area
+	// of this Micro QR Code. Function modules need to be marked off before this is called.
+	fn draw_codewords(&mut self, data: &[bool]) {
+		let mut i: usize = 0;  // Bit index into the data
+		// Do the zigzag scan; unlike QR Code there is no vertical timing column to skip
+		let mut right: i32 = self.size - 1;
+		let mut upward = true;
+		while right >= 1 {  // Index of right column in each column pair
+			for vert in 0 .. self.size {  // Vertical counter
+				for j in 0 .. 2 {
+					let x: i32 = right - j;  // Actual x coordinate
+					let y: i32 = if upward { self.size - 1 - vert } else { vert };  // Actual y coordinate
+					if !self.isfunction[(y * self.size + x) as usize] && i < data.len() {
+						*self.module_mut(x, y) = data[i];
+						i += 1;
+					}
+				}
+			}
+			upward = !upward;
+			right -= 2;
+		}
+		debug_assert_eq!(i, data.len());
+	}
+	
+	
+	// XORs the codeword modules in this Micro QR Code with the given mask pattern.
+	// The four Micro QR Code masks are QR Code masks 1, 4, 6 and 7. Calling
+	// apply_mask() with the same mask value a second time will undo the mask.
+	fn apply_mask(&mut self, mask: Mask) {
+		for y in 0 .. self.size {
+			for x in 0 .. self.size {
+				let invert: bool = match mask.value() {
+					0 => y % 2 == 0,
+					1 => (x / 3 + y / 2) % 2 == 0,
+					2 => (x * y % 2 + x * y % 3) % 2 == 0,
+					3 => ((x + y) % 2 + x * y % 3) % 2 == 0,
+					_ => unreachable!(),
+				};
+				*self.module_mut(x, y) ^= invert & !self.isfunction[(y * self.size + x) as usize];
+			}
+		}
+	}
+	
+	
+	// Calculates the mask evaluation score from the dark modules along the right and bottom edges.
+	// Unlike QR Code, the automatic mask choice algorithm selects the mask with the highest score.
+	fn get_mask_score(&self) -> i32 {
+		let size: i32 = self.size;
+		let sum1: i32 = (1 .. size).map(|y| i32::from(self.module(size - 1, y))).sum();
+		let sum2: i32 = (1 .. size).map(|x| i32::from(self.module(x, size - 1))).sum();
+		std::cmp::min(sum1, sum2) * 16 + std::cmp::max(sum1, sum2)
+	}
+	
+	
+	/*---- Private helper functions ----*/
+	
+	// Returns the number of data bits that can be stored in a Micro QR Code of the given
+	// version number and error correction level, or None if the version doesn't support
+	// the level. M1 and M3 have a 4-bit final data codeword, so this isn't always a multiple of 8.
+	fn get_num_data_bits(ver: MicroVersion, ecl: QrCodeEcc) -> Option<usize> {
+		if ecl == QrCodeEcc::High {
+			return None;
+		}
+		let bits: i16 = MicroQrCode::table_get(&MICRO_DATA_BITS, ver, ecl);
+		usize::try_from(bits).ok()
+	}
+	
+	
+	// Returns an entry from the given table based on the given values. The level must not be High.
+	fn table_get(table: &'static [[i16; 5]; 3], ver: MicroVersion, ecl: QrCodeEcc) -> i16 {
+		table[ecl.ordinal()][usize::from(ver.value())]
+	}
+	
+}
+
+
+static MICRO_DATA_BITS: [[i16; 5]; 3] = [
+	// Version: (note that index 0 is for padding, and is set to an illegal value)
+	//0,  M1,  M2,  M3,  M4    Error correction level
+	[-1,  20,  40,  84, 128],  // Low
+	[-1,  -1,  32,  68, 112],  // Medium
+	[-1,  -1,  -1,  -1,  80],  // Quartile
+];
+
+static MICRO_ECC_CODEWORDS: [[i16; 5]; 3] = [
+	// Version: (note that index 0 is for padding, and is set to an illegal value)
+	//0, M1, M2, M3, M4    Error correction level
+	[-1,  2,  5,  6,  8],  // Low
+	[-1, -1,  6,  8, 10],  // Medium
+	[-1, -1, -1, -1, 14],  // Quartile
+];
+
+
+
+/*---- QrCodeEcc functionality ----*/
+
+/// The error correction level in a QR Code symbol.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, ValueEnum)]
+#[cfg_attr(feature = "batch", derive(serde::Serialize, serde::Deserialize))]
+pub enum QrCodeEcc {
+	/// The QR Code can tolerate about  7% erroneous codewords.
+	Low     ,
+	/// The QR Code can tolerate about 15% erroneous codewords.
+	Medium  ,
+	/// The QR Code can tolerate about 25% erroneous codewords.
+	Quartile,
+	/// The QR Code can tolerate about 30% erroneous codewords.
+	High    ,
+}
+
+
+impl QrCodeEcc {
+	
+	// Returns an unsigned 2-bit integer (in the range 0 to 3).
+	fn ordinal(self) -> usize {
+		use QrCodeEcc::*;
+		match self {
+			Low      => 0,
+			Medium   => 1,
+			Quartile => 2,
+			High     => 3,
+		}
+	}
+	
+	
+	// Returns an unsigned 2-bit integer (in the range 0 to 3).
+	pub(crate) fn format_bits(self) -> u8 {
+		use QrCodeEcc::*;
+		match self {
+			Low      => 1,
+			Medium   => 0,
+			Quartile => 3,
+			High     => 2,
+		}
+	}
+	
+}
+
+
+
+/*---- QrSegment functionality ----*/
+
+/// A segment of character/binary/control data in a QR Code symbol.
+/// 
+/// Instances of this struct are immutable.
+/// 
+/// The mid-level way to create a segment is to take the payload data
+/// and call a static factory function such as `QrSegment::make_numeric()`.
+/// The low-level way to create a segment is to custom-make the bit buffer
+/// and call the `QrSegment::new()` constructor with appropriate values.
+/// 
+/// This segment struct imposes no length restrictions, but QR Codes have restrictions.
+/// Even in the most favorable conditions, a QR Code can only hold 7089 characters of data.
+/// Any segment longer than this is meaningless for the purpose of generating QR Codes.
+#[derive(Clone, PartialEq, Eq)]
+pub struct QrSegment {
+	
+	// The mode indicator of this segment. Accessed through mode().
+	mode: QrSegmentMode,
+	
+	// The length of this segment's unencoded data. Measured in characters for
+	// numeric/alphanumeric/kanji mode, bytes for byte mode, and 0 for ECI, Structured Append and FNC1 mode.
+	// Not the same as the data's bit length. Accessed through num_chars().
+	numchars: usize,
+	
+	// The data bits of this segment. Accessed through data().
+	data: Vec<bool>,
+	
+}
+
+
+impl QrSegment {
+	
+	/*---- Static factory functions (mid level) ----*/
+	
+	/// Returns a segment representing the given binary data encoded in byte mode.
+	/// 
+	/// All input byte slices are acceptable.
+	/// 
+	/// Any text string can be converted to UTF-8 bytes and encoded as a byte mode segment.
+	pub fn make_bytes(data: &[u8]) -> Self {
+		let mut bb = BitBuffer(Vec::with_capacity(data.len().checked_mul(8).unwrap()));
+		for &b in data {
+			bb.append_bits(u32::from(b), 8);
+		}
+		QrSegment::new(QrSegmentMode::Byte, data.len(), bb.0)
+	}
+	
+	
+	/// Returns a segment representing the given string of decimal digits encoded in numeric mode.
+	/// 
+	/// Panics if the string contains non-digit characters.
+	pub fn make_numeric(text: &str) -> Self {
+		assert!(text.bytes().all(|b: u8| b.is_ascii_digit()), "String contains non-numeric characters");
+		let mut bb = BitBuffer(Vec::with_capacity(
+			text.len().checked_mul(3).unwrap().checked_add(text.len().div_ceil(3)).unwrap()));
+		for chunk in text.as_bytes().chunks(3) {
+			let data: u32 = chunk.iter().fold(0u32,
+				|acc, &b| acc * 10 + u32::from(b - b'0'));
+			bb.append_bits(data, (chunk.len() as u8) * 3 + 1);
+		}
+		QrSegment::new(QrSegmentMode::Numeric, text.len(), bb.0)
+	}
+	
+	
+	/// Returns a segment representing the given text string encoded in alphanumeric mode.
+	/// 
+	/// The characters allowed are: 0 to 9, A to Z (uppercase only), space,
+	/// dollar, percent, asterisk, plus, hyphen, period, slash, colon.
+	/// 
+	/// Panics if the string contains non-encodable characters.
+	pub fn make_alphanumeric(text: &str) -> Self {
+		let mut bb = BitBuffer(Vec::with_capacity(
+			text.len().checked_mul(5).unwrap().checked_add(text.len().div_ceil(2)).unwrap()));
+		for chunk in text.as_bytes().chunks(2) {
+			let data: u32 = chunk.iter().fold(0u32, |acc, &b| acc * 45 + u32::try_from(
+				ALPHANUMERIC_CHARSET.find(char::from(b)).expect("String contains unencodable characters in alphanumeric mode")).unwrap());
+			bb.append_bits(data, (chunk.len() as u8) * 5 + 1);
+		}
+		QrSegment::new(QrSegmentMode::Alphanumeric, text.len(), bb.0)
+	}
+	
+	
+	/// Returns a segment representing the given text string encoded in kanji mode.
+	/// 
+	/// Each character is converted to Shift JIS and packed into 13 bits. The characters
+	/// allowed are those with a double-byte Shift JIS code in the range 0x8140 to 0x9FFC
+	/// or 0xE040 to 0xEBBF, which covers kanji, kana, and full-width symbols.
+	/// 
+	/// Panics if the string contains non-encodable characters.
+	pub fn make_kanji(text: &str) -> Self {
+		let numchars: usize = text.chars().count();
+		let mut bb = BitBuffer(Vec::with_capacity(numchars.checked_mul(13).unwrap()));
+		for c in text.chars() {
+			let data: u32 = kanji_value(c).expect("String contains unencodable characters in kanji mode");
+			bb.append_bits(data, 13);
+		}
+		QrSegment::new(QrSegmentMode::Kanji, numchars, bb.0)
+	}
+	
+	
+	/// Returns a list of zero or more segments to represent the given Unicode text string.
+	/// 
+	/// The result may use various segment modes and switch
+	/// modes to optimize the length of the bit stream.
+	pub fn make_segments(text: &str) -> Vec<Self> {
+		if text.is_empty() {
+			vec![]
+		} else {
+			vec![
+				if QrSegment::is_numeric(text) {
+					QrSegment::make_numeric(text)
+				} else if QrSegment::is_alphanumeric(text) {
+					QrSegment::make_alphanumeric(text)
+				} else if QrSegment::is_kanji(text) {
+					QrSegment::make_kanji(text)
+				} else {
+					QrSegment::make_bytes(text.as_bytes())
+				}
+			]
+		}
+	}
+	
+	
+	/// Returns a list of segments to represent the given Unicode text string, switching
+	/// between numeric, alphanumeric, byte, and kanji modes to minimize the total bit length.
+	/// 
+	/// The segmentation is recomputed whenever the character count field widths change,
+	/// so the result is optimal for the smallest version within the given range that can
+	/// hold the text at the given ECC level. Pass the result to `QrCode::encode_segments_advanced()`
+	/// with the same version range and ECC level.
+	/// 
+	/// Returns `Err` if the text is too long to fit in any version in the given range.
+	pub fn make_segments_optimally(text: &str, ecl: QrCodeEcc,
+			minversion: Version, maxversion: Version) -> Result<Vec<Self>,DataTooLong> {
+		QrSegment::make_segments_optimally_with(text, ecl, minversion, maxversion, &SegmentOptions::default())
+	}
+	
+	
+	/// Returns a list of segments like `make_segments_optimally()`, using the given options
+	/// for the prefix segments, the byte mode character set, kanji mode, and FNC1 mode.
+	/// 
+	/// The prefix segments count towards the capacity when choosing the version.
+	/// 
+	/// Returns `Err` if the text is too long to fit in any version in the given range.
+	pub fn make_segments_optimally_with(text: &str, ecl: QrCodeEcc,
+			minversion: Version, maxversion: Version, options: &SegmentOptions)
+			-> Result<Vec<Self>,DataTooLong> {
+		
+		assert!(minversion <= maxversion, "Invalid value");
+		let chars: Vec<char> = text.chars().collect();
+		let makesegs = |version: Version| -> Vec<Self> {
+			let mut segs: Vec<Self> = options.prefix.clone();
+			segs.extend(QrSegment::split_into_segments(&chars,
+				&QrSegment::compute_character_modes(&chars, version, options), options));
+			segs
+		};
+		
+		// Iterate through version numbers, and make tentative segments
+		let mut version: Version = minversion;
+		let mut segs: Vec<Self> = makesegs(version);
+		loop {
+			if version > minversion && (version.value() == 10 || version.value() == 27) {
+				segs = makesegs(version);
+			}
+			
+			// Check if the segments fit
+			let datacapacitybits: usize = QrCode::get_num_data_codewords(version, ecl) * 8;
+			let dataused: Option<usize> = QrSegment::get_total_bits(&segs, version);
+			if dataused.is_some_and(|n| n <= datacapacitybits) {
+				return Ok(segs);  // This version number is found to be suitable
+			} else if version >= maxversion {  // All versions in the range could not fit the given text
+				return Err(match dataused {
+					None => DataTooLong::SegmentTooLong,
+					Some(n) => DataTooLong::DataOverCapacity(n, datacapacitybits),
+				});
+			}
+			version = Version::new(version.value() + 1);
+		}
+	}
+	
+	
+	/// Returns a segment representing an Extended Channel Interpretation
+	/// (ECI) designator with the given assignment value.
+	pub fn make_eci(assignval: u32) -> Self {
 		let mut bb = BitBuffer(Vec::with_capacity(24));
 		if assignval < (1 << 7) {
 			bb.append_bits(assignval, 8);
@@ -1058,6 +1796,47 @@ impl QrSegment {
 	}
 	
 	
+	/// Returns an FNC1 in first position indicator segment, which marks the
+	/// data as a GS1 element string. It must come before all data segments.
+	/// In the data that follows, FNC1 field separators are written as
+	/// a group separator (ASCII 29) in byte mode or `%` in alphanumeric mode.
+	pub fn make_fnc1_first() -> Self {
+		QrSegment::new(QrSegmentMode::Fnc1First, 0, Vec::new())
+	}
+	
+	
+	/// Returns an FNC1 in second position indicator segment, which marks the data as
+	/// formatted according to an industry specification identified by the given application
+	/// indicator. The indicator is a two-digit number (0 to 99), or an ASCII letter plus 100.
+	/// 
+	/// Panics if the application indicator is out of range.
+	pub fn make_fnc1_second(appindicator: u8) -> Self {
+		assert!(appindicator < 100 || (appindicator - 100).is_ascii_alphabetic(),
+			"Application indicator out of range");
+		let mut bb = BitBuffer(Vec::with_capacity(8));
+		bb.append_bits(appindicator.into(), 8);
+		QrSegment::new(QrSegmentMode::Fnc1Second, 0, bb.0)
+	}
+	
+	
+	/// Returns a Structured Append header segment for the symbol at the given
+	/// zero-based index in a sequence of `total` symbols. The parity byte must be
+	/// the same in every symbol of the sequence; see `structured_append_parity()`.
+	/// 
+	/// The header must be the first segment of each symbol in the sequence.
+	/// 
+	/// Panics if total is not in the range 1 to 16, or index is not less than total.
+	pub fn make_structured_append(index: u8, total: u8, parity: u8) -> Self {
+		assert!((1 ..= 16).contains(&total), "Structured Append total out of range");
+		assert!(index < total, "Structured Append index out of range");
+		let mut bb = BitBuffer(Vec::with_capacity(16));
+		bb.append_bits(index.into(), 4);
+		bb.append_bits((total - 1).into(), 4);
+		bb.append_bits(parity.into(), 8);
+		QrSegment::new(QrSegmentMode::StructuredAppend, 0, bb.0)
+	}
+	
+	
 	/*---- Constructor (low level) ----*/
 	
 	/// Creates a new QR Code segment with the given attributes and data.
@@ -1089,27 +1868,193 @@ impl QrSegment {
 	}
 	
 	
-	/*---- Other static functions ----*/
-	
-	// Calculates and returns the number of bits needed to encode the given
-	// segments at the given version. The result is None if a segment has too many
-	// characters to fit its length field, or the total bits exceeds usize::MAX.
-	fn get_total_bits(segs: &[Self], version: Version) -> Option<usize> {
+	/*---- Other static functions ----*/
+	
+	// Calculates and returns the number of bits needed to encode the given
+	// segments at the given version. The result is None if a segment has too many
+	// characters to fit its length field, or the total bits exceeds usize::MAX.
+	fn get_total_bits(segs: &[Self], version: Version) -> Option<usize> {
+		let mut result: usize = 0;
+		for seg in segs {
+			let ccbits: u8 = seg.mode.num_char_count_bits(version);
+			// ccbits can be as large as 16, but usize can be as small as 16
+			if let Some(limit) = 1usize.checked_shl(ccbits.into()) 
+				&& seg.numchars >= limit {
+				return None;  // The segment's length doesn't fit the field's bit width
+			}
+			result = result.checked_add(4 + usize::from(ccbits))?;
+			result = result.checked_add(seg.data.len())?;
+		}
+		Some(result)
+	}
+	
+	
+	// Returns the mode to encode each character in, so that the total bit length of the
+	// resulting segments is minimal at the given version. Uses dynamic programming over
+	// the characters, tracking the cheapest way to end each prefix in each mode.
+	fn compute_character_modes(chars: &[char], version: Version,
+			options: &SegmentOptions) -> Vec<QrSegmentMode> {
+		use QrSegmentMode::*;
+		const MODES: [QrSegmentMode; 4] = [Byte, Alphanumeric, Numeric, Kanji];
+		
+		// Costs are measured in sixths of a bit, so that every per-character cost is an integer
+		let headcosts: [usize; 4] = MODES.map(|m| (4 + usize::from(m.num_char_count_bits(version))) * 6);
+		
+		// charmodes[i][j] is the mode of chars[i] on the cheapest path that ends in MODES[j]
+		let mut charmodes: Vec<[Option<QrSegmentMode>; 4]> = vec![[None; 4]; chars.len()];
+		let mut prevcosts: [usize; 4] = headcosts;
+		
+		for (&c, modes) in chars.iter().zip(charmodes.iter_mut()) {
+			let mut curcosts = [0usize; 4];
+			
+			// Extend the segment of the same mode, if the character can be encoded in it
+			curcosts[0] = prevcosts[0] + (options.encodebytes)(c.encode_utf8(&mut [0u8; 4])).len() * 8 * 6;
+			modes[0] = Some(Byte);
+			if is_alphanumeric_char(c, options.fnc1) {
+				curcosts[1] = prevcosts[1] + 33;  // 5.5 bits per character
+				modes[1] = Some(Alphanumeric);
+			}
+			if c.is_ascii_digit() {
+				curcosts[2] = prevcosts[2] + 20;  // 3.33 bits per character
+				modes[2] = Some(Numeric);
+			}
+			if options.allowkanji && kanji_value(c).is_some() {
+				curcosts[3] = prevcosts[3] + 78;  // 13 bits per character
+				modes[3] = Some(Kanji);
+			}
+			
+			// Start a new segment at the end to switch modes
+			for (j, &headcost) in headcosts.iter().enumerate() {  // To mode
+				for k in 0 .. MODES.len() {  // From mode
+					let newcost: usize = curcosts[k].div_ceil(6) * 6 + headcost;
+					if modes[k].is_some() && (modes[j].is_none() || newcost < curcosts[j]) {
+						curcosts[j] = newcost;
+						modes[j] = Some(MODES[k]);
+					}
+				}
+			}
+			
+			prevcosts = curcosts;
+		}
+		
+		// Find the optimal ending mode, then trace the path backwards
+		let mut curmode: Option<QrSegmentMode> = (0 .. MODES.len())
+			.min_by_key(|&j| prevcosts[j]).map(|j| MODES[j]);
+		let mut result: Vec<QrSegmentMode> = vec![Byte; chars.len()];
+		for (modes, res) in charmodes.iter().zip(result.iter_mut()).rev() {
+			let j: usize = MODES.iter().position(|&m| Some(m) == curmode).unwrap();
+			curmode = modes[j];
+			*res = curmode.unwrap();
+		}
+		result
+	}
+	
+	
+	// Returns segments for the given characters, grouping runs of characters
+	// with the same mode (as computed by compute_character_modes()) together.
+	fn split_into_segments(chars: &[char], charmodes: &[QrSegmentMode],
+			options: &SegmentOptions) -> Vec<Self> {
+		let mut result: Vec<Self> = Vec::new();
+		let mut start: usize = 0;
+		for end in 1 ..= chars.len() {
+			if end < chars.len() && charmodes[end] == charmodes[start] {
+				continue;
+			}
+			let text: String = chars[start .. end].iter().collect();
+			result.push(match charmodes[start] {
+				QrSegmentMode::Byte         => QrSegment::make_bytes(&(options.encodebytes)(&text)),
+				QrSegmentMode::Numeric      => QrSegment::make_numeric(&text),
+				QrSegmentMode::Alphanumeric if options.fnc1 => QrSegment::make_alphanumeric(
+					&text.replace(GROUP_SEPARATOR, "%")),
+				QrSegmentMode::Alphanumeric => QrSegment::make_alphanumeric(&text),
+				QrSegmentMode::Kanji        => QrSegment::make_kanji(&text),
+				_ => unreachable!(),
+			});
+			start = end;
+		}
+		result
+	}
+	
+	
+	// Calculates and returns the number of bits needed to encode the given segments at the
+	// given Micro QR Code version. The result is None if a segment's mode isn't available in
+	// that version, or a segment has too many characters to fit its length field.
+	fn get_micro_total_bits(segs: &[Self], version: MicroVersion) -> Option<usize> {
 		let mut result: usize = 0;
 		for seg in segs {
-			let ccbits: u8 = seg.mode.num_char_count_bits(version);
-			// ccbits can be as large as 16, but usize can be as small as 16
-			if let Some(limit) = 1usize.checked_shl(ccbits.into()) 
-				&& seg.numchars >= limit {
+			seg.mode.micro_mode_bits()?;
+			let ccbits: u8 = seg.mode.num_micro_char_count_bits(version)?;
+			if seg.numchars >= 1usize << ccbits {
 				return None;  // The segment's length doesn't fit the field's bit width
 			}
-			result = result.checked_add(4 + usize::from(ccbits))?;
+			result = result.checked_add(usize::from(version.value() - 1 + ccbits))?;
 			result = result.checked_add(seg.data.len())?;
 		}
 		Some(result)
 	}
 	
 	
+	/// Returns the Structured Append parity byte of the given message data,
+	/// which is the XOR of all its bytes before it is split into symbols.
+	pub fn structured_append_parity(data: &[u8]) -> u8 {
+		data.iter().fold(0, |acc, &b| acc ^ b)
+	}
+	
+	
+	/// Returns the message bytes this segment holds, as a decoder reads them back: the
+	/// characters of numeric and alphanumeric segments, the bytes of byte segments and the
+	/// Shift JIS codes of kanji segments. Segments that hold no message data return none.
+	/// The Structured Append parity is computed over these bytes.
+	pub fn message_bytes(&self) -> Vec<u8> {
+		let mut bits = self.data.iter();
+		let mut read = |n: usize| -> u32 {
+			bits.by_ref().take(n).fold(0, |acc, &b| (acc << 1) | u32::from(b))
+		};
+		let mut result = Vec::with_capacity(self.numchars * 2);
+		match self.mode {
+			QrSegmentMode::Numeric => {
+				let mut remaining = self.numchars;
+				while remaining > 0 {
+					let n = remaining.min(3);
+					let value = read(n * 3 + 1);
+					result.extend(format!("{:01$}", value, n).bytes());
+					remaining -= n;
+				}
+			},
+			QrSegmentMode::Alphanumeric => {
+				let charset = ALPHANUMERIC_CHARSET.as_bytes();
+				let mut remaining = self.numchars;
+				while remaining > 0 {
+					if remaining >= 2 {
+						let value = read(11) as usize;
+						result.extend([charset[value / 45], charset[value % 45]]);
+						remaining -= 2;
+					} else {
+						result.push(charset[read(6) as usize]);
+						remaining -= 1;
+					}
+				}
+			},
+			QrSegmentMode::Byte => {
+				for _ in 0 .. self.numchars {
+					result.push(read(8) as u8);
+				}
+			},
+			QrSegmentMode::Kanji => {
+				for _ in 0 .. self.numchars {
+					let value = read(13);
+					let code = (value / 0xC0) << 8 | (value % 0xC0);
+					let code = if code < 0x1F00 { code + 0x8140 } else { code + 0xC140 };
+					result.extend([(code >> 8) as u8, code as u8]);
+				}
+			},
+			QrSegmentMode::Eci | QrSegmentMode::StructuredAppend
+				| QrSegmentMode::Fnc1First | QrSegmentMode::Fnc1Second => {},
+		}
+		result
+	}
+	
+	
 	/// Tests whether the given string can be encoded as a segment in numeric mode.
 	/// 
 	/// A string is encodable iff each character is in the range 0 to 9.
@@ -1126,6 +2071,33 @@ impl QrSegment {
 		text.chars().all(|c| ALPHANUMERIC_CHARSET.contains(c))
 	}
 	
+	
+	/// Tests whether the given string can be encoded as a segment in kanji mode.
+	/// 
+	/// A string is encodable iff each character has a double-byte Shift JIS
+	/// code in the range 0x8140 to 0x9FFC or 0xE040 to 0xEBBF.
+	pub fn is_kanji(text: &str) -> bool {
+		text.chars().all(|c| kanji_value(c).is_some())
+	}
+	
+}
+
+
+// Returns the 13-bit kanji mode value of the given character, or None
+// if it has no double-byte Shift JIS code in the kanji mode ranges.
+fn kanji_value(c: char) -> Option<u32> {
+	let mut buf = [0u8; 4];
+	let (bytes, _, unmappable) = SHIFT_JIS.encode(c.encode_utf8(&mut buf));
+	if unmappable || bytes.len() != 2 {
+		return None;
+	}
+	let code: u32 = u32::from(bytes[0]) << 8 | u32::from(bytes[1]);
+	let offset: u32 = match code {
+		0x8140 ..= 0x9FFC => code - 0x8140,
+		0xE040 ..= 0xEBBF => code - 0xC140,
+		_ => return None,
+	};
+	Some((offset >> 8) * 0xC0 + (offset & 0xFF))
 }
 
 
@@ -1134,6 +2106,59 @@ impl QrSegment {
 static ALPHANUMERIC_CHARSET: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
 
 
+// The ASCII group separator, which stands for FNC1 in the data of FNC1 mode symbols.
+const GROUP_SEPARATOR: char = '\u{1D}';
+
+
+// Tests whether the given character can be encoded in alphanumeric mode. In FNC1 mode, a group
+// separator is written as a percent sign there. A literal percent sign would have to be doubled,
+// which some decoders get wrong, so it is left to byte mode instead.
+fn is_alphanumeric_char(c: char, fnc1: bool) -> bool {
+	match c {
+		'%' if fnc1 => false,
+		GROUP_SEPARATOR => fnc1,
+		_ => ALPHANUMERIC_CHARSET.contains(c),
+	}
+}
+
+
+
+/*---- SegmentOptions functionality ----*/
+
+/// Options for building segments with `QrSegment::make_segments_optimally_with()`.
+pub struct SegmentOptions<'a> {
+	
+	/// Segments to place before the text, such as an ECI designator or an FNC1 indicator.
+	pub prefix: Vec<QrSegment>,
+	
+	/// Encodes text for byte mode. It is called on single characters to measure them,
+	/// so it must encode each character independently of its neighbors.
+	pub encodebytes: &'a dyn Fn(&str) -> Vec<u8>,
+	
+	/// Whether kanji mode may be used. Many decoders mishandle
+	/// kanji mode after an ECI designator other than Shift JIS.
+	pub allowkanji: bool,
+	
+	/// Whether the text is the data of an FNC1 mode symbol, where group separators (ASCII 29)
+	/// stand for FNC1. They are written as a percent sign in alphanumeric mode, so literal
+	/// percent signs are only encoded in byte mode.
+	pub fnc1: bool,
+	
+}
+
+
+impl Default for SegmentOptions<'_> {
+	fn default() -> Self {
+		Self {
+			prefix: Vec::new(),
+			encodebytes: &|s: &str| s.as_bytes().to_vec(),
+			allowkanji: true,
+			fnc1: false,
+		}
+	}
+}
+
+
 
 /*---- QrSegmentMode functionality ----*/
 
@@ -1145,6 +2170,9 @@ pub enum QrSegmentMode {
 	Byte,
 	Kanji,
 	Eci,
+	StructuredAppend,
+	Fnc1First,
+	Fnc1Second,
 }
 
 
@@ -1160,13 +2188,16 @@ impl QrSegmentMode {
 			Byte         => 0x4,
 			Kanji        => 0x8,
 			Eci          => 0x7,
+			StructuredAppend => 0x3,
+			Fnc1First    => 0x5,
+			Fnc1Second   => 0x9,
 		}
 	}
 	
 	
 	// Returns the bit width of the character count field for a segment in this mode
 	// in a QR Code at the given version number. The result is in the range [0, 16].
-	fn num_char_count_bits(self, ver: Version) -> u8 {
+	pub(crate) fn num_char_count_bits(self, ver: Version) -> u8 {
 		use QrSegmentMode::*;
 		(match self {
 			Numeric      => [10, 12, 14],
@@ -1174,9 +2205,41 @@ impl QrSegmentMode {
 			Byte         => [ 8, 16, 16],
 			Kanji        => [ 8, 10, 12],
 			Eci          => [ 0,  0,  0],
+			StructuredAppend => [ 0,  0,  0],
+			Fnc1First    => [ 0,  0,  0],
+			Fnc1Second   => [ 0,  0,  0],
 		})[usize::from((ver.value() + 7) / 17)]
 	}
 	
+	
+	// Returns the mode indicator bits for this mode in a Micro QR Code, whose width
+	// is the version number minus one. The result is None for modes without one (ECI, Structured Append, FNC1).
+	fn micro_mode_bits(self) -> Option<u32> {
+		use QrSegmentMode::*;
+		match self {
+			Numeric      => Some(0),
+			Alphanumeric => Some(1),
+			Byte         => Some(2),
+			Kanji        => Some(3),
+			Eci | StructuredAppend | Fnc1First | Fnc1Second => None,
+		}
+	}
+	
+	
+	// Returns the bit width of the character count field for a segment in this mode in a
+	// Micro QR Code at the given version number, or None if the version doesn't support the mode.
+	fn num_micro_char_count_bits(self, ver: MicroVersion) -> Option<u8> {
+		use QrSegmentMode::*;
+		let bits: i8 = (match self {
+			Numeric      => [ 3,  4,  5,  6],
+			Alphanumeric => [-1,  3,  4,  5],
+			Byte         => [-1, -1,  4,  5],
+			Kanji        => [-1, -1,  3,  4],
+			Eci | StructuredAppend | Fnc1First | Fnc1Second => [-1, -1, -1, -1],
+		})[usize::from(ver.value() - 1)];
+		u8::try_from(bits).ok()
+	}
+	
 }
 
 
@@ -1201,6 +2264,256 @@ impl BitBuffer {
 
 
 
+/*---- Reed-Solomon decoder functionality ----*/
+
+/// A decoder for the Reed-Solomon codes over GF(2<sup>8</sup>/0x11D) that protect
+/// the codeword blocks of QR Codes.
+/// 
+/// A block is a sequence of data codewords followed by error correction codewords,
+/// as produced by the encoder. With n error correction codewords, the decoder can
+/// repair any combination of e errors at unknown positions and f erasures at known
+/// positions such that 2e + f &#x2264; n.
+/// 
+/// The decoding goes through the usual steps: syndromes, the Berlekamp-Massey
+/// algorithm for the error locator polynomial, a Chien search for its roots, and
+/// the Forney algorithm for the error values.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ReedSolomonDecoder {
+	
+	// The number of error correction codewords at the end of each block. Range [1, 254].
+	ecclen: usize,
+	
+}
+
+
+impl ReedSolomonDecoder {
+	
+	/*---- Constructor ----*/
+	
+	/// Creates a decoder for blocks that end with the given number of error correction codewords.
+	/// 
+	/// Panics if the number is outside the range [1, 254].
+	pub fn new(ecclen: usize) -> Self {
+		assert!((1 .. 255).contains(&ecclen), "Degree out of range");
+		Self { ecclen }
+	}
+	
+	
+	/*---- Public methods ----*/
+	
+	/// Returns the number of error correction codewords at the end of each block.
+	pub fn ecc_len(&self) -> usize {
+		self.ecclen
+	}
+	
+	
+	/// Tests whether a block with the given numbers of errors at unknown positions
+	/// and erasures at known positions can be corrected.
+	pub fn can_correct(&self, errors: usize, erasures: usize) -> bool {
+		2 * errors + erasures <= self.ecclen
+	}
+	
+	
+	/// Returns the syndromes of the given block, i.e. the values of the block's polynomial
+	/// at 2<sup>0</sup>, 2<sup>1</sup>, ..., 2<sup>ecclen-1</sup>. They are all zero iff
+	/// the block is a valid codeword.
+	pub fn syndromes(&self, block: &[u8]) -> Vec<u8> {
+		(0 .. self.ecclen).map(|i| {
+			let x: u8 = rs_exp(i);
+			// The first codeword is the coefficient of the highest power
+			block.iter().fold(0, |acc, &b| rs_multiply(acc, x) ^ b)
+		}).collect()
+	}
+	
+	
+	/// Repairs the given block in place, and returns the indexes of the codewords that were
+	/// changed, in increasing order. The erasures are the indexes of codewords known to be
+	/// unreliable, such as those hidden under a logo; their values are ignored.
+	/// 
+	/// Returns an error if the block is shorter than its error correction, has more than
+	/// 255 codewords, or has more errors and erasures than can be corrected. Too many errors
+	/// can also go unnoticed and be "corrected" into a different valid codeword, which is
+	/// inherent to Reed-Solomon codes.
+	pub fn decode(&self, block: &mut [u8], erasures: &[usize]) -> Result<Vec<usize>,ReedSolomonError> {
+		let n: usize = block.len();
+		if n <= self.ecclen || n > 255 {
+			return Err(ReedSolomonError::InvalidBlockLength(n));
+		}
+		let mut erasures: Vec<usize> = erasures.to_vec();
+		erasures.sort_unstable();
+		erasures.dedup();
+		if let Some(&i) = erasures.iter().find(|&&i| i >= n) {
+			return Err(ReedSolomonError::InvalidErasure(i));
+		}
+		if !self.can_correct(0, erasures.len()) {
+			return Err(ReedSolomonError::TooManyErrors);
+		}
+		
+		let syndromes: Vec<u8> = self.syndromes(block);
+		if syndromes.iter().all(|&s| s == 0) {
+			return Ok(Vec::new());
+		}
+		
+		// The codeword at index i is the coefficient of x^(n-1-i), and its locator is 2^(n-1-i)
+		let locators: Vec<u8> = erasures.iter().map(|&i| rs_exp(n - 1 - i)).collect();
+		let locator: Vec<u8> = self.errata_locator(&syndromes, &locators)?;
+		
+		// Chien search: the roots of the locator are the inverses of the codeword locators
+		let positions: Vec<usize> = (0 .. n)
+			.filter(|&i| rs_evaluate(&locator, rs_exp(255 - (n - 1 - i))) == 0)
+			.collect();
+		if positions.len() != locator.len() - 1 {
+			return Err(ReedSolomonError::TooManyErrors);
+		}
+		
+		// Forney algorithm: the evaluator is the product of the syndromes and the locator,
+		// modulo x^ecclen, and the derivative of the locator keeps the odd powers in GF(2^8)
+		let mut evaluator = vec![0u8; self.ecclen];
+		for (i, &s) in syndromes.iter().enumerate() {
+			for (j, &l) in locator.iter().enumerate().take(self.ecclen - i) {
+				evaluator[i + j] ^= rs_multiply(s, l);
+			}
+		}
+		let derivative: Vec<u8> = locator.iter().enumerate().skip(1)
+			.map(|(j, &l)| if j % 2 == 1 { l } else { 0 })
+			.collect();
+		let mut changed = Vec::new();
+		for &i in &positions {
+			let power: usize = n - 1 - i;
+			let xinv: u8 = rs_exp(255 - power);
+			let denominator: u8 = rs_evaluate(&derivative, xinv);
+			if denominator == 0 {
+				return Err(ReedSolomonError::TooManyErrors);
+			}
+			// Multiplied by x^(1 - fcr), where the first consecutive root is 2^0
+			let magnitude: u8 = rs_multiply(rs_exp(power), rs_divide(rs_evaluate(&evaluator, xinv), denominator));
+			if magnitude != 0 {
+				block[i] ^= magnitude;
+				changed.push(i);
+			}
+		}
+		
+		// Reject miscorrections, which happen with more errors than the code can handle
+		if self.syndromes(block).iter().any(|&s| s != 0) {
+			return Err(ReedSolomonError::TooManyErrors);
+		}
+		Ok(changed)
+	}
+	
+	
+	/*---- Private helper methods ----*/
+	
+	// Returns the errata locator polynomial, whose roots are the inverses of the locators
+	// of the erased and erroneous codewords, with coefficients from the lowest power up.
+	// This is the Berlekamp-Massey algorithm, started from the erasure locator polynomial.
+	fn errata_locator(&self, syndromes: &[u8], erasurelocators: &[u8]) -> Result<Vec<u8>,ReedSolomonError> {
+		// The erasure locator is the product of (1 - X x) over the erasure locators X
+		let mut locator: Vec<u8> = vec![1];
+		for &x in erasurelocators {
+			locator.push(0);
+			for j in (1 .. locator.len()).rev() {
+				locator[j] ^= rs_multiply(locator[j - 1], x);
+			}
+		}
+		
+		let numerasures: usize = erasurelocators.len();
+		let mut previous: Vec<u8> = locator.clone();
+		let mut degree: usize = numerasures;
+		let mut shift: usize = 1;
+		let mut prevdiscrepancy: u8 = 1;
+		for i in numerasures .. self.ecclen {
+			let discrepancy: u8 = (1 ..= degree.min(locator.len() - 1))
+				.fold(syndromes[i], |acc, j| acc ^ rs_multiply(locator[j], syndromes[i - j]));
+			if discrepancy == 0 {
+				shift += 1;
+				continue;
+			}
+			let factor: u8 = rs_divide(discrepancy, prevdiscrepancy);
+			let mut next: Vec<u8> = locator.clone();
+			next.resize(next.len().max(previous.len() + shift), 0);
+			for (j, &p) in previous.iter().enumerate() {
+				next[j + shift] ^= rs_multiply(factor, p);
+			}
+			if 2 * degree <= i + numerasures {
+				degree = i + 1 + numerasures - degree;
+				previous = std::mem::replace(&mut locator, next);
+				prevdiscrepancy = discrepancy;
+				shift = 1;
+			} else {
+				locator = next;
+				shift += 1;
+			}
+		}
+		
+		if !self.can_correct(degree - numerasures, numerasures) {
+			return Err(ReedSolomonError::TooManyErrors);
+		}
+		// Drop the zero coefficients above the degree
+		locator.truncate(degree + 1);
+		while locator.len() > 1 && locator.last() == Some(&0) {
+			locator.pop();
+		}
+		Ok(locator)
+	}
+	
+}
+
+
+// Exponentials and logarithms of the elements of GF(2^8/0x11D) for the generator 0x02.
+// The exponentials are repeated so that the sum of two logarithms needs no reduction.
+const RS_TABLES: ([u8; 510], [u8; 256]) = {
+	let mut exp = [0u8; 510];
+	let mut log = [0u8; 256];
+	let mut x: u16 = 1;
+	let mut i: usize = 0;
+	while i < 255 {
+		exp[i] = x as u8;
+		exp[i + 255] = x as u8;
+		log[x as usize] = i as u8;
+		x <<= 1;
+		if x & 0x100 != 0 {
+			x ^= 0x11D;
+		}
+		i += 1;
+	}
+	(exp, log)
+};
+
+
+// Returns 2 raised to the given power in GF(2^8/0x11D).
+fn rs_exp(power: usize) -> u8 {
+	RS_TABLES.0[power % 255]
+}
+
+
+// Returns the product of the two given field elements, using the tables.
+fn rs_multiply(x: u8, y: u8) -> u8 {
+	if x == 0 || y == 0 {
+		return 0;
+	}
+	let (exp, log) = &RS_TABLES;
+	exp[usize::from(log[usize::from(x)]) + usize::from(log[usize::from(y)])]
+}
+
+
+// Returns the quotient of the two given field elements. Requires y != 0.
+fn rs_divide(x: u8, y: u8) -> u8 {
+	assert!(y != 0, "Division by zero");
+	if x == 0 {
+		return 0;
+	}
+	let (exp, log) = &RS_TABLES;
+	exp[usize::from(log[usize::from(x)]) + 255 - usize::from(log[usize::from(y)])]
+}
+
+
+// Evaluates the given polynomial, with coefficients from the lowest power up, at x.
+fn rs_evaluate(poly: &[u8], x: u8) -> u8 {
+	poly.iter().rev().fold(0, |acc, &c| rs_multiply(acc, x) ^ c)
+}
+
+
+
 /*---- Miscellaneous values ----*/
 
 /// The error type when the supplied data does not fit any QR Code version.
@@ -1219,6 +2532,8 @@ impl BitBuffer {
 pub enum DataTooLong {
 	SegmentTooLong,
 	DataOverCapacity(usize, usize),
+	/// A segment is in a mode that no version up to the given one supports.
+	UnsupportedMode(QrSegmentMode, String),
 }
 
 impl std::error::Error for DataTooLong {}
@@ -1229,6 +2544,32 @@ impl std::fmt::Display for DataTooLong {
 			Self::SegmentTooLong => write!(f, "Segment too long"),
 			Self::DataOverCapacity(datalen, maxcapacity) =>
 				write!(f, "Data length = {} bits, Max capacity = {} bits", datalen, maxcapacity),
+			Self::UnsupportedMode(mode, ref maxversion) =>
+				write!(f, "{:?} mode is not supported up to version {}", mode, maxversion),
+		}
+	}
+}
+
+
+/// The error type when a Reed-Solomon block cannot be decoded.
+#[derive(Debug, Clone)]
+pub enum ReedSolomonError {
+	/// The block has more errors and erasures than its error correction can repair.
+	TooManyErrors,
+	/// The block is not longer than its error correction, or longer than 255 codewords.
+	InvalidBlockLength(usize),
+	/// An erasure index is past the end of the block.
+	InvalidErasure(usize),
+}
+
+impl std::error::Error for ReedSolomonError {}
+
+impl std::fmt::Display for ReedSolomonError {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match *self {
+			Self::TooManyErrors => write!(f, "Too many errors to correct"),
+			Self::InvalidBlockLength(len) => write!(f, "Invalid block length = {} codewords", len),
+			Self::InvalidErasure(index) => write!(f, "Erasure index = {} is out of the block", index),
 		}
 	}
 }
@@ -1260,6 +2601,32 @@ impl Version {
 }
 
 
+/// A Micro QR Code version number between 1 and 4 (inclusive), i.e. M1 to M4.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct MicroVersion(u8);
+
+impl MicroVersion {
+	/// The minimum version number (M1) supported in the Micro QR Code standard.
+	pub const MIN: MicroVersion = MicroVersion(1);
+	
+	/// The maximum version number (M4) supported in the Micro QR Code standard.
+	pub const MAX: MicroVersion = MicroVersion(4);
+	
+	/// Creates a version object from the given number.
+	/// 
+	/// Panics if the number is outside the range [1, 4].
+	pub const fn new(ver: u8) -> Self {
+		assert!(MicroVersion::MIN.value() <= ver && ver <= MicroVersion::MAX.value(), "Version number out of range");
+		Self(ver)
+	}
+	
+	/// Returns the value, which is in the range [1, 4].
+	pub const fn value(self) -> u8 {
+		self.0
+	}
+}
+
+
 /// A number between 0 and 7 (inclusive).
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
 pub struct Mask(u8);
@@ -1284,3 +2651,324 @@ impl Mask {
 fn get_bit(x: u32, i: i32) -> bool {
 	(x >> i) & 1 != 0
 }
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	const ECC_LEN: usize = 10;
+	
+	// Returns a valid block of 30 data codewords followed by their error correction codewords.
+	fn clean_block() -> Vec<u8> {
+		let data: Vec<u8> = (0u32 .. 30).map(|i| (i * 37 + 11) as u8).collect();
+		let divisor: Vec<u8> = QrCode::reed_solomon_compute_divisor(ECC_LEN);
+		let mut block: Vec<u8> = data.clone();
+		block.extend(QrCode::reed_solomon_compute_remainder(&data, &divisor));
+		block
+	}
+	
+	// Flips some bits of the codewords at the given indexes.
+	fn corrupt(block: &mut [u8], indexes: &[usize]) {
+		for &i in indexes {
+			block[i] ^= 0x5A;
+		}
+	}
+	
+	
+	#[test]
+	fn clean_block_is_unchanged() {
+		let decoder = ReedSolomonDecoder::new(ECC_LEN);
+		let mut block: Vec<u8> = clean_block();
+		assert!(decoder.syndromes(&block).iter().all(|&s| s == 0));
+		assert_eq!(decoder.decode(&mut block, &[]).unwrap(), Vec::<usize>::new());
+		assert_eq!(block, clean_block());
+	}
+	
+	
+	#[test]
+	fn corrects_errors_only() {
+		let decoder = ReedSolomonDecoder::new(ECC_LEN);
+		let errors = [0, 7, 19, 31, 39];
+		let mut block: Vec<u8> = clean_block();
+		corrupt(&mut block, &errors);
+		assert_eq!(decoder.decode(&mut block, &[]).unwrap(), errors);
+		assert_eq!(block, clean_block());
+	}
+	
+	
+	#[test]
+	fn corrects_erasures_only() {
+		let decoder = ReedSolomonDecoder::new(ECC_LEN);
+		let erasures: Vec<usize> = (10 .. 20).collect();
+		let mut block: Vec<u8> = clean_block();
+		corrupt(&mut block, &erasures);
+		assert_eq!(decoder.decode(&mut block, &erasures).unwrap(), erasures);
+		assert_eq!(block, clean_block());
+	}
+	
+	
+	#[test]
+	fn corrects_errors_and_erasures_at_capacity() {
+		let decoder = ReedSolomonDecoder::new(ECC_LEN);
+		for errors in 0 ..= ECC_LEN / 2 {
+			let erasures: Vec<usize> = (0 .. ECC_LEN - 2 * errors).map(|i| i * 3 + 1).collect();
+			let unknown: Vec<usize> = (0 .. errors).map(|i| 39 - i * 2).collect();
+			assert!(decoder.can_correct(errors, erasures.len()));
+			let mut block: Vec<u8> = clean_block();
+			corrupt(&mut block, &erasures);
+			corrupt(&mut block, &unknown);
+			let mut expected: Vec<usize> = [erasures.as_slice(), &unknown].concat();
+			expected.sort_unstable();
+			assert_eq!(decoder.decode(&mut block, &erasures).unwrap(), expected);
+			assert_eq!(block, clean_block());
+		}
+	}
+	
+	
+	#[test]
+	fn rejects_too_many_corruptions() {
+		let decoder = ReedSolomonDecoder::new(ECC_LEN);
+		assert!(!decoder.can_correct(0, ECC_LEN + 1));
+		assert!(!decoder.can_correct(ECC_LEN / 2 + 1, 0));
+		
+		let erasures: Vec<usize> = (0 .. ECC_LEN + 1).collect();
+		let mut block: Vec<u8> = clean_block();
+		corrupt(&mut block, &erasures);
+		assert!(matches!(decoder.decode(&mut block, &erasures), Err(ReedSolomonError::TooManyErrors)));
+		
+		let erasures: Vec<usize> = (0 .. ECC_LEN - 1).collect();
+		let mut block: Vec<u8> = clean_block();
+		corrupt(&mut block, &erasures);
+		corrupt(&mut block, &[25]);
+		assert!(matches!(decoder.decode(&mut block, &erasures), Err(ReedSolomonError::TooManyErrors)));
+	}
+	
+	
+	#[test]
+	fn rejects_invalid_arguments() {
+		let decoder = ReedSolomonDecoder::new(ECC_LEN);
+		let mut block: Vec<u8> = clean_block();
+		assert!(matches!(decoder.decode(&mut block[.. ECC_LEN], &[]), Err(ReedSolomonError::InvalidBlockLength(ECC_LEN))));
+		assert!(matches!(decoder.decode(&mut block, &[40]), Err(ReedSolomonError::InvalidErasure(40))));
+	}
+	
+	
+	// Encodes the given segments in exactly the given Micro QR Code version.
+	fn micro(segs: &[QrSegment], ecl: QrCodeEcc, ver: u8) -> Result<MicroQrCode,DataTooLong> {
+		let ver = MicroVersion::new(ver);
+		MicroQrCode::encode_segments_advanced(segs, ecl, ver, ver, None, false)
+	}
+	
+	
+	#[test]
+	fn micro_annex_example() {
+		// ISO/IEC 18004 Annex I: "01234567" in M2-L
+		let data: [u8; 5] = [0x40, 0x18, 0xAC, 0xC3, 0x00];
+		let divisor: Vec<u8> = QrCode::reed_solomon_compute_divisor(5);
+		assert_eq!(QrCode::reed_solomon_compute_remainder(&data, &divisor), [0x86, 0x0D, 0x22, 0xAE, 0x30]);
+		
+		let qr = micro(&QrSegment::make_segments("01234567"), QrCodeEcc::Low, 2).unwrap();
+		assert_eq!((qr.version(), qr.size(), qr.error_correction_level()), (MicroVersion::new(2), 13, QrCodeEcc::Low));
+		assert_eq!(qr.mask(), Mask::new(1));
+		assert!(qr == MicroQrCode::encode_codewords(MicroVersion::new(2), QrCodeEcc::Low, &data, Some(Mask::new(1))));
+		let expected = [
+			"#######.#.#.#",
+			"#.....#.###.#",
+			"#.###.#..##.#",
+			"#.###.#..####",
+			"#.###.#.###..",
+			"#.....#.#...#",
+			"#######..####",
+			".........##..",
+			"##.#....#...#",
+			".##.#.#.#.#.#",
+			"###..#######.",
+			"...#.#....##.",
+			"###.#..##.###",
+		];
+		for (y, row) in (0 ..).zip(expected) {
+			let actual: String = (0 .. 13).map(|x| if qr.get_module(x, y) { '#' } else { '.' }).collect();
+			assert_eq!(actual, row, "row {}", y);
+		}
+	}
+	
+	
+	#[test]
+	fn micro_numeric_capacities() {
+		// ISO/IEC 18004 Table 7: the most digits each version and level holds
+		let capacities = [
+			(1, QrCodeEcc::Low, 5),
+			(2, QrCodeEcc::Low, 10),
+			(2, QrCodeEcc::Medium, 8),
+			(3, QrCodeEcc::Low, 23),
+			(3, QrCodeEcc::Medium, 18),
+			(4, QrCodeEcc::Low, 35),
+			(4, QrCodeEcc::Medium, 30),
+			(4, QrCodeEcc::Quartile, 21),
+		];
+		for (ver, ecl, digits) in capacities {
+			let fits = |n: usize| micro(&[QrSegment::make_numeric(&"7".repeat(n))], ecl, ver).is_ok();
+			assert!(fits(digits), "M{} {:?} holds {} digits", ver, ecl, digits);
+			assert!(!fits(digits + 1), "M{} {:?} holds only {} digits", ver, ecl, digits);
+		}
+	}
+	
+	
+	#[test]
+	fn micro_codeword_tables() {
+		// Data and error correction codewords add up to the total of each version,
+		// with the final data codeword of M1 and M3 being 4 bits long
+		let totals = [5, 10, 17, 24];
+		for ver in 1 ..= 4 {
+			let ver = MicroVersion::new(ver);
+			for ecl in [QrCodeEcc::Low, QrCodeEcc::Medium, QrCodeEcc::Quartile] {
+				if let Some(bits) = MicroQrCode::get_num_data_bits(ver, ecl) {
+					let ecc = MicroQrCode::table_get(&MICRO_ECC_CODEWORDS, ver, ecl) as usize;
+					assert_eq!(bits.div_ceil(8) + ecc, totals[usize::from(ver.value()) - 1]);
+					assert_eq!(bits % 8 == 4, ver.value() % 2 == 1);
+				}
+			}
+			assert!(MicroQrCode::get_num_data_bits(ver, QrCodeEcc::High).is_none());
+		}
+		assert!(MicroQrCode::get_num_data_bits(MicroVersion::new(1), QrCodeEcc::Medium).is_none());
+		assert!(MicroQrCode::get_num_data_bits(MicroVersion::new(3), QrCodeEcc::Quartile).is_none());
+	}
+	
+	
+	#[test]
+	fn micro_rejects_unsupported_modes() {
+		let alpha = [QrSegment::make_alphanumeric("AB")];
+		assert!(matches!(micro(&alpha, QrCodeEcc::Low, 1),
+			Err(DataTooLong::UnsupportedMode(QrSegmentMode::Alphanumeric, ref ver)) if ver == "M1"));
+		assert_eq!(micro(&alpha, QrCodeEcc::Low, 2).unwrap().version(), MicroVersion::new(2));
+		
+		let bytes = [QrSegment::make_bytes(b"ab")];
+		let upto_m2 = MicroQrCode::encode_segments_advanced(&bytes, QrCodeEcc::Low,
+			MicroVersion::MIN, MicroVersion::new(2), None, false);
+		assert!(matches!(upto_m2, Err(DataTooLong::UnsupportedMode(QrSegmentMode::Byte, ref ver)) if ver == "M2"));
+		assert_eq!(MicroQrCode::encode_segments(&bytes, QrCodeEcc::Low).unwrap().version(), MicroVersion::new(3));
+		
+		let eci = [QrSegment::make_eci(26), QrSegment::make_bytes(b"ab")];
+		assert!(matches!(MicroQrCode::encode_segments(&eci, QrCodeEcc::Low),
+			Err(DataTooLong::UnsupportedMode(QrSegmentMode::Eci, ref ver)) if ver == "M4"));
+	}
+	
+	
+	// Reads the given bits as a big endian number.
+	fn bits_value(bits: &[bool]) -> u32 {
+		bits.iter().fold(0, |acc, &b| acc << 1 | u32::from(b))
+	}
+	
+	
+	#[test]
+	fn kanji_values() {
+		// ISO/IEC 18004 8.4.5: Shift JIS 0x935F and 0xE4AA
+		assert_eq!(kanji_value('点'), Some(0x0D9F));
+		assert_eq!(kanji_value('茗'), Some(0x1AAA));
+		// Full-width symbols and kana are in kanji mode too, from Shift JIS 0x8140
+		assert_eq!(kanji_value('\u{3000}'), Some(0x0000));
+		assert_eq!(kanji_value('Ａ'), Some(0x00E0));
+		assert_eq!(kanji_value('あ'), Some(0x0120));
+		assert_eq!(kanji_value('亜'), Some(0x059F));
+	}
+	
+	
+	#[test]
+	fn rejects_non_kanji_characters() {
+		for c in ['A', '1', ' ', '\u{FF71}', 'é', '€', '😀'] {
+			assert_eq!(kanji_value(c), None, "{:?}", c);
+			assert!(!QrSegment::is_kanji(&format!("点{}", c)), "{:?}", c);
+		}
+		assert!(QrSegment::is_kanji("点茗"));
+		assert!(QrSegment::is_kanji(""));
+	}
+	
+	
+	#[test]
+	fn packs_kanji_in_13_bits() {
+		let seg = QrSegment::make_kanji("点茗");
+		assert_eq!(seg.mode(), QrSegmentMode::Kanji);
+		assert_eq!(seg.num_chars(), 2);
+		assert_eq!(seg.data().len(), 26);
+		assert_eq!(bits_value(&seg.data()[.. 13]), 0x0D9F);
+		assert_eq!(bits_value(&seg.data()[13 ..]), 0x1AAA);
+	}
+	
+	
+	#[test]
+	fn make_segments_picks_kanji() {
+		let segs = QrSegment::make_segments("点茗");
+		assert_eq!(segs.len(), 1);
+		assert_eq!(segs[0].mode(), QrSegmentMode::Kanji);
+		assert_eq!(segs[0].data(), QrSegment::make_kanji("点茗").data());
+		
+		// Anything outside kanji mode falls back to UTF-8 bytes
+		let segs = QrSegment::make_segments("点A");
+		assert_eq!(segs.len(), 1);
+		assert_eq!(segs[0].mode(), QrSegmentMode::Byte);
+		assert_eq!(segs[0].num_chars(), "点A".len());
+	}
+	
+	
+	// Returns the modes and character counts of the given segments.
+	fn seg_modes(segs: &[QrSegment]) -> Vec<(QrSegmentMode, usize)> {
+		segs.iter().map(|seg| (seg.mode(), seg.num_chars())).collect()
+	}
+	
+	
+	#[test]
+	fn optimal_segments_mix_modes() {
+		let text = "ORDER-1234567890-abc";
+		let segs = QrSegment::make_segments_optimally(text, QrCodeEcc::Low, Version::MIN, Version::MAX).unwrap();
+		assert_eq!(seg_modes(&segs), [
+			(QrSegmentMode::Alphanumeric, 6),
+			(QrSegmentMode::Numeric, 10),
+			(QrSegmentMode::Byte, 4),
+		]);
+		let single = QrSegment::make_segments(text);
+		assert_eq!(seg_modes(&single), [(QrSegmentMode::Byte, 20)]);
+		let ver = Version::new(1);
+		assert_eq!(QrSegment::get_total_bits(&segs, ver), Some(138));
+		assert_eq!(QrSegment::get_total_bits(&single, ver), Some(172));
+		
+		let qr = QrCode::encode_segments_advanced(&segs, QrCodeEcc::Low, Version::MIN, Version::MAX, None, false).unwrap();
+		assert_eq!(qr.version(), ver);
+		assert!(QrCode::encode_segments_advanced(&single, QrCodeEcc::Low, ver, ver, None, false).is_err());
+	}
+	
+	
+	#[test]
+	fn optimal_segments_follow_character_count_widths() {
+		let at = |text: &str, ver: u8| {
+			let ver = Version::new(ver);
+			seg_modes(&QrSegment::make_segments_optimally(text, QrCodeEcc::Low, ver, ver).unwrap())
+		};
+		
+		// Wider character counts from version 10 make a numeric run of 7 digits not worth its headers
+		let text = "abc1234567def";
+		assert_eq!(at(text, 9), [
+			(QrSegmentMode::Byte, 3),
+			(QrSegmentMode::Numeric, 7),
+			(QrSegmentMode::Byte, 3),
+		]);
+		assert_eq!(at(text, 10), [(QrSegmentMode::Byte, 13)]);
+		
+		// And from version 27, a run of 8 digits
+		let text = "abc12345678def";
+		assert_eq!(at(text, 26), [
+			(QrSegmentMode::Byte, 3),
+			(QrSegmentMode::Numeric, 8),
+			(QrSegmentMode::Byte, 3),
+		]);
+		assert_eq!(at(text, 27), [(QrSegmentMode::Byte, 14)]);
+		
+		// Searching a range recomputes the segments for the version it settles on
+		let long = format!("{}{}", "x".repeat(200), text);
+		let segs = QrSegment::make_segments_optimally(&long, QrCodeEcc::High, Version::MIN, Version::MAX).unwrap();
+		let qr = QrCode::encode_segments_advanced(&segs, QrCodeEcc::High, Version::MIN, Version::MAX, None, false).unwrap();
+		assert!((10 ..= 26).contains(&qr.version().value()));
+		assert_eq!(seg_modes(&segs), at(&long, qr.version().value()));
+	}
+	
+}
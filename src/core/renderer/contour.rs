@@ -0,0 +1,224 @@
+use crate::core::renderer::finder::Outline;
+use crate::core::renderer::{QrGrid, custom_module_shape, module_shape};
+use crate::models::{ModuleShape, QrConfig};
+
+/// Traces the dark modules drawn as squares into the outlines of the regions they form,
+/// in pixels. Outer outlines run clockwise and the outlines of holes counterclockwise,
+/// so filling them with the nonzero rule draws every region in one piece, without seams
+/// between its modules. Regions touching only at a corner are kept apart.
+pub fn square_contours<G: QrGrid + ?Sized>(
+    grid: &G,
+    config: &QrConfig,
+    scale: f32,
+) -> Vec<Outline> {
+    let (width, height) = (grid.width(), grid.height());
+    let square = |x: usize, y: usize| {
+        x < width
+            && y < height
+            && grid.is_dark(x, y)
+            && module_shape(grid, config, x, y) == Some(ModuleShape::Square)
+            && custom_module_shape(grid, config, x, y).is_none()
+    };
+
+    // Sides of square modules that border anything else, going clockwise around each module
+    let mut edges = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            if !square(x, y) {
+                continue;
+            }
+            if y == 0 || !square(x, y - 1) {
+                edges.push(((x, y), (x + 1, y)));
+            }
+            if !square(x + 1, y) {
+                edges.push(((x + 1, y), (x + 1, y + 1)));
+            }
+            if !square(x, y + 1) {
+                edges.push(((x + 1, y + 1), (x, y + 1)));
+            }
+            if x == 0 || !square(x - 1, y) {
+                edges.push(((x, y + 1), (x, y)));
+            }
+        }
+    }
+
+    // Edges by the corner they start from: at most two, where regions touch diagonally
+    let columns = width + 1;
+    let mut starting = vec![[usize::MAX; 2]; columns * (height + 1)];
+    for (i, &((x, y), _)) in edges.iter().enumerate() {
+        let slot = &mut starting[y * columns + x];
+        slot[usize::from(slot[0] != usize::MAX)] = i;
+    }
+
+    let quiet_zone = config.quiet_zone as f32;
+    let point = |(x, y): (usize, usize)| {
+        (
+            (x as f32 + quiet_zone) * scale,
+            (y as f32 + quiet_zone) * scale,
+        )
+    };
+    let direction = |(from, to): ((usize, usize), (usize, usize))| {
+        (
+            to.0 as isize - from.0 as isize,
+            to.1 as isize - from.1 as isize,
+        )
+    };
+
+    let mut used = vec![false; edges.len()];
+    let mut contours = Vec::new();
+    for first in 0..edges.len() {
+        if used[first] {
+            continue;
+        }
+        let mut points = Vec::new();
+        let mut current = first;
+        loop {
+            used[current] = true;
+            let (dx, dy) = direction(edges[current]);
+            let end = edges[current].1;
+            let candidates = starting[end.1 * columns + end.0];
+            // Where two edges leave a corner, turn right to stay around the same module
+            let next =
+                if candidates[1] == usize::MAX || direction(edges[candidates[0]]) == (-dy, dx) {
+                    candidates[0]
+                } else {
+                    candidates[1]
+                };
+            // Only corners where the outline turns are kept
+            if direction(edges[next]) != (dx, dy) {
+                points.push(point(end));
+            }
+            if next == first {
+                break;
+            }
+            current = next;
+        }
+        contours.push(Outline::Polygon(points));
+    }
+    contours
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::qrgen::{QrCode, QrCodeEcc};
+    use crate::core::renderer::FinderPattern;
+
+    /// A grid drawn with '#' for dark modules, without finder patterns.
+    struct Drawing(Vec<&'static str>);
+
+    impl QrGrid for Drawing {
+        fn width(&self) -> usize {
+            self.0[0].len()
+        }
+
+        fn height(&self) -> usize {
+            self.0.len()
+        }
+
+        fn get_module(&self, x: usize, y: usize) -> bool {
+            self.0[y].as_bytes()[x] == b'#'
+        }
+
+        fn finder_patterns(&self) -> Vec<FinderPattern> {
+            Vec::new()
+        }
+    }
+
+    fn trace(rows: &[&'static str]) -> Vec<Vec<(f32, f32)>> {
+        let config = QrConfig {
+            quiet_zone: 0,
+            ..Default::default()
+        };
+        square_contours(&Drawing(rows.to_vec()), &config, 1.0)
+            .into_iter()
+            .map(|outline| match outline {
+                Outline::Polygon(points) => points,
+                other => panic!("Unexpected outline {:?}", other),
+            })
+            .collect()
+    }
+
+    /// Area enclosed by the points, positive when they run clockwise.
+    fn signed_area(points: &[(f32, f32)]) -> f32 {
+        let next = points.iter().cycle().skip(1);
+        points
+            .iter()
+            .zip(next)
+            .map(|(a, b)| a.0 * b.1 - b.0 * a.1)
+            .sum::<f32>()
+            / 2.0
+    }
+
+    #[test]
+    fn traces_a_single_module_clockwise() {
+        assert_eq!(
+            trace(&["#"]),
+            vec![vec![(1.0, 0.0), (1.0, 1.0), (0.0, 1.0), (0.0, 0.0)]]
+        );
+    }
+
+    #[test]
+    fn merges_adjacent_modules() {
+        let contours = trace(&["##.", ".##"]);
+        assert_eq!(contours.len(), 1);
+        assert_eq!(contours[0].len(), 8);
+        assert_eq!(signed_area(&contours[0]), 4.0);
+    }
+
+    #[test]
+    fn keeps_diagonal_neighbours_apart() {
+        let contours = trace(&["#.", ".#"]);
+        assert_eq!(contours.len(), 2);
+        for contour in &contours {
+            assert_eq!(contour.len(), 4);
+            assert_eq!(signed_area(contour), 1.0);
+        }
+
+        // Two regions touching at two corners around a light module
+        let contours = trace(&["##.", "#.#", ".##"]);
+        assert_eq!(contours.len(), 2);
+        for contour in &contours {
+            assert_eq!(contour.len(), 6);
+            assert_eq!(signed_area(contour), 3.0);
+        }
+    }
+
+    #[test]
+    fn traces_holes_counterclockwise() {
+        let contours = trace(&["###", "#.#", "###"]);
+        let mut areas: Vec<f32> = contours.iter().map(|c| signed_area(c)).collect();
+        areas.sort_by(f32::total_cmp);
+        assert_eq!(areas, vec![-1.0, 9.0]);
+
+        // Holes touching at a corner, where the modules around them keep them apart
+        let contours = trace(&["####", "#.##", "##.#", "####"]);
+        let mut areas: Vec<f32> = contours.iter().map(|c| signed_area(c)).collect();
+        areas.sort_by(f32::total_cmp);
+        assert_eq!(areas.iter().sum::<f32>(), 14.0);
+        assert!(areas[..areas.len() - 1].iter().all(|&area| area < 0.0));
+    }
+
+    #[test]
+    fn covers_every_square_module_of_a_symbol() {
+        let qr = QrCode::encode_text("https://example.com/contours", QrCodeEcc::Medium).unwrap();
+        let config = QrConfig {
+            quiet_zone: 0,
+            ..Default::default()
+        };
+        let modules = (0..qr.height())
+            .flat_map(|y| (0..qr.width()).map(move |x| (x, y)))
+            .filter(|&(x, y)| {
+                qr.is_dark(x, y) && module_shape(&qr, &config, x, y) == Some(ModuleShape::Square)
+            })
+            .count();
+        let area: f32 = square_contours(&qr, &config, 1.0)
+            .iter()
+            .map(|outline| match outline {
+                Outline::Polygon(points) => signed_area(points),
+                _ => 0.0,
+            })
+            .sum();
+        assert_eq!(area, modules as f32);
+    }
+}
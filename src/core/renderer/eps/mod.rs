@@ -1,12 +1,14 @@
+use crate::core::renderer::geometry::{Path, SymbolGeometry};
+use crate::core::renderer::gradient::Fill;
+use crate::core::renderer::icon::{ICON_BORDER_WIDTH, IconArea, IconLayout};
+use crate::core::renderer::vector::VectorIcon;
 use crate::core::renderer::{QrGrid, QrRenderer, utils};
-use crate::models::{GradientDirection, QrConfig, QrImage};
+use crate::models::{Color, QrConfig, QrImage};
 use std::fmt::Write;
 
-mod finder;
-mod module;
+mod path;
 
-use finder::append_finder_path;
-use module::append_module_path;
+use path::{append_path, clip_operator, fill_operator};
 
 /// Renders the QR code grid into an EPS string based on the provided configuration.
 pub fn render_eps<G: QrGrid + ?Sized>(
@@ -14,11 +16,11 @@ pub fn render_eps<G: QrGrid + ?Sized>(
     options: &QrConfig,
     pixel_size: f32,
 ) -> Result<String, String> {
-    let size = grid.size();
+    let width = grid.width();
+    let height = grid.height();
     let quiet_zone = options.quiet_zone as f32;
-    let width_modules = size as f32 + quiet_zone * 2.0;
-    let width_px = width_modules * pixel_size;
-    let height_px = width_px; // QR codes are square
+    let width_px = (width as f32 + quiet_zone * 2.0) * pixel_size;
+    let height_px = (height as f32 + quiet_zone * 2.0) * pixel_size;
 
     let mut eps = String::new();
 
@@ -31,175 +33,194 @@ pub fn render_eps<G: QrGrid + ?Sized>(
     writeln!(&mut eps, "0 {} translate", height_px).unwrap();
     writeln!(&mut eps, "1 -1 scale").unwrap();
 
-    if let Some(bg_color) = parse_color(&options.background) {
+    // PostScript has no transparency, so colors that can be seen through are blended
+    // with the background, or with white if it is transparent, which is left unpainted
+    let backdrop = options.background.over(Color::WHITE);
+    if !options.background.is_transparent() {
         writeln!(
             &mut eps,
             "newpath 0 0 moveto {} 0 lineto {} {} lineto 0 {} lineto closepath",
             width_px, width_px, height_px, height_px
         )
         .unwrap();
+        writeln!(&mut eps, "{} fill", set_color(backdrop, backdrop)).unwrap();
+    }
+
+    let geometry = SymbolGeometry::new(grid, options, pixel_size);
+
+    // Fill Foreground, then the finders if they are painted apart from it
+    append_fill(
+        &mut eps,
+        &geometry.foreground,
+        &Fill::foreground(grid, options, pixel_size),
+        backdrop,
+    );
+    if !geometry.finders.is_empty() {
+        append_fill(
+            &mut eps,
+            &geometry.finders,
+            &Fill::finders(grid, options, pixel_size),
+            backdrop,
+        );
+    }
+
+    for (color, path) in &geometry.colored {
+        writeln!(&mut eps, "newpath").unwrap();
+        append_path(&mut eps, path);
         writeln!(
             &mut eps,
-            "{} {} {} setrgbcolor fill",
-            bg_color.0, bg_color.1, bg_color.2
+            "{} {}",
+            set_color(*color, backdrop),
+            fill_operator(path)
         )
         .unwrap();
     }
 
-    let mut path_data = String::new();
+    if let Some(image) = &options.image {
+        append_icon(&mut eps, image, options, width, height, pixel_size)?;
+    } else if let Some(icon_path) = &options.icon {
+        let image = QrImage::load_from_path(icon_path)?;
+        append_icon(&mut eps, &image, options, width, height, pixel_size)?;
+    }
 
-    for y in 0..size {
-        for x in 0..size {
-            if grid.is_dark(x, y) {
-                if grid.is_finder(x, y) {
-                    continue; // Finders are drawn separately
-                }
+    writeln!(&mut eps, "%%EOF").unwrap();
 
-                let px = (x as f32 + quiet_zone) * pixel_size;
-                let py = (y as f32 + quiet_zone) * pixel_size;
-                let ctx = grid.module_context(x, y);
+    Ok(eps)
+}
 
-                append_module_path(&mut path_data, options.shape, px, py, pixel_size, &ctx);
-            }
-        }
+/// Fills a path with a color or gradient, blended with the backdrop.
+fn append_fill(eps: &mut String, path: &Path, fill: &Fill, backdrop: Color) {
+    if path.is_empty() {
+        return;
     }
+    writeln!(eps, "gsave").unwrap();
+    writeln!(eps, "newpath").unwrap();
+    append_path(eps, path);
 
-    // Finders
-    // Top-Left
-    append_finder_path(
-        &mut path_data,
-        options.finder,
-        quiet_zone * pixel_size,
-        quiet_zone * pixel_size,
-        pixel_size,
-    );
-    // Top-Right
-    append_finder_path(
-        &mut path_data,
-        options.finder,
-        (size as f32 - 7.0 + quiet_zone) * pixel_size,
-        quiet_zone * pixel_size,
-        pixel_size,
-    );
-    // Bottom-Left
-    append_finder_path(
-        &mut path_data,
-        options.finder,
-        quiet_zone * pixel_size,
-        (size as f32 - 7.0 + quiet_zone) * pixel_size,
-        pixel_size,
-    );
-
-    // Fill Foreground
-    if !path_data.is_empty() {
-        writeln!(&mut eps, "gsave").unwrap();
-        writeln!(&mut eps, "newpath").unwrap();
-        eps.push_str(&path_data);
-
-        // Color handling
-        if options.foreground.len() > 1 {
-            // Gradient
-            // We need to define a clipping path from the QR shape and then draw the gradient
-            writeln!(&mut eps, "clip").unwrap();
-
-            let colors: Vec<(f32, f32, f32)> = options
-                .foreground
-                .iter()
-                .filter_map(|c| parse_color(c))
-                .collect();
-
-            if !colors.is_empty() {
-                let (x0, y0, r0, x1, y1, r1) =
-                    utils::get_gradient_coords(options.gradient_direction, width_px, height_px);
-                let shading_type = if options.gradient_direction == GradientDirection::Radial {
-                    3
-                } else {
-                    2
-                };
-
-                writeln!(&mut eps, "<< /ShadingType {}", shading_type).unwrap();
-                writeln!(&mut eps, "   /ColorSpace /DeviceRGB").unwrap();
-                if shading_type == 2 {
-                    writeln!(
-                        &mut eps,
-                        "   /Coords [ {:.3} {:.3} {:.3} {:.3} ]",
-                        x0, y0, x1, y1
-                    )
-                    .unwrap();
-                } else {
+    match fill {
+        Fill::Gradient(gradient) => {
+            let mut blended = gradient.clone();
+            for stop in &mut blended.stops {
+                stop.color = stop.color.over(backdrop);
+            }
+            let shading = utils::generate_pdf_ps_shading(&blended, false);
+            match gradient.tile {
+                None => {
+                    // Clip to the modules and paint the shading over them
+                    writeln!(eps, "{}", clip_operator(path)).unwrap();
+                    writeln!(eps, "{} shfill", shading).unwrap();
+                }
+                Some(size) => {
+                    // Fill the modules with a pattern repeating the shading in every module
                     writeln!(
-                        &mut eps,
-                        "   /Coords [ {:.3} {:.3} {:.3} {:.3} {:.3} {:.3} ]",
-                        x0, y0, r0, x1, y1, r1
+                        eps,
+                        "<< /PatternType 1 /PaintType 1 /TilingType 1 /BBox [0 0 {s} {s}] \
+                         /XStep {s} /YStep {s} /PaintProc {{ pop {} shfill }} >>",
+                        shading,
+                        s = size
                     )
                     .unwrap();
+                    writeln!(eps, "matrix makepattern setpattern {}", fill_operator(path)).unwrap();
                 }
-                writeln!(
-                    &mut eps,
-                    "   /Function {}",
-                    utils::generate_pdf_ps_gradient_function(&colors)
-                )
-                .unwrap();
-                writeln!(&mut eps, "   /Extend [ true true ]").unwrap();
-                writeln!(&mut eps, ">> shfill").unwrap();
             }
-        } else if let Some(fg_color) = options.foreground.first().and_then(|c| parse_color(c)) {
+        }
+        Fill::Solid(color) => {
             writeln!(
-                &mut eps,
-                "{} {} {} setrgbcolor fill",
-                fg_color.0, fg_color.1, fg_color.2
+                eps,
+                "{} {}",
+                set_color(*color, backdrop),
+                fill_operator(path)
             )
             .unwrap();
-        } else {
-            // Default black
-            writeln!(&mut eps, "0 0 0 setrgbcolor fill").unwrap();
         }
-        writeln!(&mut eps, "grestore").unwrap();
     }
+    writeln!(eps, "grestore").unwrap();
+}
 
-    if let Some(image) = &options.image {
-        match image {
-            crate::models::QrImage::Raster(img) => {
-                append_icon(&mut eps, img, size, pixel_size, width_px, height_px);
-            }
-            crate::models::QrImage::Svg(_) => {
-                return Err(format!("EPS renderer does not support SVG icons."));
-            }
+/// An icon ready to be drawn: raster images are embedded, SVG images are converted to paths.
+enum EpsIcon<'a> {
+    Raster(&'a image::DynamicImage),
+    Vector(VectorIcon),
+}
+
+/// Appends the icon where the layout puts it, clipped to its shape,
+/// over its plate and under its border if enabled.
+/// SVG icons are converted to PostScript paths.
+fn append_icon(
+    eps: &mut String,
+    image: &QrImage,
+    options: &QrConfig,
+    grid_width: usize,
+    grid_height: usize,
+    pixel_size: f32,
+) -> Result<(), String> {
+    let quiet_zone = options.quiet_zone as f32;
+    let aspect = image
+        .aspect_ratio()
+        .ok_or("Icon image dimensions could not be determined.")?;
+    let layout = IconLayout::new(grid_width, grid_height, aspect, options);
+    let icon = match image {
+        QrImage::Raster(img) => EpsIcon::Raster(img),
+        QrImage::Svg(content) => EpsIcon::Vector(VectorIcon::parse(content)?),
+    };
+
+    let backdrop = options.background.over(Color::WHITE);
+    if options.icon_plate && !options.background.is_transparent() {
+        append_area_path(eps, &layout.frame, quiet_zone, pixel_size);
+        writeln!(eps, "{} fill", set_color(backdrop, backdrop)).unwrap();
+    }
+
+    writeln!(eps, "gsave").unwrap();
+    if layout.clip != layout.image {
+        append_area_path(eps, &layout.clip, quiet_zone, pixel_size);
+        writeln!(eps, "clip newpath").unwrap();
+    }
+    writeln!(
+        eps,
+        "{} {} translate",
+        (layout.image.x + quiet_zone) * pixel_size,
+        (layout.image.y + quiet_zone) * pixel_size
+    )
+    .unwrap();
+
+    match &icon {
+        EpsIcon::Vector(vector) => {
+            // SVG coordinates already point down, like the flipped page
+            writeln!(
+                eps,
+                "{} {} scale",
+                layout.image.width * pixel_size / vector.width,
+                layout.image.height * pixel_size / vector.height
+            )
+            .unwrap();
+            append_vector_icon(eps, vector);
+        }
+        EpsIcon::Raster(img) => {
+            writeln!(
+                eps,
+                "{} {} scale",
+                layout.image.width * pixel_size,
+                layout.image.height * pixel_size
+            )
+            .unwrap();
+            append_raster_icon(eps, img);
         }
-    } else if let Some(icon_path) = &options.icon {
-        QrImage::load_from_path(icon_path).and_then(|image| match image {
-            crate::models::QrImage::Raster(img) => {
-                append_icon(&mut eps, &img, size, pixel_size, width_px, height_px);
-                Ok(())
-            }
-            crate::models::QrImage::Svg(_) => {
-                Err(format!("EPS renderer does not support SVG icons."))
-            }
-        })?;
     }
 
-    writeln!(&mut eps, "%%EOF").unwrap();
+    writeln!(eps, "grestore").unwrap();
 
-    Ok(eps)
+    if let Some(border) = options.icon_border {
+        append_area_path(eps, &layout.border(), quiet_zone, pixel_size);
+        writeln!(eps, "{} setlinewidth", ICON_BORDER_WIDTH * pixel_size).unwrap();
+        writeln!(eps, "{} stroke", set_color(border, backdrop)).unwrap();
+    }
+    Ok(())
 }
 
-fn append_icon(
-    eps: &mut String,
-    img: &image::DynamicImage,
-    size: usize,
-    pixel_size: f32,
-    width_px: f32,
-    height_px: f32,
-) {
+/// Appends a raster icon as a unit square image.
+fn append_raster_icon(eps: &mut String, img: &image::DynamicImage) {
     let img = img.to_rgba8();
     let (w, h) = img.dimensions();
-    let icon_size = (size as f32 * pixel_size) * 0.25;
-    let x = (width_px - icon_size) / 2.0;
-    let y = (height_px - icon_size) / 2.0;
-
-    writeln!(eps, "gsave").unwrap();
-    writeln!(eps, "{} {} translate", x, y).unwrap();
-    writeln!(eps, "{} {} scale", icon_size, icon_size).unwrap();
 
     writeln!(eps, "/picstr {} string def", w * 3).unwrap(); // RGB
     writeln!(eps, "{} {} 8", w, h).unwrap();
@@ -213,14 +234,63 @@ fn append_icon(
         // RGB only, ignore alpha for EPS simple implementation
         write!(eps, "{:02x}{:02x}{:02x}", pixel[0], pixel[1], pixel[2]).unwrap();
     }
-    writeln!(eps, "").unwrap();
+    writeln!(eps).unwrap();
+}
 
-    writeln!(eps, "grestore").unwrap();
+/// Appends the paths of a vector icon, in the icon's own coordinates.
+fn append_vector_icon(eps: &mut String, icon: &VectorIcon) {
+    for path in &icon.paths {
+        let [a, b, c, d, e, f] = path.transform;
+        writeln!(eps, "gsave").unwrap();
+        writeln!(eps, "[{} {} {} {} {} {}] concat", a, b, c, d, e, f).unwrap();
+        writeln!(eps, "newpath").unwrap();
+        append_path(eps, &path.path);
+        if let Some((r, g, b)) = path.fill {
+            let fill = fill_operator(&path.path);
+            let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+            if path.stroke.is_some() {
+                // Filling consumes the path, which the stroke still needs
+                writeln!(eps, "gsave {} {} {} setrgbcolor {} grestore", r, g, b, fill).unwrap();
+            } else {
+                writeln!(eps, "{} {} {} setrgbcolor {}", r, g, b, fill).unwrap();
+            }
+        }
+        if let Some(stroke) = &path.stroke {
+            let (r, g, b) = stroke.color;
+            writeln!(
+                eps,
+                "{} {} {} setrgbcolor",
+                r as f32 / 255.0,
+                g as f32 / 255.0,
+                b as f32 / 255.0
+            )
+            .unwrap();
+            writeln!(
+                eps,
+                "{} setlinewidth {} setlinecap {} setlinejoin {} setmiterlimit",
+                stroke.width, stroke.cap, stroke.join, stroke.miter_limit
+            )
+            .unwrap();
+            if let Some((dashes, offset)) = &stroke.dash {
+                let dashes: Vec<String> = dashes.iter().map(|d| d.to_string()).collect();
+                writeln!(eps, "[{}] {} setdash", dashes.join(" "), offset).unwrap();
+            }
+            writeln!(eps, "stroke").unwrap();
+        }
+        writeln!(eps, "grestore").unwrap();
+    }
+}
+
+/// Appends a new closed path around an icon area.
+fn append_area_path(eps: &mut String, area: &IconArea, quiet_zone: f32, pixel_size: f32) {
+    writeln!(eps, "newpath").unwrap();
+    append_path(eps, &area.path(quiet_zone, pixel_size));
 }
 
-fn parse_color(hex: &str) -> Option<(f32, f32, f32)> {
-    utils::parse_hex_color(hex)
-        .map(|(r, g, b)| (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0))
+/// Sets the color, blended with the backdrop if it can be seen through.
+fn set_color(color: Color, backdrop: Color) -> String {
+    let (r, g, b) = color.over(backdrop).rgb_f32();
+    format!("{} {} {} setrgbcolor", r, g, b)
 }
 
 pub struct EpsRenderer {
@@ -0,0 +1,34 @@
+use crate::core::renderer::geometry::{FillRule, Path, Segment};
+use std::fmt::Write;
+
+/// Appends the PostScript path construction operators of a path.
+pub fn append_path(eps: &mut String, path: &Path) {
+    for segment in path.cubic_segments() {
+        match segment {
+            Segment::MoveTo(x, y) => writeln!(eps, "{} {} moveto", x, y),
+            Segment::LineTo(x, y) => writeln!(eps, "{} {} lineto", x, y),
+            Segment::CubicTo(x1, y1, x2, y2, x, y) => {
+                writeln!(eps, "{} {} {} {} {} {} curveto", x1, y1, x2, y2, x, y)
+            }
+            Segment::Close => writeln!(eps, "closepath"),
+            Segment::QuadTo(..) => unreachable!("quadratic curves are raised to cubic ones"),
+        }
+        .unwrap();
+    }
+}
+
+/// Returns the operator filling a path with its fill rule.
+pub fn fill_operator(path: &Path) -> &'static str {
+    match path.fill_rule {
+        FillRule::NonZero => "fill",
+        FillRule::EvenOdd => "eofill",
+    }
+}
+
+/// Returns the operator clipping to a path with its fill rule.
+pub fn clip_operator(path: &Path) -> &'static str {
+    match path.fill_rule {
+        FillRule::NonZero => "clip",
+        FillRule::EvenOdd => "eoclip",
+    }
+}
@@ -0,0 +1,299 @@
+use crate::core::renderer::geometry::Path;
+use crate::core::renderer::shape::{BuiltinFinder, FinderShapeProvider};
+use crate::core::renderer::{FinderCorner, QrGrid};
+use crate::models::{AlignmentShape, Color, EyeShape, FinderShape, QrConfig};
+
+/// How a corner of a square outline is drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Corner {
+    Sharp,
+    /// A quarter circle with the given radius.
+    Round(f32),
+    /// A cut at 45 degrees, starting the given length from the corner on each side.
+    Cut(f32),
+}
+
+/// A closed outline of a part of a finder pattern, in pixels.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outline {
+    /// A square, with its corners clockwise from the top left.
+    Square {
+        x: f32,
+        y: f32,
+        size: f32,
+        corners: [Corner; 4],
+    },
+    Circle {
+        cx: f32,
+        cy: f32,
+        r: f32,
+    },
+    /// A polygon, with its points clockwise, or counterclockwise around a hole.
+    Polygon(Vec<(f32, f32)>),
+}
+
+/// A corner of a square outline, as a path going around the square passes it.
+#[derive(Debug, Clone, Copy)]
+pub struct CornerTurn {
+    /// Where the path leaves the incoming side.
+    pub from: (f32, f32),
+    /// The corner of the square.
+    pub at: (f32, f32),
+    /// Where the path joins the outgoing side.
+    pub to: (f32, f32),
+    pub corner: Corner,
+}
+
+impl Outline {
+    /// Returns the point a path around a square outline starts from, the middle of its left
+    /// side, and the corners it passes, clockwise or counterclockwise.
+    /// Returns `None` for other outlines.
+    pub fn square_turns(&self, clockwise: bool) -> Option<((f32, f32), Vec<CornerTurn>)> {
+        let Outline::Square {
+            x,
+            y,
+            size,
+            corners,
+        } = *self
+        else {
+            return None;
+        };
+        let points = [(x, y), (x + size, y), (x + size, y + size), (x, y + size)];
+        let mut order = [0, 1, 2, 3];
+        if !clockwise {
+            order = [3, 2, 1, 0];
+        }
+
+        let turns = (0..4)
+            .map(|i| {
+                let at = points[order[i]];
+                let prev = points[order[(i + 3) % 4]];
+                let next = points[order[(i + 1) % 4]];
+                let length = match corners[order[i]] {
+                    Corner::Sharp => 0.0,
+                    Corner::Round(r) => r.min(size / 2.0),
+                    Corner::Cut(c) => c.min(size / 2.0),
+                };
+                // Neighbouring corners are a side away along one axis
+                let toward = |p: (f32, f32)| {
+                    (
+                        at.0 + (p.0 - at.0) / size * length,
+                        at.1 + (p.1 - at.1) / size * length,
+                    )
+                };
+                let corner = match corners[order[i]] {
+                    Corner::Round(_) => Corner::Round(length),
+                    Corner::Cut(_) => Corner::Cut(length),
+                    Corner::Sharp => Corner::Sharp,
+                };
+                CornerTurn {
+                    from: toward(prev),
+                    at,
+                    to: toward(next),
+                    corner,
+                }
+            })
+            .collect();
+        Some(((x, y + size / 2.0), turns))
+    }
+}
+
+/// The outlines of a finder pattern: the outer ring, as an outline and its hole, and the eye.
+/// Alignment patterns are laid out the same way, without an eye for the 3x3 ones.
+#[derive(Debug, Clone)]
+pub struct FinderOutlines {
+    pub outer: Outline,
+    pub hole: Outline,
+    pub eye: Option<Outline>,
+}
+
+impl FinderOutlines {
+    /// Lays out a finder pattern `modules` wide (7 for finders, 5 for sub-finders)
+    /// with its top left corner at (x, y) in pixels. Patterns narrower than 5 modules have no eye.
+    pub fn new(
+        finder: FinderShape,
+        eye: EyeShape,
+        x: f32,
+        y: f32,
+        modules: f32,
+        scale: f32,
+    ) -> Self {
+        let ring = |inset: f32, corners: fn(f32, f32) -> [Corner; 4]| {
+            let size = (modules - inset * 2.0) * scale;
+            square(
+                x + inset * scale,
+                y + inset * scale,
+                size,
+                corners(size, scale),
+            )
+        };
+
+        let (outer, hole) = match finder {
+            FinderShape::Square => (
+                ring(0.0, |_, _| [Corner::Sharp; 4]),
+                ring(1.0, |_, _| [Corner::Sharp; 4]),
+            ),
+            FinderShape::Circle => (
+                circle(x, y, modules * scale),
+                circle(x + scale, y + scale, (modules - 2.0) * scale),
+            ),
+            FinderShape::Rounded => (
+                ring(0.0, |_, scale| [Corner::Round(scale); 4]),
+                ring(1.0, |_, scale| [Corner::Round(scale * 0.7); 4]),
+            ),
+            FinderShape::Leaf => (ring(0.0, leaf), ring(1.0, leaf)),
+            FinderShape::CutCorner => (
+                ring(0.0, |size, _| [Corner::Cut(size * 2.0 / 7.0); 4]),
+                // Keeps the ring as wide along the cuts as along the sides
+                ring(1.0, |size, scale| {
+                    [Corner::Cut((size + scale * 2.0) * 2.0 / 7.0 - scale * (2.0 - 2f32.sqrt())); 4]
+                }),
+            ),
+        };
+
+        let (ex, ey, size) = (x + 2.0 * scale, y + 2.0 * scale, (modules - 4.0) * scale);
+        let (cx, cy) = (ex + size / 2.0, ey + size / 2.0);
+        let eye = (size > 0.0).then(|| match eye {
+            EyeShape::Square => square(ex, ey, size, [Corner::Sharp; 4]),
+            EyeShape::Rounded => square(ex, ey, size, [Corner::Round(scale * 0.5); 4]),
+            EyeShape::Circle => circle(ex, ey, size),
+            EyeShape::Leaf => square(ex, ey, size, leaf(size, scale)),
+            EyeShape::Diamond => {
+                // The points reach into the light ring like the star's, a diamond inscribed
+                // in the eye leaves its diagonals too short for scanners to find the pattern
+                let r = size * 0.65;
+                Outline::Polygon(vec![(cx, cy - r), (cx + r, cy), (cx, cy + r), (cx - r, cy)])
+            }
+            EyeShape::Star => {
+                // The points reach into the light ring, for the body to cover most of the eye
+                let r = size * 0.65;
+                // Moves the star down to center it, its lower points being closer to the middle
+                // than its top point
+                let cy = cy + r * (1.0 - 36f32.to_radians().cos()) / 2.0;
+                Outline::Polygon(
+                    (0..10)
+                        .map(|i| {
+                            let angle = (-90.0 + 36.0 * i as f32).to_radians();
+                            let r = if i % 2 == 0 { r } else { r * 0.6 };
+                            (cx + r * angle.cos(), cy + r * angle.sin())
+                        })
+                        .collect(),
+                )
+            }
+        });
+
+        Self { outer, hole, eye }
+    }
+}
+
+/// A finder pattern laid out in its style, with the colors of its parts,
+/// or `None` for parts painted with the finder foreground, or like the modules without one.
+#[derive(Debug, Clone)]
+pub struct StyledFinder {
+    pub ring: Path,
+    pub eye: Path,
+    pub color: Option<Color>,
+    pub eye_color: Option<Color>,
+}
+
+/// Lays out the finder patterns of the grid in the configured style, with the style of
+/// their corner overriding it.
+pub fn styled_finders<G: QrGrid + ?Sized>(
+    grid: &G,
+    config: &QrConfig,
+    scale: f32,
+) -> Vec<StyledFinder> {
+    let quiet_zone = config.quiet_zone as f32;
+    grid.finder_patterns()
+        .iter()
+        .map(|finder| {
+            let style = match finder.corner {
+                FinderCorner::TopLeft => &config.finder_top_left,
+                FinderCorner::TopRight => &config.finder_top_right,
+                FinderCorner::BottomLeft => &config.finder_bottom_left,
+                FinderCorner::BottomRight => &config.finder_bottom_right,
+            }
+            .as_ref();
+            let shape = style.and_then(|s| s.shape).unwrap_or(config.finder);
+            let eye = style
+                .and_then(|s| s.eye)
+                .or(config.finder_eye)
+                .unwrap_or(shape.into());
+            let builtin = BuiltinFinder { shape, eye };
+            let provider = match &config.finder_provider {
+                Some(provider) if style.is_none_or(|s| s.shape.is_none() && s.eye.is_none()) => {
+                    provider.as_ref()
+                }
+                _ => &builtin as &dyn FinderShapeProvider,
+            };
+
+            let x = (finder.x as f32 + quiet_zone) * scale;
+            let y = (finder.y as f32 + quiet_zone) * scale;
+            let size = finder.size as f32 * scale;
+            let (mut ring, mut eye) = (
+                provider.ring_path(x, y, size, scale),
+                provider.eye_path(x, y, size, scale),
+            );
+            let turns = style.map_or(0, |s| s.rotate / 90);
+            ring.rotate(turns, x + size / 2.0, y + size / 2.0);
+            eye.rotate(turns, x + size / 2.0, y + size / 2.0);
+            StyledFinder {
+                ring,
+                eye,
+                color: style.and_then(|s| s.color).or(config.finder_color),
+                eye_color: style.and_then(|s| s.eye_color).or(config.finder_eye_color),
+            }
+        })
+        .collect()
+}
+
+/// Lays out the alignment patterns of the grid as whole shapes,
+/// or returns none if they are drawn module by module.
+pub fn alignment_outlines<G: QrGrid + ?Sized>(
+    grid: &G,
+    config: &QrConfig,
+    scale: f32,
+) -> Vec<FinderOutlines> {
+    let (shape, eye) = match config.alignment {
+        AlignmentShape::Modules => return Vec::new(),
+        AlignmentShape::Square => (FinderShape::Square, EyeShape::Square),
+        AlignmentShape::Rounded => (FinderShape::Rounded, EyeShape::Rounded),
+        AlignmentShape::Circle => (FinderShape::Circle, EyeShape::Circle),
+    };
+    let quiet_zone = config.quiet_zone as f32;
+    grid.alignment_patterns()
+        .iter()
+        .map(|pattern| {
+            FinderOutlines::new(
+                shape,
+                eye,
+                (pattern.x as f32 + quiet_zone) * scale,
+                (pattern.y as f32 + quiet_zone) * scale,
+                pattern.size as f32,
+                scale,
+            )
+        })
+        .collect()
+}
+
+fn square(x: f32, y: f32, size: f32, corners: [Corner; 4]) -> Outline {
+    Outline::Square {
+        x,
+        y,
+        size,
+        corners,
+    }
+}
+
+fn circle(x: f32, y: f32, size: f32) -> Outline {
+    Outline::Circle {
+        cx: x + size / 2.0,
+        cy: y + size / 2.0,
+        r: size / 2.0,
+    }
+}
+
+fn leaf(size: f32, _: f32) -> [Corner; 4] {
+    let r = Corner::Round(size / 2.0);
+    [r, Corner::Sharp, r, Corner::Sharp]
+}
@@ -0,0 +1,321 @@
+use crate::core::renderer::contour::square_contours;
+use crate::core::renderer::finder::{
+    Corner, FinderOutlines, Outline, alignment_outlines, styled_finders,
+};
+use crate::core::renderer::shape::ModuleShapeProvider;
+use crate::core::renderer::{QrGrid, custom_module_shape, module_shape};
+use crate::models::{Color, ModuleShape, QrConfig};
+
+/// Distance of the control points of a cubic Bézier curve approximating a quarter circle,
+/// as a share of the radius.
+pub const KAPPA: f32 = 0.552_284_8;
+
+/// A segment of a path.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Segment {
+    MoveTo(f32, f32),
+    LineTo(f32, f32),
+    QuadTo(f32, f32, f32, f32),
+    CubicTo(f32, f32, f32, f32, f32, f32),
+    Close,
+}
+
+/// How the inside of a path is told apart from the outside where its subpaths overlap.
+/// Only icons converted from SVG use the even-odd rule, which is kept by drawing them as
+/// paths of their own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum FillRule {
+    #[default]
+    NonZero,
+    EvenOdd,
+}
+
+/// A backend-neutral path. Shapes are built once into paths, which each backend
+/// only has to write out in its own format. Paths are filled with the non-zero rule,
+/// so holes have to run the opposite way to the outline around them.
+#[derive(Debug, Clone, Default)]
+pub struct Path {
+    pub segments: Vec<Segment>,
+    pub(crate) fill_rule: FillRule,
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    pub fn move_to(&mut self, x: f32, y: f32) {
+        self.segments.push(Segment::MoveTo(x, y));
+    }
+
+    pub fn line_to(&mut self, x: f32, y: f32) {
+        self.segments.push(Segment::LineTo(x, y));
+    }
+
+    pub fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        self.segments.push(Segment::QuadTo(x1, y1, x, y));
+    }
+
+    pub fn cubic_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        self.segments.push(Segment::CubicTo(x1, y1, x2, y2, x, y));
+    }
+
+    pub fn close(&mut self) {
+        self.segments.push(Segment::Close);
+    }
+
+    /// Adds the subpaths of another path, which are then filled with this path's rule.
+    pub fn append(&mut self, other: Path) {
+        self.segments.extend(other.segments);
+    }
+
+    /// Turns the path clockwise around the given point by the given number of quarter turns.
+    pub fn rotate(&mut self, quarter_turns: u16, cx: f32, cy: f32) {
+        let turn = |x: &mut f32, y: &mut f32| {
+            for _ in 0..quarter_turns % 4 {
+                (*x, *y) = (cx - (*y - cy), cy + (*x - cx));
+            }
+        };
+        for segment in &mut self.segments {
+            match segment {
+                Segment::MoveTo(x, y) | Segment::LineTo(x, y) => turn(x, y),
+                Segment::QuadTo(x1, y1, x, y) => {
+                    turn(x1, y1);
+                    turn(x, y);
+                }
+                Segment::CubicTo(x1, y1, x2, y2, x, y) => {
+                    turn(x1, y1);
+                    turn(x2, y2);
+                    turn(x, y);
+                }
+                Segment::Close => {}
+            }
+        }
+    }
+
+    /// Returns the segments with quadratic curves raised to cubic ones,
+    /// for formats that only have the latter.
+    pub fn cubic_segments(&self) -> Vec<Segment> {
+        let (mut current, mut start) = ((0.0, 0.0), (0.0, 0.0));
+        self.segments
+            .iter()
+            .map(|&segment| match segment {
+                Segment::MoveTo(x, y) => {
+                    (current, start) = ((x, y), (x, y));
+                    segment
+                }
+                Segment::LineTo(x, y) | Segment::CubicTo(.., x, y) => {
+                    current = (x, y);
+                    segment
+                }
+                Segment::QuadTo(x1, y1, x, y) => {
+                    let (x0, y0) = current;
+                    current = (x, y);
+                    Segment::CubicTo(
+                        x0 + (x1 - x0) * 2.0 / 3.0,
+                        y0 + (y1 - y0) * 2.0 / 3.0,
+                        x + (x1 - x) * 2.0 / 3.0,
+                        y + (y1 - y) * 2.0 / 3.0,
+                        x,
+                        y,
+                    )
+                }
+                Segment::Close => {
+                    current = start;
+                    segment
+                }
+            })
+            .collect()
+    }
+
+    pub fn push_rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.move_to(x, y);
+        self.line_to(x + width, y);
+        self.line_to(x + width, y + height);
+        self.line_to(x, y + height);
+        self.close();
+    }
+
+    /// Adds a rectangle running clockwise, with the radii of its corners clockwise
+    /// from the top left.
+    pub fn push_rounded_rect(&mut self, x: f32, y: f32, width: f32, height: f32, radii: [f32; 4]) {
+        let (x1, y1) = (x + width, y + height);
+        let [top_left, top_right, bottom_right, bottom_left] = radii;
+        self.move_to(x + top_left, y);
+        self.corner_to((x1 - top_right, y), (x1, y), (x1, y + top_right));
+        self.corner_to((x1, y1 - bottom_right), (x1, y1), (x1 - bottom_right, y1));
+        self.corner_to((x + bottom_left, y1), (x, y1), (x, y1 - bottom_left));
+        self.corner_to((x, y + top_left), (x, y), (x + top_left, y));
+        self.close();
+    }
+
+    /// Adds a circle running clockwise or counterclockwise, drawn with cubic curves.
+    pub fn push_circle(&mut self, cx: f32, cy: f32, r: f32, clockwise: bool) {
+        let mut points = [(cx + r, cy), (cx, cy + r), (cx - r, cy), (cx, cy - r)];
+        if !clockwise {
+            points.reverse();
+        }
+        self.move_to(points[0].0, points[0].1);
+        for i in 0..4 {
+            let (from, to) = (points[i], points[(i + 1) % 4]);
+            // The corner of the bounding square the quarter circle turns around
+            let at = if from.0 == cx {
+                (to.0, from.1)
+            } else {
+                (from.0, to.1)
+            };
+            self.arc_to(from, at, to);
+        }
+        self.close();
+    }
+
+    /// Adds a closed outline, running clockwise or counterclockwise.
+    pub fn push_outline(&mut self, outline: &Outline, clockwise: bool) {
+        match outline {
+            Outline::Circle { cx, cy, r } => self.push_circle(*cx, *cy, *r, clockwise),
+            Outline::Polygon(points) => {
+                let mut points: Vec<_> = points.iter().collect();
+                if !clockwise {
+                    points.reverse();
+                }
+                for (i, &&(x, y)) in points.iter().enumerate() {
+                    if i == 0 {
+                        self.move_to(x, y);
+                    } else {
+                        self.line_to(x, y);
+                    }
+                }
+                self.close();
+            }
+            Outline::Square { .. } => {
+                let Some(((x, y), turns)) = outline.square_turns(clockwise) else {
+                    return;
+                };
+                self.move_to(x, y);
+                for turn in turns {
+                    match turn.corner {
+                        Corner::Sharp => self.line_to(turn.at.0, turn.at.1),
+                        Corner::Round(_) => self.corner_to(turn.from, turn.at, turn.to),
+                        Corner::Cut(_) => {
+                            self.line_to(turn.from.0, turn.from.1);
+                            self.line_to(turn.to.0, turn.to.1);
+                        }
+                    }
+                }
+                self.close();
+            }
+        }
+    }
+
+    /// Adds the outer ring of a finder pattern, its hole running the other way
+    /// so that the nonzero fill rule leaves it empty.
+    pub fn push_finder_ring(&mut self, outlines: &FinderOutlines) {
+        self.push_outline(&outlines.outer, true);
+        self.push_outline(&outlines.hole, false);
+    }
+
+    /// Adds the eye of a finder pattern, if it has one.
+    pub fn push_finder_eye(&mut self, outlines: &FinderOutlines) {
+        if let Some(eye) = &outlines.eye {
+            self.push_outline(eye, true);
+        }
+    }
+
+    /// Runs on to `from`, then turns around the corner `at` to `to` along a quarter circle,
+    /// or straight through the corner if the points meet there.
+    pub fn corner_to(&mut self, from: (f32, f32), at: (f32, f32), to: (f32, f32)) {
+        if from == at || to == at {
+            self.line_to(at.0, at.1);
+        } else {
+            self.line_to(from.0, from.1);
+            self.arc_to(from, at, to);
+        }
+    }
+
+    /// Adds a quarter circle from `from`, the current point, around the corner `at` to `to`.
+    fn arc_to(&mut self, from: (f32, f32), at: (f32, f32), to: (f32, f32)) {
+        let toward = |p: (f32, f32)| (p.0 + (at.0 - p.0) * KAPPA, p.1 + (at.1 - p.1) * KAPPA);
+        let (c1, c2) = (toward(from), toward(to));
+        self.cubic_to(c1.0, c1.1, c2.0, c2.1, to.0, to.1);
+    }
+}
+
+/// The shapes of a symbol, in pixels, laid out once for all backends.
+#[derive(Debug, Clone)]
+pub struct SymbolGeometry {
+    /// Everything painted with the foreground: the modules and the patterns
+    /// that have no color of their own.
+    pub foreground: Path,
+    /// Parts of finder patterns painted with the finder foreground, if it is set.
+    /// Without one they are painted with the foreground.
+    pub finders: Path,
+    /// Parts of finder patterns with colors of their own, by color.
+    pub colored: Vec<(Color, Path)>,
+}
+
+impl SymbolGeometry {
+    pub fn new<G: QrGrid + ?Sized>(grid: &G, config: &QrConfig, scale: f32) -> Self {
+        let quiet_zone = config.quiet_zone as f32;
+        let mut foreground = Path::new();
+
+        // Square modules are drawn merged, below
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                if !grid.is_dark(x, y) {
+                    continue;
+                }
+                let px = (x as f32 + quiet_zone) * scale;
+                let py = (y as f32 + quiet_zone) * scale;
+                let ctx = grid.module_context(x, y);
+                if let Some(provider) = custom_module_shape(grid, config, x, y) {
+                    foreground.append(provider.module_path(px, py, scale, &ctx));
+                } else if let Some(shape) = module_shape(grid, config, x, y)
+                    && shape != ModuleShape::Square
+                {
+                    foreground.append(shape.module_path(px, py, scale, &ctx));
+                }
+            }
+        }
+
+        // Square modules, as the outlines of the regions they form
+        for contour in square_contours(grid, config, scale) {
+            foreground.push_outline(&contour, true);
+        }
+
+        // Alignment patterns, if drawn as whole shapes
+        for outlines in alignment_outlines(grid, config, scale) {
+            foreground.push_finder_ring(&outlines);
+            foreground.push_finder_eye(&outlines);
+        }
+
+        // Finders, with the modules unless they have a foreground or colors of their own
+        let mut finders = Path::new();
+        let mut colored: Vec<(Color, Path)> = Vec::new();
+        for finder in styled_finders(grid, config, scale) {
+            for (color, part) in [(finder.color, finder.ring), (finder.eye_color, finder.eye)] {
+                let path = match color {
+                    None if !config.finder_foreground.is_empty() => &mut finders,
+                    None => &mut foreground,
+                    Some(color) => match colored.iter().position(|(c, _)| *c == color) {
+                        Some(i) => &mut colored[i].1,
+                        None => {
+                            colored.push((color, Path::new()));
+                            &mut colored.last_mut().unwrap().1
+                        }
+                    },
+                };
+                path.append(part);
+            }
+        }
+
+        Self {
+            foreground,
+            finders,
+            colored,
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use crate::core::renderer::QrGrid;
+use crate::models::{Color, ColorStop, GradientDirection, GradientSpan, QrConfig};
+
+/// A color of a gradient, at a share of the way along it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GradientStop {
+    pub offset: f32,
+    pub color: Color,
+}
+
+/// How the colors of a gradient are laid out, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GradientShape {
+    /// Colors change along the line from `start` to `end`.
+    Linear { start: (f32, f32), end: (f32, f32) },
+    /// Colors change going out from `center`, reaching the last one at `radius`.
+    Radial { center: (f32, f32), radius: f32 },
+}
+
+/// A gradient laid out over the area it spans. Every backend draws it from these
+/// coordinates, with the colors before the first stop and after the last one
+/// carried on to the edges.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    pub shape: GradientShape,
+    pub stops: Vec<GradientStop>,
+    /// Size of the square tiles the gradient repeats in, when it spans every module
+    /// on its own. Tiles start at the origin, so they line up with the modules.
+    pub tile: Option<f32>,
+}
+
+/// What a set of colors paints with: a single color, or a gradient between them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fill {
+    Solid(Color),
+    Gradient(Gradient),
+}
+
+impl Fill {
+    /// The fill of the modules.
+    pub fn foreground<G: QrGrid + ?Sized>(grid: &G, config: &QrConfig, scale: f32) -> Self {
+        Self::new(
+            &config.foreground,
+            config.gradient_direction,
+            grid,
+            config,
+            scale,
+        )
+    }
+
+    /// The fill of the finder patterns, when they have a foreground of their own.
+    pub fn finders<G: QrGrid + ?Sized>(grid: &G, config: &QrConfig, scale: f32) -> Self {
+        Self::new(
+            &config.finder_foreground,
+            config
+                .finder_gradient_direction
+                .unwrap_or(config.gradient_direction),
+            grid,
+            config,
+            scale,
+        )
+    }
+
+    /// Lays out the given colors in the given direction, over the area the configuration
+    /// spreads gradients on.
+    pub fn new<G: QrGrid + ?Sized>(
+        colors: &[ColorStop],
+        direction: GradientDirection,
+        grid: &G,
+        config: &QrConfig,
+        scale: f32,
+    ) -> Self {
+        let stops = color_stops(colors);
+        if stops.len() < 2 {
+            return Fill::Solid(stops.first().map_or(Color::BLACK, |s| s.color));
+        }
+
+        let quiet_zone = config.quiet_zone as f32;
+        let (width, height) = (grid.width() as f32, grid.height() as f32);
+        let (x, y, w, h) = match config.gradient_span {
+            GradientSpan::Canvas => (
+                0.0,
+                0.0,
+                (width + quiet_zone * 2.0) * scale,
+                (height + quiet_zone * 2.0) * scale,
+            ),
+            GradientSpan::Symbol => (
+                quiet_zone * scale,
+                quiet_zone * scale,
+                width * scale,
+                height * scale,
+            ),
+            GradientSpan::Module => (0.0, 0.0, scale, scale),
+        };
+
+        let shape = match direction.angle() {
+            // Like in CSS, the line is long enough for the corners of the area
+            // to get the first and last colors
+            Some(angle) => {
+                let (sin, cos) = angle.to_radians().sin_cos();
+                let half = (w * sin.abs() + h * cos.abs()) / 2.0;
+                let (cx, cy) = (x + w / 2.0, y + h / 2.0);
+                GradientShape::Linear {
+                    start: (cx - sin * half, cy + cos * half),
+                    end: (cx + sin * half, cy - cos * half),
+                }
+            }
+            None => GradientShape::Radial {
+                center: (
+                    x + config.gradient_center.x * w,
+                    y + config.gradient_center.y * h,
+                ),
+                radius: config.gradient_radius.max(0.0) * (w * w + h * h).sqrt() / 2.0,
+            },
+        };
+
+        Fill::Gradient(Gradient {
+            shape,
+            stops,
+            tile: (config.gradient_span == GradientSpan::Module).then_some(scale),
+        })
+    }
+
+    /// Whether anything painted with the fill can be seen through.
+    pub fn is_opaque(&self) -> bool {
+        match self {
+            Fill::Solid(color) => color.is_opaque(),
+            Fill::Gradient(gradient) => gradient.stops.iter().all(|s| s.color.is_opaque()),
+        }
+    }
+}
+
+/// Lays out the stops of a gradient between the given colors. Like in CSS, the first
+/// and last stops default to the ends, others without a position are spread evenly between
+/// their neighbours, and a stop placed before an earlier one is moved up to it.
+pub fn color_stops(colors: &[ColorStop]) -> Vec<GradientStop> {
+    let mut offsets: Vec<Option<f32>> = colors.iter().map(|stop| stop.offset).collect();
+    if let Some(first) = offsets.first_mut() {
+        first.get_or_insert(0.0);
+    }
+    if let Some(last) = offsets.last_mut() {
+        last.get_or_insert(1.0);
+    }
+
+    let mut previous = 0;
+    for i in 1..offsets.len() {
+        let Some(offset) = offsets[i] else {
+            continue;
+        };
+        // Spread the stops without a position between the ones around them
+        let from = offsets[previous].unwrap_or_default();
+        let steps = (i - previous) as f32;
+        for (k, missing) in offsets[previous + 1..i].iter_mut().enumerate() {
+            *missing = Some(from + (offset - from) * (k + 1) as f32 / steps);
+        }
+        previous = i;
+    }
+
+    let mut floor = f32::NEG_INFINITY;
+    colors
+        .iter()
+        .zip(offsets)
+        .map(|(stop, offset)| {
+            floor = floor.max(offset.unwrap_or_default().clamp(0.0, 1.0));
+            GradientStop {
+                offset: floor,
+                color: stop.color,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offsets(stops: &[&str]) -> Vec<f32> {
+        let colors: Vec<ColorStop> = stops.iter().map(|stop| stop.parse().unwrap()).collect();
+        color_stops(&colors)
+            .iter()
+            .map(|stop| (stop.offset * 1000.0).round() / 1000.0)
+            .collect()
+    }
+
+    #[test]
+    fn spreads_stops_without_position() {
+        assert_eq!(offsets(&["red"]), vec![0.0]);
+        assert_eq!(offsets(&["red", "blue"]), vec![0.0, 1.0]);
+        assert_eq!(offsets(&["red", "lime", "blue"]), vec![0.0, 0.5, 1.0]);
+        assert_eq!(
+            offsets(&["red", "lime@20%", "white", "gray", "blue@0.8"]),
+            vec![0.0, 0.2, 0.4, 0.6, 0.8]
+        );
+        assert_eq!(offsets(&["red@0.5", "lime", "blue"]), vec![0.5, 0.75, 1.0]);
+    }
+
+    #[test]
+    fn clamps_stops_into_range() {
+        assert_eq!(offsets(&["red@-0.5", "blue@150%"]), vec![0.0, 1.0]);
+        // Stops are spread between the positions as given, then clamped
+        assert_eq!(offsets(&["red@-1", "lime", "blue@1"]), vec![0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn moves_stops_up_to_earlier_ones() {
+        assert_eq!(
+            offsets(&["red@0.6", "lime@0.3", "blue"]),
+            vec![0.6, 0.6, 1.0]
+        );
+        assert_eq!(
+            offsets(&["red", "lime@0.7", "white", "blue@0.2"]),
+            vec![0.0, 0.7, 0.7, 0.7]
+        );
+    }
+
+    #[test]
+    fn keeps_stop_colors() {
+        let colors: Vec<ColorStop> = ["red", "#00F@40%"]
+            .iter()
+            .map(|stop| stop.parse().unwrap())
+            .collect();
+        let stops = color_stops(&colors);
+        assert_eq!(stops[0].color, Color::rgb(255, 0, 0));
+        assert_eq!(stops[1].color, Color::rgb(0, 0, 255));
+        assert_eq!(stops[1].offset, 0.4);
+    }
+}
@@ -0,0 +1,389 @@
+use crate::core::renderer::geometry::Path;
+use crate::core::renderer::{AlignmentPattern, FinderPattern, QrGrid};
+use crate::models::{Excavation, IconShape, QrConfig};
+
+/// Width of the border drawn around the icon, in modules.
+pub const ICON_BORDER_WIDTH: f32 = 0.25;
+
+/// A rectangle with rounded corners, measured in modules
+/// from the top left corner of the symbol, quiet zone excluded.
+/// A radius of zero makes a plain rectangle, and a radius of half the side of a square a circle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IconArea {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub radius: f32,
+}
+
+/// Where the icon and everything around it go on the symbol.
+/// Every renderer draws from this layout, so all output formats match.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IconLayout {
+    /// Where the whole icon image is drawn.
+    pub image: IconArea,
+    /// The part of the image that is shown, given the icon shape.
+    pub clip: IconArea,
+    /// The padded area around the icon, which is cleared, filled by the plate
+    /// and outlined by the border.
+    pub frame: IconArea,
+}
+
+impl IconLayout {
+    /// Lays out an icon with the given aspect ratio (width divided by height)
+    /// on a grid of the given size.
+    pub fn new(grid_width: usize, grid_height: usize, aspect: f64, config: &QrConfig) -> Self {
+        let side = grid_width.min(grid_height) as f32 * config.icon_size;
+        let aspect = aspect as f32;
+        let (width, height) = if aspect >= 1.0 {
+            (side, side / aspect)
+        } else {
+            (side * aspect, side)
+        };
+        let cx = grid_width as f32 / 2.0 + config.icon_offset.x;
+        let cy = grid_height as f32 / 2.0 + config.icon_offset.y;
+        let image = IconArea::centered(cx, cy, width, height, 0.0);
+
+        let clip = match config.icon_shape {
+            IconShape::Square => image,
+            IconShape::Rounded => IconArea::centered(
+                cx,
+                cy,
+                width,
+                height,
+                config.icon_radius.min(width.min(height) / 2.0),
+            ),
+            IconShape::Circle => {
+                let diameter = width.min(height);
+                IconArea::centered(cx, cy, diameter, diameter, diameter / 2.0)
+            }
+        };
+
+        let padding = config.icon_padding;
+        let frame = match config.excavate {
+            Excavation::None => IconArea::centered(
+                cx,
+                cy,
+                clip.width + padding * 2.0,
+                clip.height + padding * 2.0,
+                if clip.is_rect() {
+                    0.0
+                } else {
+                    clip.radius + padding
+                },
+            ),
+            Excavation::Box => IconArea::centered(
+                cx,
+                cy,
+                clip.width + padding * 2.0,
+                clip.height + padding * 2.0,
+                0.0,
+            ),
+            Excavation::Circle => {
+                let diameter = clip.width.max(clip.height) + padding * 2.0;
+                IconArea::centered(cx, cy, diameter, diameter, diameter / 2.0)
+            }
+            Excavation::Rounded => {
+                let width = clip.width + padding * 2.0;
+                let height = clip.height + padding * 2.0;
+                IconArea::centered(
+                    cx,
+                    cy,
+                    width,
+                    height,
+                    (width.min(height) * 0.25).max(clip.radius + padding),
+                )
+            }
+        };
+
+        Self { image, clip, frame }
+    }
+
+    /// The line on which the border is stroked, half its width inside the frame.
+    pub fn border(&self) -> IconArea {
+        let inset = ICON_BORDER_WIDTH / 2.0;
+        IconArea {
+            x: self.frame.x + inset,
+            y: self.frame.y + inset,
+            width: (self.frame.width - inset * 2.0).max(0.0),
+            height: (self.frame.height - inset * 2.0).max(0.0),
+            radius: (self.frame.radius - inset).max(0.0),
+        }
+    }
+}
+
+impl IconArea {
+    /// A rectangle of the given size centered on the given point.
+    fn centered(cx: f32, cy: f32, width: f32, height: f32, radius: f32) -> Self {
+        Self {
+            x: cx - width / 2.0,
+            y: cy - height / 2.0,
+            width,
+            height,
+            radius,
+        }
+    }
+
+    /// Whether the area is a plain rectangle.
+    pub fn is_rect(&self) -> bool {
+        self.radius <= 0.0
+    }
+
+    /// Builds the outline of the area, in pixels.
+    pub fn path(&self, quiet_zone: f32, scale: f32) -> Path {
+        let x = (self.x + quiet_zone) * scale;
+        let y = (self.y + quiet_zone) * scale;
+        let (width, height) = (self.width * scale, self.height * scale);
+        let mut path = Path::new();
+        if self.is_rect() {
+            path.push_rect(x, y, width, height);
+        } else {
+            path.push_rounded_rect(x, y, width, height, [self.radius * scale; 4]);
+        }
+        path
+    }
+
+    /// Whether the center of the module at the given position is inside the area.
+    pub fn contains_center(&self, x: usize, y: usize) -> bool {
+        self.reaches(x, y, 0.0)
+    }
+
+    /// Whether any part of the module at the given position is inside the area.
+    pub fn overlaps(&self, x: usize, y: usize) -> bool {
+        self.reaches(x, y, 0.5)
+    }
+
+    /// Whether a square of half side `half` centered on the module at the given position
+    /// strictly overlaps the area. The area is the rectangle shrunk by the corner radius,
+    /// grown back by the radius in every direction.
+    fn reaches(&self, x: usize, y: usize, half: f32) -> bool {
+        let gap = |c: f32, start: f32, len: f32| {
+            (c - (start + len / 2.0)).abs() - half - (len / 2.0 - self.radius)
+        };
+        let gx = gap(x as f32 + 0.5, self.x, self.width);
+        let gy = gap(y as f32 + 0.5, self.y, self.height);
+        (gx < 0.0 && gy < 0.0) || gx.max(0.0).hypot(gy.max(0.0)) < self.radius
+    }
+}
+
+/// Returns which modules of a grid of the given size the icon hides, row by row.
+/// Without excavation, plate or border, a module is hidden when its center is under the visible
+/// part of the icon. Otherwise every module partly covered by the frame is hidden.
+pub fn hidden_modules(
+    grid_width: usize,
+    grid_height: usize,
+    aspect: f64,
+    config: &QrConfig,
+) -> Vec<bool> {
+    let layout = IconLayout::new(grid_width, grid_height, aspect, config);
+    let framed =
+        config.excavate != Excavation::None || config.icon_plate || config.icon_border.is_some();
+    (0..grid_height)
+        .flat_map(|y| (0..grid_width).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            if framed {
+                layout.frame.overlaps(x, y)
+            } else {
+                layout.clip.contains_center(x, y)
+            }
+        })
+        .collect()
+}
+
+/// A grid with the modules behind the icon cleared, so that every renderer leaves them out.
+/// Finder patterns are never cleared. Alignment patterns with cleared modules are no longer
+/// patterns, so that those drawn as whole shapes are not painted back over the cleared area.
+pub struct ExcavatedGrid<'a> {
+    inner: &'a dyn QrGrid,
+    cleared: Vec<bool>,
+    alignment: Vec<AlignmentPattern>,
+}
+
+impl<'a> ExcavatedGrid<'a> {
+    /// Clears the modules under the area configured for an icon with the given aspect ratio.
+    pub fn new(inner: &'a dyn QrGrid, aspect: f64, config: &QrConfig) -> Self {
+        let (width, height) = (inner.width(), inner.height());
+        let area = IconLayout::new(width, height, aspect, config).frame;
+        let cleared = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| area.overlaps(x, y) && !inner.is_finder(x, y))
+            .collect::<Vec<bool>>();
+        let alignment = inner
+            .alignment_patterns()
+            .into_iter()
+            .filter(|pattern| {
+                (pattern.y..pattern.y + pattern.size)
+                    .all(|y| (pattern.x..pattern.x + pattern.size).all(|x| !cleared[y * width + x]))
+            })
+            .collect();
+        Self {
+            inner,
+            cleared,
+            alignment,
+        }
+    }
+}
+
+impl QrGrid for ExcavatedGrid<'_> {
+    fn width(&self) -> usize {
+        self.inner.width()
+    }
+
+    fn height(&self) -> usize {
+        self.inner.height()
+    }
+
+    fn get_module(&self, x: usize, y: usize) -> bool {
+        !self.cleared[y * self.width() + x] && self.inner.get_module(x, y)
+    }
+
+    fn finder_patterns(&self) -> Vec<FinderPattern> {
+        self.inner.finder_patterns()
+    }
+
+    fn is_finder(&self, x: usize, y: usize) -> bool {
+        self.inner.is_finder(x, y)
+    }
+
+    fn alignment_patterns(&self) -> Vec<AlignmentPattern> {
+        self.alignment.clone()
+    }
+
+    fn is_alignment(&self, x: usize, y: usize) -> bool {
+        self.alignment
+            .iter()
+            .any(|a| x >= a.x && x < a.x + a.size && y >= a.y && y < a.y + a.size)
+    }
+
+    fn is_timing(&self, x: usize, y: usize) -> bool {
+        self.inner.is_timing(x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::qrgen::{QrCode, QrCodeEcc, QrSegment, Version};
+    use crate::core::renderer::finder::alignment_outlines;
+    use crate::core::renderer::png::render_qr;
+    use crate::models::{AlignmentShape, IconOffset};
+
+    fn symbol(version: u8) -> QrCode {
+        let version = Version::new(version);
+        QrCode::encode_segments_advanced(
+            &QrSegment::make_segments("https://example.com/excavation"),
+            QrCodeEcc::Medium,
+            version,
+            version,
+            None,
+            false,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn drops_alignment_patterns_with_cleared_modules() {
+        let qr = symbol(7);
+        let config = QrConfig {
+            excavate: Excavation::Box,
+            icon_padding: 1.0,
+            icon_offset: IconOffset { x: 4.0, y: 0.0 },
+            alignment: AlignmentShape::Square,
+            ..Default::default()
+        };
+        let grid = ExcavatedGrid::new(&qr, 1.0, &config);
+
+        let center = AlignmentPattern {
+            x: 20,
+            y: 20,
+            size: 5,
+        };
+        assert!(qr.alignment_patterns().contains(&center));
+        assert!(!grid.alignment_patterns().contains(&center));
+        assert!(!grid.is_alignment(22, 22));
+        for pattern in grid.alignment_patterns() {
+            assert!(
+                (pattern.y..pattern.y + 5)
+                    .all(|y| (pattern.x..pattern.x + 5).all(|x| !grid.cleared[y * 45 + x]))
+            );
+        }
+        assert_eq!(
+            alignment_outlines(&grid, &config, 1.0).len(),
+            grid.alignment_patterns().len()
+        );
+
+        // The eye of the center pattern stays cleared once rendered
+        let pixmap = render_qr(&grid, &config, 4.0).unwrap();
+        let pixel = pixmap.pixel((22 + 4) * 4 + 2, (22 + 4) * 4 + 2).unwrap();
+        assert_eq!((pixel.red(), pixel.green(), pixel.blue()), (255, 255, 255));
+    }
+
+    #[test]
+    fn counts_cleared_modules_for_each_excavation() {
+        let qr = symbol(7);
+        // The icon is 11.25 modules wide, framed 12.25 wide around (25.5, 20.5)
+        let cases = [
+            (Excavation::None, 121),
+            (Excavation::Box, 169),
+            (Excavation::Circle, 145),
+            (Excavation::Rounded, 165),
+        ];
+        for (excavate, count) in cases {
+            let config = QrConfig {
+                excavate,
+                icon_padding: 0.5,
+                icon_offset: IconOffset { x: 3.0, y: -2.0 },
+                ..Default::default()
+            };
+            let hidden = hidden_modules(45, 45, 1.0, &config);
+            assert_eq!(hidden.iter().filter(|&&h| h).count(), count, "{excavate:?}");
+            if excavate == Excavation::None {
+                continue;
+            }
+
+            let grid = ExcavatedGrid::new(&qr, 1.0, &config);
+            assert_eq!(grid.cleared, hidden, "{excavate:?}");
+            for (i, &cleared) in grid.cleared.iter().enumerate() {
+                let (x, y) = (i % 45, i / 45);
+                if cleared {
+                    assert!(!grid.get_module(x, y));
+                } else {
+                    assert_eq!(grid.get_module(x, y), QrGrid::get_module(&qr, x, y));
+                }
+            }
+        }
+        // The box is 13 modules wide, from (19, 14)
+        let config = QrConfig {
+            excavate: Excavation::Box,
+            icon_padding: 0.5,
+            icon_offset: IconOffset { x: 3.0, y: -2.0 },
+            ..Default::default()
+        };
+        let grid = ExcavatedGrid::new(&qr, 1.0, &config);
+        for y in 0..45 {
+            for x in 0..45 {
+                let inside = (19..32).contains(&x) && (14..27).contains(&y);
+                assert_eq!(grid.cleared[y * 45 + x], inside, "({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn never_clears_finder_patterns() {
+        let qr = symbol(7);
+        let config = QrConfig {
+            excavate: Excavation::Box,
+            icon_padding: 0.0,
+            icon_offset: IconOffset { x: -14.0, y: -14.0 },
+            ..Default::default()
+        };
+        // The box covers modules 2 to 14 each way, 5x5 of them in the top left finder
+        let hidden = hidden_modules(45, 45, 1.0, &config);
+        assert_eq!(hidden.iter().filter(|&&h| h).count(), 169);
+        let grid = ExcavatedGrid::new(&qr, 1.0, &config);
+        assert_eq!(grid.cleared.iter().filter(|&&c| c).count(), 169 - 25);
+        assert!((0..7).all(|y| (0..7).all(|x| !grid.cleared[y * 45 + x])));
+        assert_eq!(grid.finder_patterns().len(), 3);
+    }
+}
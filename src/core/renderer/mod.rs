@@ -1,10 +1,62 @@
-use crate::core::qrgen::QrCode;
+use crate::core::qrgen::{MicroQrCode, QrCode};
+use crate::core::renderer::finder::{Corner, CornerTurn, Outline};
+use crate::core::rmqr::RmqrCode;
+use crate::models::{AlignmentShape, ModuleShape, QrConfig};
 
+pub mod contour;
 pub mod eps;
+pub mod finder;
+pub mod geometry;
+pub mod gradient;
+pub mod icon;
 pub mod pdf;
 pub mod png;
+pub mod shape;
 pub mod svg;
 pub mod utils;
+pub mod vector;
+
+pub use shape::{FinderShapeProvider, ModuleShapeProvider};
+
+/// Returns the shape the module at the given position is drawn with, or `None` for modules
+/// drawn as part of a whole pattern: finders, and alignment patterns unless they are drawn
+/// module by module. Timing patterns are kept square along with such alignment patterns.
+pub fn module_shape<G: QrGrid + ?Sized>(
+    grid: &G,
+    config: &QrConfig,
+    x: usize,
+    y: usize,
+) -> Option<ModuleShape> {
+    if grid.is_finder(x, y) {
+        return None;
+    }
+    if config.alignment == AlignmentShape::Modules {
+        return Some(config.shape);
+    }
+    if grid.is_alignment(x, y) {
+        None
+    } else if grid.is_timing(x, y) {
+        Some(ModuleShape::Square)
+    } else {
+        Some(config.shape)
+    }
+}
+
+/// Returns the custom provider that draws the module at the given position instead of
+/// its shape, if there is one. Timing patterns kept square are left to their shape.
+pub fn custom_module_shape<'a, G: QrGrid + ?Sized>(
+    grid: &G,
+    config: &'a QrConfig,
+    x: usize,
+    y: usize,
+) -> Option<&'a dyn ModuleShapeProvider> {
+    let provider = config.module_provider.as_deref()?;
+    module_shape(grid, config, x, y)?;
+    if config.alignment != AlignmentShape::Modules && grid.is_timing(x, y) {
+        return None;
+    }
+    Some(provider)
+}
 
 /// Trait for QR code renderers.
 /// Allows rendering to an in-memory format and saving to a file.
@@ -21,43 +73,302 @@ pub struct ModuleContext {
     pub bottom: bool,
     pub left: bool,
     pub right: bool,
+    pub top_left: bool,
+    pub top_right: bool,
+    pub bottom_left: bool,
+    pub bottom_right: bool,
+}
+
+impl ModuleContext {
+    /// Outline of a dark module joined into a blob with its dark neighbours:
+    /// corners with no dark neighbour on either side are rounded with the given radius.
+    pub fn liquid_outline(&self, x: f32, y: f32, size: f32, radius: f32) -> Outline {
+        let open = [
+            !self.top && !self.left,
+            !self.top && !self.right,
+            !self.bottom && !self.right,
+            !self.bottom && !self.left,
+        ];
+        Outline::Square {
+            x,
+            y,
+            size,
+            corners: open.map(|open| {
+                if open {
+                    Corner::Round(radius)
+                } else {
+                    Corner::Sharp
+                }
+            }),
+        }
+    }
+
+    /// Concave corners where the module and two dark neighbours wrap around a light
+    /// diagonal one. Each is filled in up to the curve a round corner with the given
+    /// radius would take there, so the blob flows around the light module.
+    pub fn liquid_fillets(&self, x: f32, y: f32, size: f32, radius: f32) -> Vec<CornerTurn> {
+        let (x1, y1) = (x + size, y + size);
+        let corners = [
+            (self.top && self.left && !self.top_left, (x, y), -1.0, -1.0),
+            (
+                self.top && self.right && !self.top_right,
+                (x1, y),
+                1.0,
+                -1.0,
+            ),
+            (
+                self.bottom && self.right && !self.bottom_right,
+                (x1, y1),
+                1.0,
+                1.0,
+            ),
+            (
+                self.bottom && self.left && !self.bottom_left,
+                (x, y1),
+                -1.0,
+                1.0,
+            ),
+        ];
+        corners
+            .into_iter()
+            .filter(|&(concave, ..)| concave)
+            .map(|(_, at, dx, dy)| CornerTurn {
+                from: (at.0 + dx * radius, at.1),
+                at,
+                to: (at.0, at.1 + dy * radius),
+                corner: Corner::Round(radius),
+            })
+            .collect()
+    }
+}
+
+/// Corners of the symbol that finder patterns are placed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinderCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Position and size of a finder pattern in the grid, measured in modules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FinderPattern {
+    pub x: usize,
+    pub y: usize,
+    /// Width and height of the pattern: 7 for regular finders, 5 for the rMQR sub-finder.
+    pub size: usize,
+    pub corner: FinderCorner,
+}
+
+/// Position and size of an alignment pattern in the grid, measured in modules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlignmentPattern {
+    pub x: usize,
+    pub y: usize,
+    /// Width and height of the pattern: 5 for QR codes, 3 for rMQR codes.
+    pub size: usize,
 }
 
 /// Trait representing a QR code grid for rendering purposes.
+/// Grids may be rectangular, so the width and height are reported separately.
 pub trait QrGrid {
-    fn size(&self) -> usize;
+    fn width(&self) -> usize;
+    fn height(&self) -> usize;
     fn get_module(&self, x: usize, y: usize) -> bool;
     fn is_dark(&self, x: usize, y: usize) -> bool {
         self.get_module(x, y)
     }
+    /// Returns the finder patterns of the symbol.
+    /// Defaults to the three 7x7 corners used by a regular QR code.
+    fn finder_patterns(&self) -> Vec<FinderPattern> {
+        let right = self.width() - 7;
+        let bottom = self.height() - 7;
+        vec![
+            FinderPattern {
+                x: 0,
+                y: 0,
+                size: 7,
+                corner: FinderCorner::TopLeft,
+            },
+            FinderPattern {
+                x: right,
+                y: 0,
+                size: 7,
+                corner: FinderCorner::TopRight,
+            },
+            FinderPattern {
+                x: 0,
+                y: bottom,
+                size: 7,
+                corner: FinderCorner::BottomLeft,
+            },
+        ]
+    }
     fn is_finder(&self, x: usize, y: usize) -> bool {
-        if x < 7 && y < 7 {
-            return true;
-        }
-        if x >= self.size() - 7 && y < 7 {
-            return true;
-        }
-        if x < 7 && y >= self.size() - 7 {
-            return true;
-        }
+        self.finder_patterns()
+            .iter()
+            .any(|f| x >= f.x && x < f.x + f.size && y >= f.y && y < f.y + f.size)
+    }
+    /// Returns the alignment patterns of the symbol. Defaults to none.
+    fn alignment_patterns(&self) -> Vec<AlignmentPattern> {
+        Vec::new()
+    }
+    fn is_alignment(&self, x: usize, y: usize) -> bool {
+        self.alignment_patterns()
+            .iter()
+            .any(|a| x >= a.x && x < a.x + a.size && y >= a.y && y < a.y + a.size)
+    }
+    /// Whether the module is part of a timing pattern, outside finder and alignment patterns.
+    /// Defaults to none.
+    fn is_timing(&self, _x: usize, _y: usize) -> bool {
         false
     }
     fn module_context(&self, x: usize, y: usize) -> ModuleContext {
         ModuleContext {
             top: y > 0 && self.get_module(x, y - 1),
-            bottom: y < self.size() - 1 && self.get_module(x, y + 1),
+            bottom: y < self.height() - 1 && self.get_module(x, y + 1),
             left: x > 0 && self.get_module(x - 1, y),
-            right: x < self.size() - 1 && self.get_module(x + 1, y),
+            right: x < self.width() - 1 && self.get_module(x + 1, y),
+            top_left: x > 0 && y > 0 && self.get_module(x - 1, y - 1),
+            top_right: x < self.width() - 1 && y > 0 && self.get_module(x + 1, y - 1),
+            bottom_left: x > 0 && y < self.height() - 1 && self.get_module(x - 1, y + 1),
+            bottom_right: x < self.width() - 1
+                && y < self.height() - 1
+                && self.get_module(x + 1, y + 1),
         }
     }
 }
 
 impl QrGrid for QrCode {
-    fn size(&self) -> usize {
+    fn width(&self) -> usize {
+        self.size() as usize
+    }
+
+    fn height(&self) -> usize {
+        self.size() as usize
+    }
+
+    fn get_module(&self, x: usize, y: usize) -> bool {
+        self.get_module(x as i32, y as i32)
+    }
+
+    /// The alignment patterns at every pair of positions, except those on the finders.
+    fn alignment_patterns(&self) -> Vec<AlignmentPattern> {
+        let positions = self.get_alignment_pattern_positions();
+        let last = positions.len().saturating_sub(1);
+        let mut patterns = Vec::new();
+        for (i, &y) in positions.iter().enumerate() {
+            for (j, &x) in positions.iter().enumerate() {
+                let end = |k: usize| k == 0 || k == last;
+                if end(i) && end(j) && (i, j) != (last, last) {
+                    continue;
+                }
+                patterns.push(AlignmentPattern {
+                    x: x as usize - 2,
+                    y: y as usize - 2,
+                    size: 5,
+                });
+            }
+        }
+        patterns
+    }
+
+    /// Row and column 6, between the finders.
+    fn is_timing(&self, x: usize, y: usize) -> bool {
+        let between = |i: usize| (8..self.width() - 8).contains(&i);
+        ((y == 6 && between(x)) || (x == 6 && between(y))) && !self.is_alignment(x, y)
+    }
+}
+
+impl QrGrid for MicroQrCode {
+    fn width(&self) -> usize {
+        self.size() as usize
+    }
+
+    fn height(&self) -> usize {
         self.size() as usize
     }
 
     fn get_module(&self, x: usize, y: usize) -> bool {
         self.get_module(x as i32, y as i32)
     }
+
+    fn finder_patterns(&self) -> Vec<FinderPattern> {
+        vec![FinderPattern {
+            x: 0,
+            y: 0,
+            size: 7,
+            corner: FinderCorner::TopLeft,
+        }]
+    }
+
+    /// Row and column 0, after the finder.
+    fn is_timing(&self, x: usize, y: usize) -> bool {
+        (y == 0 && x >= 8) || (x == 0 && y >= 8)
+    }
+}
+
+impl QrGrid for RmqrCode {
+    fn width(&self) -> usize {
+        self.width() as usize
+    }
+
+    fn height(&self) -> usize {
+        self.height() as usize
+    }
+
+    fn get_module(&self, x: usize, y: usize) -> bool {
+        self.get_module(x as i32, y as i32)
+    }
+
+    /// The finder pattern in the top left corner and the sub-finder in the bottom right.
+    fn finder_patterns(&self) -> Vec<FinderPattern> {
+        let width = self.width() as usize;
+        let height = self.height() as usize;
+        vec![
+            FinderPattern {
+                x: 0,
+                y: 0,
+                size: 7,
+                corner: FinderCorner::TopLeft,
+            },
+            FinderPattern {
+                x: width - 5,
+                y: height - 5,
+                size: 5,
+                corner: FinderCorner::BottomRight,
+            },
+        ]
+    }
+
+    /// The 3x3 patterns at both ends of each alignment column.
+    fn alignment_patterns(&self) -> Vec<AlignmentPattern> {
+        let height = self.height() as usize;
+        self.alignment_columns()
+            .iter()
+            .flat_map(|&cx| {
+                [0, height - 3].map(|y| AlignmentPattern {
+                    x: usize::from(cx) - 1,
+                    y,
+                    size: 3,
+                })
+            })
+            .collect()
+    }
+
+    /// The edges of the symbol and the alignment columns, outside finder and alignment patterns.
+    fn is_timing(&self, x: usize, y: usize) -> bool {
+        let width = self.width() as usize;
+        let height = self.height() as usize;
+        let on_line = y == 0
+            || y == height - 1
+            || x == 0
+            || x == width - 1
+            || self
+                .alignment_columns()
+                .iter()
+                .any(|&cx| usize::from(cx) == x);
+        on_line && !self.is_finder(x, y) && !self.is_alignment(x, y)
+    }
 }
@@ -1,14 +1,16 @@
+use crate::core::renderer::geometry::{FillRule, SymbolGeometry};
+use crate::core::renderer::gradient::{Fill, Gradient};
+use crate::core::renderer::icon::{ICON_BORDER_WIDTH, IconArea, IconLayout};
+use crate::core::renderer::vector::VectorIcon;
 use crate::core::renderer::{QrGrid, QrRenderer, utils};
-use crate::models::{GradientDirection, QrConfig, QrImage};
+use crate::models::{Color, QrConfig, QrImage};
 use image::{ColorType, DynamicImage, GenericImageView};
 use std::fs::File;
 use std::io::Write;
 
-mod finder;
-mod module;
+mod path;
 
-use finder::append_finder_path;
-use module::append_module_path;
+use path::{append_path, clip_operator, fill_operator};
 
 pub struct PdfRenderer {
     data: Vec<u8>,
@@ -57,7 +59,7 @@ impl PdfWriter {
     fn start_obj(&mut self) -> usize {
         let id = self.offsets.len();
         self.offsets.push(self.buffer.len());
-        write!(&mut self.buffer, "{} 0 obj\n", id).unwrap();
+        writeln!(&mut self.buffer, "{} 0 obj", id).unwrap();
         id
     }
 
@@ -76,21 +78,21 @@ impl PdfWriter {
     fn finish(&mut self, root_id: usize) -> Vec<u8> {
         let xref_offset = self.buffer.len();
         self.buffer.extend_from_slice(b"xref\n");
-        write!(&mut self.buffer, "0 {}\n", self.offsets.len()).unwrap();
+        writeln!(&mut self.buffer, "0 {}", self.offsets.len()).unwrap();
         self.buffer.extend_from_slice(b"0000000000 65535 f \n");
         for offset in self.offsets.iter().skip(1) {
-            write!(&mut self.buffer, "{:010} 00000 n \n", offset).unwrap();
+            writeln!(&mut self.buffer, "{:010} 00000 n ", offset).unwrap();
         }
         self.buffer.extend_from_slice(b"trailer\n");
-        write!(
+        writeln!(
             &mut self.buffer,
-            "<< /Size {} /Root {} 0 R >>\n",
+            "<< /Size {} /Root {} 0 R >>",
             self.offsets.len(),
             root_id
         )
         .unwrap();
         self.buffer.extend_from_slice(b"startxref\n");
-        write!(&mut self.buffer, "{}\n", xref_offset).unwrap();
+        writeln!(&mut self.buffer, "{}", xref_offset).unwrap();
         self.buffer.extend_from_slice(b"%%EOF\n");
         self.buffer.clone()
     }
@@ -121,7 +123,7 @@ impl PdfWriter {
         let mut smask_id = None;
         if let Some(alpha) = alpha_data {
             let id = self.start_obj();
-            write!(&mut self.buffer, "<< /Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceGray /BitsPerComponent 8 /Length {} >>\n", width, height, alpha.len()).unwrap();
+            writeln!(&mut self.buffer, "<< /Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceGray /BitsPerComponent 8 /Length {} >>", width, height, alpha.len()).unwrap();
             self.write("stream\n");
             self.write_bytes(&alpha);
             self.write("\nendstream\n");
@@ -134,7 +136,7 @@ impl PdfWriter {
         if let Some(sm_id) = smask_id {
             write!(&mut self.buffer, "/SMask {} 0 R ", sm_id).unwrap();
         }
-        write!(&mut self.buffer, "/Length {} >>\n", rgb_data.len()).unwrap();
+        writeln!(&mut self.buffer, "/Length {} >>", rgb_data.len()).unwrap();
         self.write("stream\n");
         self.write_bytes(&rgb_data);
         self.write("\nendstream\n");
@@ -149,53 +151,37 @@ fn render_pdf<G: QrGrid + ?Sized>(
     options: &QrConfig,
     pixel_size: f32,
 ) -> Result<Vec<u8>, String> {
-    let size = grid.size();
+    let width = grid.width();
+    let height = grid.height();
     let quiet_zone = options.quiet_zone as f32;
-    let width_modules = size as f32 + quiet_zone * 2.0;
-    let width_px = width_modules * pixel_size;
-    let height_px = width_px;
+    let width_px = (width as f32 + quiet_zone * 2.0) * pixel_size;
+    let height_px = (height as f32 + quiet_zone * 2.0) * pixel_size;
 
     let mut writer = PdfWriter::new();
 
-    let mut icon_info = None;
-    if let Some(image) = &options.image {
-        match image {
-            crate::models::QrImage::Raster(img) => {
-                let (id, _) = writer.create_image_xobject(&img);
-                // Calculate size and position
-                // Default to 25% of QR size
-                let icon_size_px = width_px * 0.25;
-                let icon_x = (width_px - icon_size_px) / 2.0;
-                let icon_y = (height_px - icon_size_px) / 2.0;
-                icon_info = Some((id, icon_size_px, icon_size_px, icon_x, icon_y));
-            }
-            crate::models::QrImage::Svg(_) => {
-                return Err(format!("PDF output does not support SVG icons."));
-            }
-        }
-    } else if let Some(icon_path) = &options.icon {
-        match QrImage::load_from_path(icon_path) {
-            Ok(image) => match image {
-                crate::models::QrImage::Raster(img) => {
-                    let (id, _) = writer.create_image_xobject(&img);
-                    // Calculate size and position
-                    // Default to 25% of QR size
-                    let icon_size_px = width_px * 0.25;
-                    let icon_x = (width_px - icon_size_px) / 2.0;
-                    let icon_y = (height_px - icon_size_px) / 2.0;
-                    icon_info = Some((id, icon_size_px, icon_size_px, icon_x, icon_y));
-                }
-                crate::models::QrImage::Svg(_) => {
-                    return Err(format!("PDF output does not support SVG icons."));
-                }
-            },
-            Err(e) => {
-                return Err(format!(
-                    "Icon file '{}' could not be loaded: {}",
-                    icon_path, e
-                ));
-            }
+    let loaded;
+    let image = match (&options.image, &options.icon) {
+        (Some(image), _) => Some(image),
+        (None, Some(icon_path)) => {
+            loaded = QrImage::load_from_path(icon_path)
+                .map_err(|e| format!("Icon file '{}' could not be loaded: {}", icon_path, e))?;
+            Some(&loaded)
         }
+        (None, None) => None,
+    };
+
+    // SVG icons are drawn as vector paths, raster icons as an image XObject
+    let mut icon_info = None;
+    if let Some(image) = image {
+        let aspect = image
+            .aspect_ratio()
+            .ok_or("Icon image dimensions could not be determined.")?;
+        let layout = IconLayout::new(width, height, aspect, options);
+        let icon = match image {
+            QrImage::Raster(img) => PdfIcon::Image(writer.create_image_xobject(img).0),
+            QrImage::Svg(content) => PdfIcon::Vector(VectorIcon::parse(content)?),
+        };
+        icon_info = Some((icon, layout));
     }
 
     // 1. Content Stream
@@ -207,107 +193,132 @@ fn render_pdf<G: QrGrid + ?Sized>(
     use std::fmt::Write as FmtWrite;
     writeln!(&mut content, "1 0 0 -1 0 {:.4} cm", height_px).unwrap();
 
-    // Background
-    if let Some(bg) = parse_color(&options.background) {
-        writeln!(&mut content, "{:.3} {:.3} {:.3} rg", bg.0, bg.1, bg.2).unwrap();
+    // Graphics states with the opacities of colors that can be seen through, as /GS1, /GS2...
+    let mut opacities = Vec::new();
+
+    // Background, left out to keep the page transparent
+    if !options.background.is_transparent() {
+        writeln!(&mut content, "q").unwrap();
+        set_color(&mut content, options.background, "rg", &mut opacities);
         writeln!(&mut content, "0 0 {:.4} {:.4} re f", width_px, height_px).unwrap();
+        writeln!(&mut content, "Q").unwrap();
     }
 
-    // Save state for QR drawing (to isolate clipping)
-    writeln!(&mut content, "q").unwrap();
-
-    // Foreground Color (only if solid)
-    if options.foreground.len() <= 1 {
-        let fg = if let Some(c) = options.foreground.first().and_then(|c| parse_color(c)) {
-            c
-        } else {
-            (0.0, 0.0, 0.0)
-        };
-        writeln!(&mut content, "{:.3} {:.3} {:.3} rg", fg.0, fg.1, fg.2).unwrap();
+    // Draw modules, then finders if they are painted apart from them.
+    // Gradients are named after the fill, /Sh1 and /P1 for the modules, and so are
+    // the soft masks /M1... of gradients that can be seen through.
+    let geometry = SymbolGeometry::new(grid, options, pixel_size);
+    let mut fills = vec![(
+        Fill::foreground(grid, options, pixel_size),
+        &geometry.foreground,
+    )];
+    if !geometry.finders.is_empty() {
+        fills.push((Fill::finders(grid, options, pixel_size), &geometry.finders));
     }
-
-    // Draw modules
-    for y in 0..size {
-        for x in 0..size {
-            if grid.is_dark(x, y) {
-                if grid.is_finder(x, y) {
-                    continue; // Finders are drawn separately
-                }
-
-                let px = (x as f32 + quiet_zone) * pixel_size;
-                let py = (y as f32 + quiet_zone) * pixel_size;
-                let ctx = grid.module_context(x, y);
-
-                append_module_path(&mut content, options.shape, px, py, pixel_size, &ctx);
+    for (n, (fill, path)) in fills.iter().enumerate() {
+        let n = n + 1;
+        // Save state for QR drawing (to isolate clipping)
+        writeln!(&mut content, "q").unwrap();
+        if let Fill::Gradient(_) = fill
+            && !fill.is_opaque()
+        {
+            writeln!(&mut content, "/M{} gs", n).unwrap();
+        }
+        match fill {
+            Fill::Solid(color) => {
+                set_color(&mut content, *color, "rg", &mut opacities);
+                append_path(&mut content, path);
+                writeln!(&mut content, "{}", fill_operator(path)).unwrap();
+            }
+            Fill::Gradient(gradient) if gradient.tile.is_none() => {
+                // Clip to the modules and paint the shading over them
+                append_path(&mut content, path);
+                writeln!(&mut content, "{}", clip_operator(path)).unwrap();
+                writeln!(&mut content, "/Sh{} sh", n).unwrap();
+            }
+            Fill::Gradient(_) => {
+                // Fill the modules with a pattern repeating the shading in every module
+                writeln!(&mut content, "/Pattern cs /P{} scn", n).unwrap();
+                append_path(&mut content, path);
+                writeln!(&mut content, "{}", fill_operator(path)).unwrap();
             }
         }
+        // Restore state (removes clipping)
+        writeln!(&mut content, "Q").unwrap();
     }
 
-    // Finders
-    // Top-Left
-    append_finder_path(
-        &mut content,
-        options.finder,
-        quiet_zone * pixel_size,
-        quiet_zone * pixel_size,
-        pixel_size,
-    );
-    // Top-Right
-    append_finder_path(
-        &mut content,
-        options.finder,
-        (size as f32 - 7.0 + quiet_zone) * pixel_size,
-        quiet_zone * pixel_size,
-        pixel_size,
-    );
-    // Bottom-Left
-    append_finder_path(
-        &mut content,
-        options.finder,
-        quiet_zone * pixel_size,
-        (size as f32 - 7.0 + quiet_zone) * pixel_size,
-        pixel_size,
-    );
-
-    // Fill or Clip
-    let mut shading_id = None;
-
-    if options.foreground.len() > 1 {
-        // Gradient Mode
-        // 1. Clip to the path
-        // Use Non-Zero winding rule (W) instead of Even-Odd (W*) to ensure overlapping modules merge
-        writeln!(&mut content, "W n").unwrap();
-
-        // 2. Paint Shading
-        writeln!(&mut content, "/Sh1 sh").unwrap();
-
-        // We need to create the Shading object later
-        shading_id = Some(0); // Placeholder, will be assigned
-    } else {
-        // Solid Mode
-        // Use Non-Zero winding rule (f) instead of Even-Odd (f*)
-        writeln!(&mut content, "f").unwrap();
+    for (color, path) in &geometry.colored {
+        writeln!(&mut content, "q").unwrap();
+        set_color(&mut content, *color, "rg", &mut opacities);
+        append_path(&mut content, path);
+        writeln!(&mut content, "{}", fill_operator(path)).unwrap();
+        writeln!(&mut content, "Q").unwrap();
     }
 
-    // Restore state (removes clipping)
-    writeln!(&mut content, "Q").unwrap();
+    // Draw Icon, over its plate and under its border
+    if let Some((icon, layout)) = &icon_info {
+        let area_path = |content: &mut String, area: &IconArea| {
+            append_path(content, &area.path(quiet_zone, pixel_size));
+        };
+
+        if options.icon_plate && !options.background.is_transparent() {
+            writeln!(&mut content, "q").unwrap();
+            set_color(&mut content, options.background, "rg", &mut opacities);
+            area_path(&mut content, &layout.frame);
+            writeln!(&mut content, "f").unwrap();
+            writeln!(&mut content, "Q").unwrap();
+        }
 
-    // Draw Icon
-    if let Some((_, w, h, x, y)) = icon_info {
         writeln!(&mut content, "q").unwrap();
-        // Translate to position, Scale to size, Flip Y (to draw upright in flipped coords)
-        // Matrix: w 0 0 -h x (y+h)
-        writeln!(
-            &mut content,
-            "{:.4} 0 0 -{:.4} {:.4} {:.4} cm",
-            w,
-            h,
-            x,
-            y + h
-        )
-        .unwrap();
-        writeln!(&mut content, "/Im1 Do").unwrap();
+        if layout.clip != layout.image {
+            area_path(&mut content, &layout.clip);
+            writeln!(&mut content, "W n").unwrap();
+        }
+        let (w, h) = (
+            layout.image.width * pixel_size,
+            layout.image.height * pixel_size,
+        );
+        let x = (layout.image.x + quiet_zone) * pixel_size;
+        let y = (layout.image.y + quiet_zone) * pixel_size;
+        match icon {
+            PdfIcon::Image(_) => {
+                // Translate to position, Scale to size, Flip Y (to draw upright in flipped coords)
+                // Matrix: w 0 0 -h x (y+h)
+                writeln!(
+                    &mut content,
+                    "{:.4} 0 0 -{:.4} {:.4} {:.4} cm",
+                    w,
+                    h,
+                    x,
+                    y + h
+                )
+                .unwrap();
+                writeln!(&mut content, "/Im1 Do").unwrap();
+            }
+            PdfIcon::Vector(vector) => {
+                // SVG coordinates already point down, like the flipped page
+                writeln!(
+                    &mut content,
+                    "{:.4} 0 0 {:.4} {:.4} {:.4} cm",
+                    w / vector.width,
+                    h / vector.height,
+                    x,
+                    y
+                )
+                .unwrap();
+                append_vector_icon(&mut content, vector);
+            }
+        }
         writeln!(&mut content, "Q").unwrap();
+
+        if let Some(border) = options.icon_border {
+            writeln!(&mut content, "q").unwrap();
+            set_color(&mut content, border, "RG", &mut opacities);
+            writeln!(&mut content, "{:.4} w", ICON_BORDER_WIDTH * pixel_size).unwrap();
+            area_path(&mut content, &layout.border());
+            writeln!(&mut content, "S").unwrap();
+            writeln!(&mut content, "Q").unwrap();
+        }
     }
 
     writer.write(&format!("<< /Length {} >>\n", content.len()));
@@ -320,57 +331,74 @@ fn render_pdf<G: QrGrid + ?Sized>(
     let mut resources_str = String::new();
     let mut shading_res = String::new();
 
-    if let Some(_) = shading_id {
-        let colors: Vec<(f32, f32, f32)> = options
-            .foreground
-            .iter()
-            .filter_map(|c| parse_color(c))
-            .collect();
-
-        if !colors.is_empty() {
-            // Function Object
-            let func_id = writer.start_obj();
-            writer.write(&utils::generate_pdf_ps_gradient_function(&colors));
-            writer.write("\n");
-            writer.end_obj();
-
-            // Shading Object
-            let sh_id = writer.start_obj();
-            let (x0, y0, r0, x1, y1, r1) =
-                utils::get_gradient_coords(options.gradient_direction, width_px, height_px);
-            let shading_type = if options.gradient_direction == GradientDirection::Radial {
-                3
-            } else {
-                2
-            };
+    let (mut shadings, mut patterns) = (String::new(), String::new());
+    let mut ext_gstates: String = opacities
+        .iter()
+        .enumerate()
+        .map(|(i, opacity)| format!(" /GS{} << {} >>", i + 1, opacity))
+        .collect();
+    for (n, (fill, _)) in fills.iter().enumerate() {
+        let Fill::Gradient(gradient) = fill else {
+            continue;
+        };
+        let n = n + 1;
+        // Tiles are laid out in the flipped coordinates of the content stream
+        let flip = format!("[1 0 0 -1 0 {:.4}]", height_px);
+        match write_gradient(&mut writer, gradient, false, &flip) {
+            GradientPaint::Shading(id) => write!(&mut shadings, " /Sh{} {} 0 R", n, id).unwrap(),
+            GradientPaint::Pattern(id) => write!(&mut patterns, " /P{} {} 0 R", n, id).unwrap(),
+        }
 
+        if !fill.is_opaque() {
+            // The opacities of the stops are blended the same way, in gray, into a soft mask
+            // painted in the coordinates of the content stream where it is set
+            let (resources, paint) =
+                match write_gradient(&mut writer, gradient, true, "[1 0 0 1 0 0]") {
+                    GradientPaint::Shading(id) => (
+                        format!("/Shading << /Sh1 {} 0 R >>", id),
+                        "/Sh1 sh\n".to_string(),
+                    ),
+                    GradientPaint::Pattern(id) => (
+                        format!("/Pattern << /P1 {} 0 R >>", id),
+                        format!(
+                            "/Pattern cs /P1 scn 0 0 {:.4} {:.4} re f\n",
+                            width_px, height_px
+                        ),
+                    ),
+                };
+            let form_id = writer.start_obj();
             writer.write(&format!(
-                "<< /ShadingType {} /ColorSpace /DeviceRGB ",
-                shading_type
-            ));
-            if shading_type == 2 {
-                writer.write(&format!(
-                    "/Coords [ {:.3} {:.3} {:.3} {:.3} ] ",
-                    x0, y0, x1, y1
-                ));
-            } else {
-                writer.write(&format!(
-                    "/Coords [ {:.3} {:.3} {:.3} {:.3} {:.3} {:.3} ] ",
-                    x0, y0, r0, x1, y1, r1
-                ));
-            }
-            writer.write(&format!(
-                "/Function {} 0 R /Extend [ true true ] >>\n",
-                func_id
+                "<< /Type /XObject /Subtype /Form /BBox [0 0 {:.4} {:.4}] \
+                 /Group << /S /Transparency /CS /DeviceGray >> /Resources << {} >> /Length {} >>\n",
+                width_px,
+                height_px,
+                resources,
+                paint.len()
             ));
+            writer.write("stream\n");
+            writer.write(&paint);
+            writer.write("endstream\n");
             writer.end_obj();
-
-            shading_res = format!(" /Shading << /Sh1 {} 0 R >>", sh_id);
+            write!(
+                &mut ext_gstates,
+                " /M{} << /SMask << /Type /Mask /S /Luminosity /G {} 0 R >> >>",
+                n, form_id
+            )
+            .unwrap();
         }
     }
+    if !shadings.is_empty() {
+        shading_res = format!(" /Shading <<{} >>", shadings);
+    }
+    if !patterns.is_empty() {
+        shading_res.push_str(&format!(" /Pattern <<{} >>", patterns));
+    }
+    if !ext_gstates.is_empty() {
+        shading_res.push_str(&format!(" /ExtGState <<{} >>", ext_gstates));
+    }
 
     let mut xobject_res = String::new();
-    if let Some((id, _, _, _, _)) = icon_info {
+    if let Some((PdfIcon::Image(id), _)) = icon_info {
         xobject_res = format!(" /XObject << /Im1 {} 0 R >>", id);
     }
 
@@ -422,7 +450,122 @@ fn render_pdf<G: QrGrid + ?Sized>(
     Ok(writer.finish(catalog_id))
 }
 
-fn parse_color(hex: &str) -> Option<(f32, f32, f32)> {
-    utils::parse_hex_color(hex)
-        .map(|(r, g, b)| (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0))
+/// Sets the color for filling ("rg") or stroking ("RG"), with its opacity in a graphics
+/// state if it can be seen through. Graphics states are named after their position
+/// in the given list, which gets the ones not used yet.
+fn set_color(content: &mut String, color: Color, operator: &str, opacities: &mut Vec<String>) {
+    use std::fmt::Write as FmtWrite;
+
+    if !color.is_opaque() {
+        let key = if operator == "RG" { "CA" } else { "ca" };
+        let opacity = format!("/{} {:.3}", key, color.opacity());
+        let index = match opacities.iter().position(|o| *o == opacity) {
+            Some(index) => index,
+            None => {
+                opacities.push(opacity);
+                opacities.len() - 1
+            }
+        };
+        writeln!(content, "/GS{} gs", index + 1).unwrap();
+    }
+    let (r, g, b) = color.rgb_f32();
+    writeln!(content, "{:.3} {:.3} {:.3} {}", r, g, b, operator).unwrap();
+}
+
+/// How a gradient written to the document is painted.
+enum GradientPaint {
+    /// A shading, by its object ID.
+    Shading(usize),
+    /// A pattern repeating the shading in every module, by its object ID.
+    Pattern(usize),
+}
+
+/// Writes the shading of a gradient, in its colors or in gray from its opacities when
+/// `alpha` is set, wrapped in a pattern with the given matrix if it repeats in every module.
+fn write_gradient(
+    writer: &mut PdfWriter,
+    gradient: &Gradient,
+    alpha: bool,
+    matrix: &str,
+) -> GradientPaint {
+    let sh_id = writer.start_obj();
+    writer.write(&utils::generate_pdf_ps_shading(gradient, alpha));
+    writer.write("\n");
+    writer.end_obj();
+
+    let Some(size) = gradient.tile else {
+        return GradientPaint::Shading(sh_id);
+    };
+    let paint = "/Sh1 sh\n";
+    let pattern_id = writer.start_obj();
+    writer.write(&format!(
+        "<< /Type /Pattern /PatternType 1 /PaintType 1 /TilingType 1 \
+         /BBox [0 0 {s:.4} {s:.4}] /XStep {s:.4} /YStep {s:.4} \
+         /Matrix {m} /Resources << /Shading << /Sh1 {sh} 0 R >> >> /Length {len} >>\n",
+        s = size,
+        m = matrix,
+        sh = sh_id,
+        len = paint.len()
+    ));
+    writer.write("stream\n");
+    writer.write(paint);
+    writer.write("endstream\n");
+    writer.end_obj();
+    GradientPaint::Pattern(pattern_id)
+}
+
+/// An icon ready to be drawn in the content stream.
+enum PdfIcon {
+    /// A raster image, by the object ID of its XObject.
+    Image(usize),
+    Vector(VectorIcon),
+}
+
+/// Appends the paths of a vector icon, in the icon's own coordinates.
+fn append_vector_icon(content: &mut String, icon: &VectorIcon) {
+    use std::fmt::Write as FmtWrite;
+
+    for path in &icon.paths {
+        let [a, b, c, d, e, f] = path.transform;
+        writeln!(content, "q").unwrap();
+        writeln!(content, "{} {} {} {} {} {} cm", a, b, c, d, e, f).unwrap();
+        append_path(content, &path.path);
+        if let Some((r, g, b)) = path.fill {
+            writeln!(content, "{} rg", rgb(r, g, b)).unwrap();
+        }
+        if let Some(stroke) = &path.stroke {
+            let (r, g, b) = stroke.color;
+            writeln!(content, "{} RG", rgb(r, g, b)).unwrap();
+            writeln!(
+                content,
+                "{} w {} J {} j {} M",
+                stroke.width, stroke.cap, stroke.join, stroke.miter_limit
+            )
+            .unwrap();
+            if let Some((dashes, offset)) = &stroke.dash {
+                let dashes: Vec<String> = dashes.iter().map(|d| d.to_string()).collect();
+                writeln!(content, "[{}] {} d", dashes.join(" "), offset).unwrap();
+            }
+        }
+        let even_odd = path.path.fill_rule == FillRule::EvenOdd;
+        let operator = match (path.fill, &path.stroke) {
+            (Some(_), None) => fill_operator(&path.path),
+            (None, Some(_)) => "S",
+            (Some(_), Some(_)) if even_odd => "B*",
+            (Some(_), Some(_)) => "B",
+            (None, None) => "n",
+        };
+        writeln!(content, "{}", operator).unwrap();
+        writeln!(content, "Q").unwrap();
+    }
+}
+
+/// Formats an RGB color as PDF color components.
+fn rgb(r: u8, g: u8, b: u8) -> String {
+    format!(
+        "{:.3} {:.3} {:.3}",
+        r as f32 / 255.0,
+        g as f32 / 255.0,
+        b as f32 / 255.0
+    )
 }
@@ -0,0 +1,36 @@
+use crate::core::renderer::geometry::{FillRule, Path, Segment};
+use std::fmt::Write;
+
+/// Appends the PDF path construction operators of a path.
+pub fn append_path(content: &mut String, path: &Path) {
+    for segment in path.cubic_segments() {
+        match segment {
+            Segment::MoveTo(x, y) => writeln!(content, "{:.4} {:.4} m", x, y),
+            Segment::LineTo(x, y) => writeln!(content, "{:.4} {:.4} l", x, y),
+            Segment::CubicTo(x1, y1, x2, y2, x, y) => writeln!(
+                content,
+                "{:.4} {:.4} {:.4} {:.4} {:.4} {:.4} c",
+                x1, y1, x2, y2, x, y
+            ),
+            Segment::Close => writeln!(content, "h"),
+            Segment::QuadTo(..) => unreachable!("quadratic curves are raised to cubic ones"),
+        }
+        .unwrap();
+    }
+}
+
+/// Returns the operator filling a path with its fill rule.
+pub fn fill_operator(path: &Path) -> &'static str {
+    match path.fill_rule {
+        FillRule::NonZero => "f",
+        FillRule::EvenOdd => "f*",
+    }
+}
+
+/// Returns the operators clipping to a path with its fill rule, ending the path.
+pub fn clip_operator(path: &Path) -> &'static str {
+    match path.fill_rule {
+        FillRule::NonZero => "W n",
+        FillRule::EvenOdd => "W* n",
+    }
+}
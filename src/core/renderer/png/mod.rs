@@ -1,12 +1,14 @@
-use crate::core::renderer::{QrGrid, QrRenderer, utils};
-use crate::models::{GradientDirection, OutputFormat, QrConfig, QrImage};
+use crate::core::renderer::geometry::{self, SymbolGeometry};
+use crate::core::renderer::gradient::{Fill, Gradient, GradientShape};
+use crate::core::renderer::icon::{ICON_BORDER_WIDTH, IconArea, IconLayout};
+use crate::core::renderer::{QrGrid, QrRenderer};
+use crate::models::{OutputFormat, QrConfig, QrImage};
 use tiny_skia::*;
 
-mod finder;
-mod module;
+mod path;
 
-use finder::draw_finder;
-use module::draw_module;
+use path::fill_path;
+pub(crate) use path::skia_path;
 
 /// Renders a QR code grid into a PNG image represented as a Pixmap.
 /// If failed, returns an error message as a String.
@@ -15,184 +17,225 @@ pub fn render_qr<G: QrGrid + ?Sized>(
     options: &QrConfig,
     pixel_size: f32,
 ) -> Result<Pixmap, String> {
-    let size = grid.size();
+    let width = grid.width();
+    let height = grid.height();
     let quiet_zone = options.quiet_zone as f32; // Unidades de modulo
-    let width_px = (size as f32 + quiet_zone * 2.0) * pixel_size;
+    let width_px = (width as f32 + quiet_zone * 2.0) * pixel_size;
+    let height_px = (height as f32 + quiet_zone * 2.0) * pixel_size;
 
     let mut pixmap =
-        Pixmap::new(width_px as u32, width_px as u32).ok_or("Error creating image buffer")?;
-    let bg_color = parse_color(&options.background)?;
-    pixmap.fill(bg_color);
+        Pixmap::new(width_px as u32, height_px as u32).ok_or("Error creating image buffer")?;
+    pixmap.fill(skia_color(options.background));
 
-    let mut paint = Paint::default();
-    paint.anti_alias = true;
-
-    let mut colors = Vec::new();
-    for color_hex in &options.foreground {
-        colors.push(parse_color(color_hex)?);
+    let geometry = SymbolGeometry::new(grid, options, pixel_size);
+    fill_with(
+        &mut pixmap,
+        &geometry.foreground,
+        &Fill::foreground(grid, options, pixel_size),
+    )?;
+    if !geometry.finders.is_empty() {
+        fill_with(
+            &mut pixmap,
+            &geometry.finders,
+            &Fill::finders(grid, options, pixel_size),
+        )?;
     }
-    if colors.len() > 1 {
-        let stops: Vec<GradientStop> = colors
-            .iter()
-            .enumerate()
-            .map(|(i, &color)| {
-                let pos = i as f32 / (colors.len() - 1) as f32;
-                GradientStop::new(pos, color)
-            })
-            .collect();
-
-        let shader = if options.gradient_direction == GradientDirection::Radial {
-            let center_x = width_px / 2.0;
-            let center_y = width_px / 2.0;
-            let qr_size_px = size as f32 * pixel_size;
-            let radius = qr_size_px / 2.0 * 1.414;
-            RadialGradient::new(
-                Point::from_xy(center_x, center_y),
-                Point::from_xy(center_x, center_y),
-                radius,
-                stops,
-                SpreadMode::Pad,
-                Transform::identity(),
-            )
-            .ok_or("Failed to create radial gradient")?
-        } else {
-            let (start_point, end_point) = match options.gradient_direction {
-                GradientDirection::TopToBottom => (
-                    Point::from_xy(width_px / 2.0, quiet_zone * pixel_size),
-                    Point::from_xy(width_px / 2.0, width_px - quiet_zone * pixel_size),
-                ),
-                GradientDirection::LeftToRight => (
-                    Point::from_xy(quiet_zone * pixel_size, width_px / 2.0),
-                    Point::from_xy(width_px - quiet_zone * pixel_size, width_px / 2.0),
-                ),
-                GradientDirection::TopLeftToBottomRight => (
-                    Point::from_xy(quiet_zone * pixel_size, quiet_zone * pixel_size),
-                    Point::from_xy(
-                        width_px - quiet_zone * pixel_size,
-                        width_px - quiet_zone * pixel_size,
-                    ),
-                ),
-                GradientDirection::BottomLeftToTopRight => (
-                    Point::from_xy(quiet_zone * pixel_size, width_px - quiet_zone * pixel_size),
-                    Point::from_xy(width_px - quiet_zone * pixel_size, quiet_zone * pixel_size),
-                ),
-                _ => unreachable!(),
-            };
-
-            LinearGradient::new(
-                start_point,
-                end_point,
-                stops,
-                SpreadMode::Pad,
-                Transform::identity(),
-            )
-            .ok_or("Failed to create linear gradient")?
-        };
-
-        paint.shader = shader;
-    } else {
-        paint.set_color(colors[0]);
+    let mut paint = Paint {
+        anti_alias: true,
+        ..Default::default()
+    };
+    for (color, path) in &geometry.colored {
+        paint.set_color(skia_color(*color));
+        fill_path(&mut pixmap, path, &paint);
     }
 
-    for y in 0..size {
-        for x in 0..size {
-            if grid.is_finder(x, y) {
-                continue;
-            }
-
-            if grid.is_dark(x, y) {
-                let px = (x as f32 + quiet_zone) * pixel_size;
-                let py = (y as f32 + quiet_zone) * pixel_size;
+    if let Some(image) = &options.image {
+        draw_icon(&mut pixmap, options, image, width, height, pixel_size)?;
+    } else if let Some(icon_path) = &options.icon {
+        let image = QrImage::load_from_path(icon_path)?;
+        draw_icon(&mut pixmap, options, &image, width, height, pixel_size)?;
+    }
 
-                let ctx = grid.module_context(x, y);
+    Ok(pixmap)
+}
 
-                let path = draw_module(options.shape, px, py, pixel_size, &ctx);
-                pixmap.fill_path(
-                    &path,
-                    &paint,
-                    FillRule::Winding,
-                    Transform::identity(),
-                    None,
-                );
+/// Fills a path with a color or gradient.
+fn fill_with(pixmap: &mut Pixmap, path: &geometry::Path, fill: &Fill) -> Result<(), String> {
+    // Tile a gradient spanning every module is drawn into, repeated by the paint
+    let tile: Pixmap;
+    let mut paint = Paint {
+        anti_alias: true,
+        ..Default::default()
+    };
+
+    match fill {
+        Fill::Solid(color) => paint.set_color(skia_color(*color)),
+        Fill::Gradient(gradient) => {
+            let shader = gradient_shader(gradient)?;
+            match gradient.tile {
+                None => paint.shader = shader,
+                Some(size) => {
+                    let side = size.ceil().max(1.0) as u32;
+                    let mut pixmap =
+                        Pixmap::new(side, side).ok_or("Error creating gradient tile")?;
+                    let rect = Rect::from_xywh(0.0, 0.0, size, size)
+                        .ok_or("Error creating gradient tile")?;
+                    let tile_paint = Paint {
+                        shader,
+                        ..Default::default()
+                    };
+                    let fit = side as f32 / size;
+                    pixmap.fill_rect(rect, &tile_paint, Transform::from_scale(fit, fit), None);
+                    tile = pixmap;
+                    paint.shader = Pattern::new(
+                        tile.as_ref(),
+                        SpreadMode::Repeat,
+                        FilterQuality::Nearest,
+                        1.0,
+                        Transform::from_scale(1.0 / fit, 1.0 / fit),
+                    );
+                }
             }
         }
     }
 
-    draw_finder(
-        &mut pixmap,
-        0.0,
-        0.0,
-        pixel_size,
-        quiet_zone,
-        options.finder,
-        &paint,
-    );
-    draw_finder(
-        &mut pixmap,
-        (size - 7) as f32,
-        0.0,
-        pixel_size,
-        quiet_zone,
-        options.finder,
-        &paint,
-    );
-    draw_finder(
-        &mut pixmap,
-        0.0,
-        (size - 7) as f32,
-        pixel_size,
-        quiet_zone,
-        options.finder,
-        &paint,
-    );
+    fill_path(pixmap, path, &paint);
+    Ok(())
+}
 
-    if let Some(image) = &options.image {
-        match image {
-            crate::models::QrImage::Raster(img) => {
-                draw_icon(&mut pixmap, options.ppm, img, size as f32, width_px)?;
-            }
-            crate::models::QrImage::Svg(_) => {
-                return Err(format!(
-                    "{} format does not support SVG icons.",
-                    options.format
-                ));
-            }
-        }
-    } else if let Some(icon_path) = &options.icon {
-        match QrImage::load_from_path(icon_path) {
-            Ok(img) => match img {
-                crate::models::QrImage::Raster(img) => {
-                    draw_icon(&mut pixmap, options.ppm, &img, size as f32, width_px)?;
-                }
-                crate::models::QrImage::Svg(_) => {
-                    return Err(format!(
-                        "{} format does not support SVG icons.",
-                        options.format
-                    ));
-                }
-            },
-            Err(e) => Err(e)?,
-        }
+/// Builds the shader painting a gradient.
+fn gradient_shader(gradient: &Gradient) -> Result<Shader<'static>, String> {
+    let stops = gradient
+        .stops
+        .iter()
+        .map(|stop| GradientStop::new(stop.offset, skia_color(stop.color)))
+        .collect();
+    match gradient.shape {
+        GradientShape::Linear { start, end } => LinearGradient::new(
+            Point::from_xy(start.0, start.1),
+            Point::from_xy(end.0, end.1),
+            stops,
+            SpreadMode::Pad,
+            Transform::identity(),
+        )
+        .ok_or_else(|| "Failed to create linear gradient".to_string()),
+        GradientShape::Radial { center, radius } => RadialGradient::new(
+            Point::from_xy(center.0, center.1),
+            Point::from_xy(center.0, center.1),
+            radius,
+            stops,
+            SpreadMode::Pad,
+            Transform::identity(),
+        )
+        .ok_or_else(|| "Failed to create radial gradient".to_string()),
     }
-
-    Ok(pixmap)
 }
 
-/// Parses a hex color string (e.g., "#RRGGBB" or "RRGGBB") into a Color.
-fn parse_color(hex: &str) -> Result<Color, String> {
-    let (r, g, b) = utils::parse_hex_color(hex).ok_or_else(|| format!("Invalid color: {}", hex))?;
-    Ok(Color::from_rgba8(r, g, b, 255))
+/// Converts a color of the configuration into a tiny-skia color.
+fn skia_color(color: crate::models::Color) -> Color {
+    Color::from_rgba8(color.r, color.g, color.b, color.a)
 }
 
-/// Draws an icon at the center of the QR code pixmap.
-/// The icon is scaled to fit within 20% of the QR code size ignoring quiet zones.
+/// Draws the icon where the layout puts it, clipped to its shape,
+/// over its plate and under its border if enabled.
+/// SVG icons are rasterised at the size they are drawn at.
 fn draw_icon(
     pixmap: &mut Pixmap,
-    ppm: u32,
-    img: &image::DynamicImage,
-    size: f32,
-    canvas_size: f32,
+    options: &QrConfig,
+    image: &QrImage,
+    grid_width: usize,
+    grid_height: usize,
+    pixel_size: f32,
 ) -> Result<(), String> {
+    let quiet_zone = options.quiet_zone as f32;
+    let aspect = image
+        .aspect_ratio()
+        .ok_or("Icon image dimensions could not be determined.")?;
+    let layout = IconLayout::new(grid_width, grid_height, aspect, options);
+
+    let mut paint = Paint {
+        anti_alias: true,
+        ..Default::default()
+    };
+
+    if options.icon_plate {
+        paint.set_color(skia_color(options.background));
+        pixmap.fill_path(
+            &area_path(&layout.frame, quiet_zone, pixel_size)?,
+            &paint,
+            FillRule::Winding,
+            Transform::identity(),
+            None,
+        );
+    }
+
+    let icon_pixmap = match image {
+        QrImage::Raster(img) => raster_icon(img)?,
+        QrImage::Svg(content) => svg_icon(
+            content,
+            layout.image.width * pixel_size,
+            layout.image.height * pixel_size,
+        )?,
+    };
+    let width = icon_pixmap.width() as f32;
+    let height = icon_pixmap.height() as f32;
+
+    let scale_x = layout.image.width * pixel_size / width;
+    let scale_y = layout.image.height * pixel_size / height;
+    let translate_x = (layout.image.x + quiet_zone) * pixel_size;
+    let translate_y = (layout.image.y + quiet_zone) * pixel_size;
+
+    let transform =
+        Transform::from_scale(scale_x, scale_y).post_translate(translate_x, translate_y);
+
+    let mask = if layout.clip == layout.image {
+        None
+    } else {
+        let mut mask =
+            Mask::new(pixmap.width(), pixmap.height()).ok_or("Could not create icon mask")?;
+        mask.fill_path(
+            &area_path(&layout.clip, quiet_zone, pixel_size)?,
+            FillRule::Winding,
+            true,
+            Transform::identity(),
+        );
+        Some(mask)
+    };
+
+    let pixmap_paint = PixmapPaint {
+        blend_mode: BlendMode::SourceOver,
+        ..Default::default()
+    };
+
+    pixmap.draw_pixmap(
+        0,
+        0,
+        icon_pixmap.as_ref(),
+        &pixmap_paint,
+        transform,
+        mask.as_ref(),
+    );
+
+    if let Some(border) = options.icon_border {
+        paint.set_color(skia_color(border));
+        let stroke = Stroke {
+            width: ICON_BORDER_WIDTH * pixel_size,
+            ..Default::default()
+        };
+        pixmap.stroke_path(
+            &area_path(&layout.border(), quiet_zone, pixel_size)?,
+            &paint,
+            &stroke,
+            Transform::identity(),
+            None,
+        );
+    }
+
+    Ok(())
+}
+
+/// Converts a raster icon into a pixmap, premultiplying its alpha for tiny-skia.
+fn raster_icon(img: &image::DynamicImage) -> Result<Pixmap, String> {
     let mut img_source = img.to_rgba8();
 
     // Premultiply alpha for tiny-skia
@@ -203,28 +246,35 @@ fn draw_icon(
         pixel[2] = (pixel[2] as f32 * alpha + 0.5) as u8;
     }
 
-    let width = img_source.width() as f32;
-    let height = img_source.height() as f32;
-
-    let icon_pixmap = Pixmap::from_vec(
+    let (width, height) = img_source.dimensions();
+    Pixmap::from_vec(
         img_source.into_raw(),
-        tiny_skia::IntSize::from_wh(width as u32, height as u32).unwrap(),
+        tiny_skia::IntSize::from_wh(width, height).ok_or("Icon image is empty")?,
     )
-    .ok_or("Could not create pixmap from icon image")?;
-
-    let target_icon_size = size * 0.25 * ppm as f32;
-    let scale = target_icon_size / width.max(height);
-    let translate_x = (canvas_size - (width * scale)) / 2.0;
-    let translate_y = (canvas_size - (height * scale)) / 2.0;
-
-    let transform = Transform::from_scale(scale, scale).post_translate(translate_x, translate_y);
-
-    let mut paint = PixmapPaint::default();
-    paint.blend_mode = BlendMode::SourceOver;
+    .ok_or_else(|| "Could not create pixmap from icon image".to_string())
+}
 
-    pixmap.draw_pixmap(0, 0, icon_pixmap.as_ref(), &paint, transform, None);
+/// Rasterises an SVG icon at the given size in pixels.
+fn svg_icon(content: &str, width: f32, height: f32) -> Result<Pixmap, String> {
+    let tree = resvg::usvg::Tree::from_str(content, &resvg::usvg::Options::default())
+        .map_err(|e| format!("Invalid SVG icon: {}", e))?;
+    let mut pixmap = Pixmap::new(width.ceil().max(1.0) as u32, height.ceil().max(1.0) as u32)
+        .ok_or("Could not create pixmap for the SVG icon")?;
+    let size = tree.size();
+    resvg::render(
+        &tree,
+        Transform::from_scale(
+            pixmap.width() as f32 / size.width(),
+            pixmap.height() as f32 / size.height(),
+        ),
+        &mut pixmap.as_mut(),
+    );
+    Ok(pixmap)
+}
 
-    Ok(())
+/// Builds the path of an icon area, in pixels.
+fn area_path(area: &IconArea, quiet_zone: f32, scale: f32) -> Result<Path, String> {
+    skia_path(&area.path(quiet_zone, scale)).ok_or_else(|| "Invalid icon shape".to_string())
 }
 
 /// Saves the rendered Pixmap to a file.
@@ -271,9 +321,7 @@ pub fn save_image(pixmap: &Pixmap, path: &str, format: OutputFormat) -> Result<S
 
         // Handle formats that don't support alpha or need specific conversion
         let output_image = match format {
-            OutputFormat::Jpg | OutputFormat::Jpeg | OutputFormat::Bmp => {
-                image::DynamicImage::ImageRgb8(dynamic_image.into_rgb8())
-            }
+            OutputFormat::Jpg | OutputFormat::Jpeg | OutputFormat::Bmp => flatten(&dynamic_image),
             _ => dynamic_image,
         };
 
@@ -295,6 +343,17 @@ pub fn save_image(pixmap: &Pixmap, path: &str, format: OutputFormat) -> Result<S
     Ok(final_path)
 }
 
+/// Blends an image over white, for formats without an alpha channel.
+fn flatten(image: &image::DynamicImage) -> image::DynamicImage {
+    let rgba = image.to_rgba8();
+    let rgb = image::RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, a] = rgba.get_pixel(x, y).0;
+        let color = crate::models::Color::rgba(r, g, b, a).over(crate::models::Color::WHITE);
+        image::Rgb([color.r, color.g, color.b])
+    });
+    image::DynamicImage::ImageRgb8(rgb)
+}
+
 pub struct PngRenderer {
     pixmap: Pixmap,
     format: OutputFormat,
@@ -343,7 +402,7 @@ impl QrRenderer for PngRenderer {
             // Handle formats that don't support alpha or need specific conversion
             let output_image = match self.format {
                 OutputFormat::Jpg | OutputFormat::Jpeg | OutputFormat::Bmp => {
-                    image::DynamicImage::ImageRgb8(dynamic_image.into_rgb8())
+                    flatten(&dynamic_image)
                 }
                 _ => dynamic_image,
             };
@@ -0,0 +1,37 @@
+use crate::core::renderer::geometry::{FillRule, Path, Segment};
+use tiny_skia::{Paint, PathBuilder, Pixmap, Transform};
+
+/// Converts a path into a tiny-skia path, or returns `None` if it is empty.
+pub(crate) fn skia_path(path: &Path) -> Option<tiny_skia::Path> {
+    let mut pb = PathBuilder::new();
+    for segment in &path.segments {
+        match *segment {
+            Segment::MoveTo(x, y) => pb.move_to(x, y),
+            Segment::LineTo(x, y) => pb.line_to(x, y),
+            Segment::QuadTo(x1, y1, x, y) => pb.quad_to(x1, y1, x, y),
+            Segment::CubicTo(x1, y1, x2, y2, x, y) => pb.cubic_to(x1, y1, x2, y2, x, y),
+            Segment::Close => pb.close(),
+        }
+    }
+    pb.finish()
+}
+
+pub(crate) fn skia_fill_rule(rule: FillRule) -> tiny_skia::FillRule {
+    match rule {
+        FillRule::NonZero => tiny_skia::FillRule::Winding,
+        FillRule::EvenOdd => tiny_skia::FillRule::EvenOdd,
+    }
+}
+
+/// Fills a path on the pixmap with the given paint.
+pub fn fill_path(pixmap: &mut Pixmap, path: &Path, paint: &Paint) {
+    if let Some(skia) = skia_path(path) {
+        pixmap.fill_path(
+            &skia,
+            paint,
+            skia_fill_rule(path.fill_rule),
+            Transform::identity(),
+            None,
+        );
+    }
+}
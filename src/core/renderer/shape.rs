@@ -0,0 +1,131 @@
+use crate::core::renderer::ModuleContext;
+use crate::core::renderer::finder::FinderOutlines;
+use crate::core::renderer::geometry::Path;
+use crate::models::{EyeShape, FinderShape, ModuleShape};
+use std::fmt;
+
+/// Draws dark modules. Implement it to give modules a shape of your own,
+/// and set it as `QrConfig::module_provider`.
+pub trait ModuleShapeProvider: fmt::Debug + Send + Sync {
+    /// Returns the path of a dark module in the square cell at `x`, `y` with sides of
+    /// `size` pixels. The context tells which of its neighbours are dark, for shapes
+    /// that join up with them. The path is filled together with the other modules with
+    /// the non-zero rule: holes have to run the opposite way to the outline around them.
+    fn module_path(&self, x: f32, y: f32, size: f32, ctx: &ModuleContext) -> Path;
+}
+
+/// Draws finder patterns. Implement it to give finder patterns a shape of your own,
+/// and set it as `QrConfig::finder_provider`.
+pub trait FinderShapeProvider: fmt::Debug + Send + Sync {
+    /// Returns the path of the outer ring of a finder pattern in the square at `x`, `y`
+    /// with sides of `size` pixels, `module` pixels being one module. The light ring
+    /// inside it has to be left out of the path as a hole running the opposite way to
+    /// the outline, since finder patterns are filled with the non-zero rule.
+    fn ring_path(&self, x: f32, y: f32, size: f32, module: f32) -> Path;
+
+    /// Returns the path of the eye of a finder pattern in the same square as the ring,
+    /// filled with the non-zero rule as well.
+    fn eye_path(&self, x: f32, y: f32, size: f32, module: f32) -> Path;
+}
+
+impl ModuleShapeProvider for ModuleShape {
+    fn module_path(&self, x: f32, y: f32, size: f32, ctx: &ModuleContext) -> Path {
+        let mut path = Path::new();
+        match self {
+            ModuleShape::Square => path.push_rect(x, y, size, size),
+            ModuleShape::Dots => {
+                path.push_circle(x + size / 2.0, y + size / 2.0, size * 0.45, true);
+            }
+            ModuleShape::Gapped => {
+                let gap = size * 0.05;
+                let s = size - gap * 2.0;
+                path.push_rounded_rect(x + gap, y + gap, s, s, [size * 0.1; 4]);
+            }
+            ModuleShape::Diamond => {
+                path.move_to(x + size / 2.0, y);
+                path.line_to(x + size, y + size / 2.0);
+                path.line_to(x + size / 2.0, y + size);
+                path.line_to(x, y + size / 2.0);
+                path.close();
+            }
+            ModuleShape::HorizontalBars => {
+                // Bars run on into the modules next to them, and have round ends elsewhere
+                let height = size * 0.6;
+                let r = height / 2.0;
+                let left = if ctx.left { 0.0 } else { r };
+                let right = if ctx.right { 0.0 } else { r };
+                let y = y + (size - height) / 2.0;
+                path.push_rounded_rect(x, y, size, height, [left, right, right, left]);
+            }
+            ModuleShape::VerticalBars => {
+                let width = size * 0.6;
+                let r = width / 2.0;
+                let top = if ctx.top { 0.0 } else { r };
+                let bottom = if ctx.bottom { 0.0 } else { r };
+                let x = x + (size - width) / 2.0;
+                path.push_rounded_rect(x, y, width, size, [top, top, bottom, bottom]);
+            }
+            ModuleShape::Heart => {
+                let s = size;
+                let s_half = s / 2.0;
+                path.move_to(x + s_half, y + s * 0.3);
+                path.cubic_to(x + s_half, y, x + s * 0.95, y, x + s * 0.95, y + s * 0.3);
+                path.cubic_to(
+                    x + s * 0.95,
+                    y + s * 0.6,
+                    x + s * 0.65,
+                    y + s * 0.9,
+                    x + s_half,
+                    y + s,
+                );
+                path.cubic_to(
+                    x + s * 0.35,
+                    y + s * 0.9,
+                    x + s * 0.05,
+                    y + s * 0.6,
+                    x + s * 0.05,
+                    y + s * 0.3,
+                );
+                path.cubic_to(x + s * 0.05, y, x + s_half, y, x + s_half, y + s * 0.3);
+                path.close();
+            }
+            ModuleShape::Liquid => {
+                let r = size / 2.0;
+                path.push_outline(&ctx.liquid_outline(x, y, size, r), true);
+                for fillet in ctx.liquid_fillets(x, y, size, r) {
+                    path.move_to(fillet.at.0, fillet.at.1);
+                    path.corner_to(fillet.from, fillet.at, fillet.to);
+                    path.close();
+                }
+            }
+        }
+        path
+    }
+}
+
+/// A finder pattern of the built-in shapes, for the ring and for the eye.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BuiltinFinder {
+    pub shape: FinderShape,
+    pub eye: EyeShape,
+}
+
+impl BuiltinFinder {
+    fn outlines(&self, x: f32, y: f32, size: f32, module: f32) -> FinderOutlines {
+        FinderOutlines::new(self.shape, self.eye, x, y, size / module, module)
+    }
+}
+
+impl FinderShapeProvider for BuiltinFinder {
+    fn ring_path(&self, x: f32, y: f32, size: f32, module: f32) -> Path {
+        let mut path = Path::new();
+        path.push_finder_ring(&self.outlines(x, y, size, module));
+        path
+    }
+
+    fn eye_path(&self, x: f32, y: f32, size: f32, module: f32) -> Path {
+        let mut path = Path::new();
+        path.push_finder_eye(&self.outlines(x, y, size, module));
+        path
+    }
+}
@@ -1,12 +1,13 @@
+use crate::core::renderer::geometry::SymbolGeometry;
+use crate::core::renderer::gradient::{Fill, Gradient, GradientShape};
+use crate::core::renderer::icon::{ICON_BORDER_WIDTH, IconArea, IconLayout};
 use crate::core::renderer::{QrGrid, QrRenderer};
-use crate::models::{GradientDirection, QrConfig, QrImage};
+use crate::models::{Color, QrConfig, QrImage};
 use std::fmt::Write;
 
-mod finder;
-mod module;
+mod path;
 
-use finder::append_finder_path;
-use module::append_module_path;
+use path::{append_path_data, fill_rule_attr};
 
 /// Renders a QR code grid into an SVG String.
 pub fn render_svg<G: QrGrid + ?Sized>(
@@ -14,147 +15,80 @@ pub fn render_svg<G: QrGrid + ?Sized>(
     options: &QrConfig,
     pixel_size: f32,
 ) -> Result<String, String> {
-    let size = grid.size();
+    let width = grid.width();
+    let height = grid.height();
     let quiet_zone = options.quiet_zone as f32;
-    let width_px = (size as f32 + quiet_zone * 2.0) * pixel_size;
-
-    let sanitize_color = |color: &str| -> String {
-        if !color.starts_with('#') && color.chars().all(|c| c.is_ascii_hexdigit()) {
-            format!("#{}", color)
-        } else {
-            color.to_string()
-        }
-    };
+    let width_px = (width as f32 + quiet_zone * 2.0) * pixel_size;
+    let height_px = (height as f32 + quiet_zone * 2.0) * pixel_size;
 
     let mut svg = String::new();
 
     // SVG Header
-    writeln!(&mut svg, r#"<svg viewBox="0 0 {w} {h}" width="{w}" height="{h}" xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">"#, w=width_px, h=width_px).unwrap();
+    writeln!(&mut svg, r#"<svg viewBox="0 0 {w} {h}" width="{w}" height="{h}" xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">"#, w=width_px, h=height_px).unwrap();
 
-    // Background
-    writeln!(
-        &mut svg,
-        r#"<rect width="100%" height="100%" fill="{}" />"#,
-        sanitize_color(&options.background)
-    )
-    .unwrap();
+    // Background, left out to keep the image transparent
+    if !options.background.is_transparent() {
+        writeln!(
+            &mut svg,
+            r#"<rect width="100%" height="100%" {} />"#,
+            paint_attrs("fill", options.background)
+        )
+        .unwrap();
+    }
 
     // Definitions for Gradients
-    let fill_id = "qr-fill";
-    let mut fill_attr =
-        sanitize_color(options.foreground.first().unwrap_or(&"#000000".to_string()));
-
-    if options.foreground.len() > 1 {
-        fill_attr = format!("url(#{})", fill_id);
-
-        writeln!(&mut svg, "<defs>").unwrap();
-
-        if options.gradient_direction == GradientDirection::Radial {
-            writeln!(
-                &mut svg,
-                r#"<radialGradient id="{}" cx="50%" cy="50%" r="70.7%" fx="50%" fy="50%">"#,
-                fill_id
-            )
-            .unwrap();
-        } else {
-            let (x1, y1, x2, y2) = match options.gradient_direction {
-                GradientDirection::TopToBottom => ("50%", "0%", "50%", "100%"),
-                GradientDirection::LeftToRight => ("0%", "50%", "100%", "50%"),
-                GradientDirection::TopLeftToBottomRight => ("0%", "0%", "100%", "100%"),
-                GradientDirection::BottomLeftToTopRight => ("0%", "100%", "100%", "0%"),
-                _ => ("0%", "0%", "100%", "100%"),
-            };
-            writeln!(
-                &mut svg,
-                r#"<linearGradient id="{}" x1="{}" y1="{}" x2="{}" y2="{}">"#,
-                fill_id, x1, y1, x2, y2
-            )
-            .unwrap();
-        }
-
-        for (i, color) in options.foreground.iter().enumerate() {
-            let offset = (i as f32 / (options.foreground.len() - 1) as f32) * 100.0;
-            writeln!(
-                &mut svg,
-                r#"<stop offset="{}%" stop-color="{}" />"#,
-                offset,
-                sanitize_color(color)
-            )
-            .unwrap();
-        }
-
-        if options.gradient_direction == GradientDirection::Radial {
-            writeln!(&mut svg, "</radialGradient>").unwrap();
-        } else {
-            writeln!(&mut svg, "</linearGradient>").unwrap();
-        }
-        writeln!(&mut svg, "</defs>").unwrap();
+    let geometry = SymbolGeometry::new(grid, options, pixel_size);
+    let mut fills = vec![(Fill::foreground(grid, options, pixel_size), "qr-fill")];
+    if !geometry.finders.is_empty() {
+        fills.push((Fill::finders(grid, options, pixel_size), "qr-finder-fill"));
     }
-
-    // Draw Modules
-    let mut path_data = String::new();
-
-    for y in 0..size {
-        for x in 0..size {
-            if grid.is_finder(x, y) {
-                continue;
+    let mut fill_attrs = Vec::new();
+    let mut defs = String::new();
+    for (fill, id) in &fills {
+        fill_attrs.push(match fill {
+            Fill::Solid(color) => paint_attrs("fill", *color),
+            Fill::Gradient(gradient) => {
+                format!(r#"fill="{}""#, append_gradient(&mut defs, gradient, id))
             }
-
-            if grid.is_dark(x, y) {
-                let px = (x as f32 + quiet_zone) * pixel_size;
-                let py = (y as f32 + quiet_zone) * pixel_size;
-
-                let ctx = grid.module_context(x, y);
-
-                append_module_path(&mut path_data, options.shape, px, py, pixel_size, &ctx);
-            }
-        }
+        });
+    }
+    if !defs.is_empty() {
+        writeln!(&mut svg, "<defs>\n{}</defs>", defs).unwrap();
     }
 
-    // Draw Finders
-    // Top Left
-    append_finder_path(
-        &mut path_data,
-        options.finder,
-        0.0,
-        0.0,
-        pixel_size,
-        quiet_zone,
+    // Draw Modules and finders, with the finder parts that have colors of their own on top
+    let mut paths: Vec<_> = fill_attrs
+        .into_iter()
+        .zip([&geometry.foreground, &geometry.finders])
+        .collect();
+    paths.extend(
+        geometry
+            .colored
+            .iter()
+            .map(|(color, path)| (paint_attrs("fill", *color), path)),
     );
-    // Top Right
-    append_finder_path(
-        &mut path_data,
-        options.finder,
-        (size - 7) as f32,
-        0.0,
-        pixel_size,
-        quiet_zone,
-    );
-    // Bottom Left
-    append_finder_path(
-        &mut path_data,
-        options.finder,
-        0.0,
-        (size - 7) as f32,
-        pixel_size,
-        quiet_zone,
-    );
-
-    if !path_data.is_empty() {
+    for (fill, path) in paths {
+        if path.is_empty() {
+            continue;
+        }
+        let mut data = String::new();
+        append_path_data(&mut data, path);
         writeln!(
             &mut svg,
-            r#"<path fill="{}" d="{}" />"#,
-            fill_attr, path_data
+            r#"<path {}{} d="{}" />"#,
+            fill,
+            fill_rule_attr(path),
+            data
         )
         .unwrap();
     }
 
     if let Some(image) = &options.image {
-        append_icon(&mut svg, image, size, pixel_size, width_px)?;
+        append_icon(&mut svg, image, options, width, height, pixel_size)?;
     } else if let Some(icon_path) = &options.icon {
         match QrImage::load_from_path(icon_path) {
             Ok(image) => {
-                append_icon(&mut svg, &image, size, pixel_size, width_px)?;
+                append_icon(&mut svg, &image, options, width, height, pixel_size)?;
             }
             Err(e) => {
                 return Err(format!("Failed to load icon image: {}", e));
@@ -167,15 +101,80 @@ pub fn render_svg<G: QrGrid + ?Sized>(
     Ok(svg)
 }
 
+/// Appends the definition of a gradient with the given ID, in user space so that it is laid
+/// out the same as by the other backends. Gradients spanning every module are wrapped in
+/// a pattern repeating them. Returns the fill attribute painting with it.
+fn append_gradient(svg: &mut String, gradient: &Gradient, id: &str) -> String {
+    let gradient_id = match gradient.tile {
+        Some(_) => format!("{}-gradient", id),
+        None => id.to_string(),
+    };
+    let element = match gradient.shape {
+        GradientShape::Linear { start, end } => {
+            writeln!(
+                svg,
+                r#"<linearGradient id="{}" gradientUnits="userSpaceOnUse" x1="{}" y1="{}" x2="{}" y2="{}">"#,
+                gradient_id, start.0, start.1, end.0, end.1
+            )
+            .unwrap();
+            "linearGradient"
+        }
+        GradientShape::Radial { center, radius } => {
+            writeln!(
+                svg,
+                r#"<radialGradient id="{}" gradientUnits="userSpaceOnUse" cx="{}" cy="{}" r="{}">"#,
+                gradient_id, center.0, center.1, radius
+            )
+            .unwrap();
+            "radialGradient"
+        }
+    };
+    for stop in &gradient.stops {
+        writeln!(
+            svg,
+            r#"<stop offset="{}" {} />"#,
+            stop.offset,
+            paint_attrs("stop-color", stop.color)
+        )
+        .unwrap();
+    }
+    writeln!(svg, "</{}>", element).unwrap();
+
+    if let Some(size) = gradient.tile {
+        writeln!(
+            svg,
+            r#"<pattern id="{}" patternUnits="userSpaceOnUse" width="{s}" height="{s}"><rect width="{s}" height="{s}" fill="url(#{})" /></pattern>"#,
+            id,
+            gradient_id,
+            s = size
+        )
+        .unwrap();
+    }
+    format!("url(#{})", id)
+}
+
+/// Attributes painting with a color, setting the given property ("fill", "stroke" or
+/// "stop-color") and its opacity if the color can be seen through.
+fn paint_attrs(property: &str, color: Color) -> String {
+    let mut attrs = format!(r#"{}="{}""#, property, color.to_rgb_hex());
+    if !color.is_opaque() {
+        let opacity = property.replace("-color", "");
+        write!(attrs, r#" {}-opacity="{}""#, opacity, color.opacity()).unwrap();
+    }
+    attrs
+}
+
+/// Appends the icon where the layout puts it, clipped to its shape,
+/// over its plate and under its border if enabled.
 fn append_icon(
     svg: &mut String,
     image: &crate::models::QrImage,
-    size: usize,
+    options: &QrConfig,
+    grid_width: usize,
+    grid_height: usize,
     pixel_size: f32,
-    width_px: f32,
 ) -> Result<(), String> {
-    let mut width: Option<u32> = None;
-    let mut height: Option<u32> = None;
+    let quiet_zone = options.quiet_zone as f32;
     let mime_type;
     let mut encoded_data = String::new();
 
@@ -183,61 +182,8 @@ fn append_icon(
         crate::models::QrImage::Svg(content) => {
             mime_type = "image/svg+xml";
             encoded_data = encode_base64(content.as_bytes());
-
-            // Simple manual parsing for width/height/viewBox
-            if let Some(start) = content.find("<svg") {
-                let end = content[start..].find('>').unwrap_or(content.len()) + start;
-                let tag = &content[start..end];
-
-                let parse_attr = |attr: &str| -> Option<u32> {
-                    if let Some(pos) = tag.find(attr) {
-                        let rest = &tag[pos + attr.len()..];
-                        if let Some(quote_start) = rest.find('"') {
-                            let rest = &rest[quote_start + 1..];
-                            if let Some(quote_end) = rest.find('"') {
-                                let val_str = &rest[..quote_end];
-                                let num_str: String = val_str
-                                    .chars()
-                                    .filter(|c| c.is_numeric() || *c == '.')
-                                    .collect();
-                                return num_str.parse::<f32>().ok().map(|v| v as u32);
-                            }
-                        }
-                    }
-                    None
-                };
-
-                width = parse_attr("width=");
-                height = parse_attr("height=");
-
-                if width.is_none() || height.is_none() {
-                    if let Some(pos) = tag.find("viewBox=") {
-                        let rest = &tag[pos + 8..];
-                        if let Some(quote_start) = rest.find('"') {
-                            let rest = &rest[quote_start + 1..];
-                            if let Some(quote_end) = rest.find('"') {
-                                let val_str = &rest[..quote_end];
-                                let parts: Vec<f32> = val_str
-                                    .split_whitespace()
-                                    .filter_map(|s| s.parse().ok())
-                                    .collect();
-                                if parts.len() == 4 {
-                                    if width.is_none() {
-                                        width = Some(parts[2] as u32);
-                                    }
-                                    if height.is_none() {
-                                        height = Some(parts[3] as u32);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
         }
         crate::models::QrImage::Raster(img) => {
-            width = Some(img.width());
-            height = Some(img.height());
             mime_type = "image/png";
 
             let mut buffer = Vec::new();
@@ -253,32 +199,74 @@ fn append_icon(
         }
     }
 
-    if let (Some(w_px), Some(h_px)) = (width, height) {
-        if encoded_data.is_empty() {
-            return Err(format!("Failed to encode icon image data."))?;
-        }
+    if encoded_data.is_empty() {
+        return Err("Failed to encode icon image data.".to_string());
+    }
+    let aspect = image
+        .aspect_ratio()
+        .ok_or("Icon image dimensions could not be determined.")?;
+    let layout = IconLayout::new(grid_width, grid_height, aspect, options);
 
-        let icon_size = size as f32 * 0.25 * pixel_size;
-        let scale = icon_size / (w_px.max(h_px) as f32);
-        let w = w_px as f32 * scale;
-        let h = h_px as f32 * scale;
-        let x = (width_px - w) / 2.0;
-        let y = (width_px - h) / 2.0;
+    if options.icon_plate {
+        writeln!(
+            svg,
+            r#"<rect {} {} />"#,
+            area_attrs(&layout.frame, quiet_zone, pixel_size),
+            paint_attrs("fill", options.background)
+        )
+        .unwrap();
+    }
 
-        let href = format!("data:{};base64,{}", mime_type, encoded_data);
+    let mut clip_attr = String::new();
+    if layout.clip != layout.image {
+        writeln!(
+            svg,
+            r#"<clipPath id="icon-clip"><rect {} /></clipPath>"#,
+            area_attrs(&layout.clip, quiet_zone, pixel_size)
+        )
+        .unwrap();
+        clip_attr = r#" clip-path="url(#icon-clip)""#.to_string();
+    }
 
+    let href = format!("data:{};base64,{}", mime_type, encoded_data);
+
+    writeln!(
+        svg,
+        r#"<image x="{}" y="{}" width="{}" height="{}" preserveAspectRatio="none"{} href="{}" />"#,
+        (layout.image.x + quiet_zone) * pixel_size,
+        (layout.image.y + quiet_zone) * pixel_size,
+        layout.image.width * pixel_size,
+        layout.image.height * pixel_size,
+        clip_attr,
+        href
+    )
+    .unwrap();
+
+    if let Some(border) = options.icon_border {
         writeln!(
             svg,
-            r#"<image x="{}" y="{}" width="{}" height="{}" href="{}" />"#,
-            x, y, w, h, href
+            r#"<rect {} fill="none" {} stroke-width="{}" />"#,
+            area_attrs(&layout.border(), quiet_zone, pixel_size),
+            paint_attrs("stroke", border),
+            ICON_BORDER_WIDTH * pixel_size
         )
         .unwrap();
-    } else {
-        return Err(format!("Icon image dimensions could not be determined."))?;
     }
     Ok(())
 }
 
+/// Position, size and corner radius attributes of a `<rect>` covering an icon area.
+fn area_attrs(area: &IconArea, quiet_zone: f32, pixel_size: f32) -> String {
+    format!(
+        r#"x="{}" y="{}" width="{}" height="{}" rx="{}""#,
+        (area.x + quiet_zone) * pixel_size,
+        (area.y + quiet_zone) * pixel_size,
+        area.width * pixel_size,
+        area.height * pixel_size,
+        area.radius * pixel_size
+    )
+}
+
 fn encode_base64(input: &[u8]) -> String {
     const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
     let mut output = String::with_capacity((input.len() * 4 / 3) + 4);
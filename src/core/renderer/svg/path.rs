@@ -0,0 +1,25 @@
+use crate::core::renderer::geometry::{FillRule, Path, Segment};
+use std::fmt::Write;
+
+/// Appends the SVG path data of a path.
+pub fn append_path_data(data: &mut String, path: &Path) {
+    for segment in &path.segments {
+        let _ = match *segment {
+            Segment::MoveTo(x, y) => write!(data, "M{} {} ", x, y),
+            Segment::LineTo(x, y) => write!(data, "L{} {} ", x, y),
+            Segment::QuadTo(x1, y1, x, y) => write!(data, "Q{} {} {} {} ", x1, y1, x, y),
+            Segment::CubicTo(x1, y1, x2, y2, x, y) => {
+                write!(data, "C{} {} {} {} {} {} ", x1, y1, x2, y2, x, y)
+            }
+            Segment::Close => write!(data, "Z "),
+        };
+    }
+}
+
+/// Returns the attribute selecting the fill rule of a path, or nothing for the default one.
+pub fn fill_rule_attr(path: &Path) -> &'static str {
+    match path.fill_rule {
+        FillRule::NonZero => "",
+        FillRule::EvenOdd => r#" fill-rule="evenodd""#,
+    }
+}
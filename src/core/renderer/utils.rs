@@ -1,21 +1,4 @@
-use crate::models::GradientDirection;
-
-pub fn parse_hex_color(hex: &str) -> Option<(u8, u8, u8)> {
-    let hex = hex.trim_start_matches('#');
-    if hex.len() == 6 {
-        let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
-        let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
-        let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
-        Some((r, g, b))
-    } else if hex.len() == 3 {
-        let r = u8::from_str_radix(&hex[0..1], 16).ok()?;
-        let g = u8::from_str_radix(&hex[1..2], 16).ok()?;
-        let b = u8::from_str_radix(&hex[2..3], 16).ok()?;
-        Some((r * 17, g * 17, b * 17))
-    } else {
-        None
-    }
-}
+use crate::core::renderer::gradient::{Gradient, GradientShape};
 
 pub fn is_svg_buffer(buffer: &[u8]) -> bool {
     if let Ok(s) = std::str::from_utf8(buffer) {
@@ -37,33 +20,68 @@ pub fn is_svg_file(path: &str) -> bool {
     false
 }
 
-pub fn get_gradient_coords(
-    direction: GradientDirection,
-    width: f32,
-    height: f32,
-) -> (f32, f32, f32, f32, f32, f32) {
-    // Returns (x0, y0, r0, x1, y1, r1)
-    match direction {
-        GradientDirection::TopToBottom => (0.0, 0.0, 0.0, 0.0, height, 0.0),
-        GradientDirection::LeftToRight => (0.0, 0.0, 0.0, width, 0.0, 0.0),
-        GradientDirection::TopLeftToBottomRight => (0.0, 0.0, 0.0, width, height, 0.0),
-        GradientDirection::BottomLeftToTopRight => (0.0, height, 0.0, width, 0.0, 0.0),
-        GradientDirection::Radial => (
-            width / 2.0,
-            height / 2.0,
-            0.0,
-            width / 2.0,
-            height / 2.0,
-            width / 2.0 * 1.414,
+/// Builds a shading dictionary drawing the gradient, in the syntax PDF and PostScript share.
+/// It blends the RGB colors of the stops, or their opacities in gray when `alpha` is set,
+/// for masks of gradients that can be seen through.
+pub fn generate_pdf_ps_shading(gradient: &Gradient, alpha: bool) -> String {
+    let (shading_type, coords) = match gradient.shape {
+        GradientShape::Linear { start, end } => (
+            2,
+            format!("{:.3} {:.3} {:.3} {:.3}", start.0, start.1, end.0, end.1),
         ),
-    }
+        GradientShape::Radial { center, radius } => (
+            3,
+            format!(
+                "{:.3} {:.3} 0 {:.3} {:.3} {:.3}",
+                center.0, center.1, center.0, center.1, radius
+            ),
+        ),
+    };
+    let stops: Vec<(f32, Vec<f32>)> = gradient
+        .stops
+        .iter()
+        .map(|stop| {
+            let components = if alpha {
+                vec![stop.color.opacity()]
+            } else {
+                let (r, g, b) = stop.color.rgb_f32();
+                vec![r, g, b]
+            };
+            (stop.offset, components)
+        })
+        .collect();
+    format!(
+        "<< /ShadingType {} /ColorSpace /{} /Coords [ {} ] /Function {} /Extend [ true true ] >>",
+        shading_type,
+        if alpha { "DeviceGray" } else { "DeviceRGB" },
+        coords,
+        generate_pdf_ps_gradient_function(&stops)
+    )
 }
 
-pub fn generate_pdf_ps_gradient_function(colors: &[(f32, f32, f32)]) -> String {
-    let n = colors.len();
-    if n < 2 {
-        return String::new();
+/// Builds a function blending the color components of the given stops,
+/// by their positions from 0 to 1.
+pub fn generate_pdf_ps_gradient_function(stops: &[(f32, Vec<f32>)]) -> String {
+    // The function has to cover the whole domain, so the end colors are carried on to the ends
+    let mut stops = stops.to_vec();
+    match (stops.first().cloned(), stops.last().cloned()) {
+        (Some(first), Some(last)) => {
+            if first.0 > 0.0 {
+                stops.insert(0, (0.0, first.1));
+            }
+            if last.0 < 1.0 {
+                stops.push((1.0, last.1));
+            }
+        }
+        _ => return String::new(),
     }
+    let n = stops.len();
+    let components = |c: &[f32]| {
+        c.iter()
+            .map(|v| format!("{:.3}", v))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
 
     let mut s = String::new();
 
@@ -73,18 +91,18 @@ pub fn generate_pdf_ps_gradient_function(colors: &[(f32, f32, f32)]) -> String {
     // Functions array
     s.push_str("/Functions [");
     for i in 0..n - 1 {
-        let c0 = colors[i];
-        let c1 = colors[i + 1];
-        s.push_str(&format!(" << /FunctionType 2 /Domain [0 1] /C0 [{:.3} {:.3} {:.3}] /C1 [{:.3} {:.3} {:.3}] /N 1 >>",
-            c0.0, c0.1, c0.2, c1.0, c1.1, c1.2));
+        s.push_str(&format!(
+            " << /FunctionType 2 /Domain [0 1] /C0 [{}] /C1 [{}] /N 1 >>",
+            components(&stops[i].1),
+            components(&stops[i + 1].1)
+        ));
     }
     s.push_str(" ] ");
 
     // Bounds array
     s.push_str("/Bounds [");
-    for i in 1..n - 1 {
-        let val = i as f32 / (n - 1) as f32;
-        s.push_str(&format!(" {:.3}", val));
+    for stop in &stops[1..n - 1] {
+        s.push_str(&format!(" {:.3}", stop.0));
     }
     s.push_str(" ] ");
 
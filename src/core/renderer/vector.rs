@@ -0,0 +1,178 @@
+use crate::core::renderer::geometry::{FillRule, Path};
+use resvg::usvg::{self, Node, Paint, PaintOrder, Tree};
+use tiny_skia::PathSegment;
+
+/// How a vector path is stroked.
+#[derive(Debug, Clone)]
+pub struct VectorStroke {
+    pub color: (u8, u8, u8),
+    pub width: f32,
+    /// Line cap as numbered by PDF and PostScript: 0 butt, 1 round, 2 square.
+    pub cap: u8,
+    /// Line join as numbered by PDF and PostScript: 0 miter, 1 round, 2 bevel.
+    pub join: u8,
+    pub miter_limit: f32,
+    /// Dash lengths and the offset into them, if the stroke is dashed.
+    pub dash: Option<(Vec<f32>, f32)>,
+}
+
+/// A path of a vector icon, painted with solid colors.
+#[derive(Debug, Clone)]
+pub struct VectorPath {
+    /// Transform from the path coordinates to the icon's, as [a b c d e f].
+    pub transform: [f32; 6],
+    /// The path, with the fill rule it is filled with.
+    pub path: Path,
+    /// Fill color, if the path is filled.
+    pub fill: Option<(u8, u8, u8)>,
+    pub stroke: Option<VectorStroke>,
+}
+
+/// An SVG icon reduced to paths that PDF and PostScript can draw natively.
+#[derive(Debug, Clone)]
+pub struct VectorIcon {
+    pub width: f32,
+    pub height: f32,
+    /// Paths in painting order.
+    pub paths: Vec<VectorPath>,
+}
+
+impl VectorIcon {
+    /// Converts an SVG document into paths.
+    /// Fails on features that have no direct equivalent, such as gradients, transparency,
+    /// clip paths, masks, filters, embedded images and text.
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let tree = Tree::from_str(content, &usvg::Options::default())
+            .map_err(|e| format!("Invalid SVG icon: {}", e))?;
+        let mut paths = Vec::new();
+        collect_paths(tree.root(), &mut paths)?;
+        Ok(Self {
+            width: tree.size().width(),
+            height: tree.size().height(),
+            paths,
+        })
+    }
+}
+
+fn unsupported(feature: &str) -> String {
+    format!(
+        "SVG icons with {} cannot be converted to vector paths, \
+         use a PNG icon or an SVG output instead",
+        feature
+    )
+}
+
+fn collect_paths(group: &usvg::Group, paths: &mut Vec<VectorPath>) -> Result<(), String> {
+    if group.opacity().get() < 1.0 {
+        return Err(unsupported("transparency"));
+    }
+    if group.clip_path().is_some() {
+        return Err(unsupported("clip paths"));
+    }
+    if group.mask().is_some() {
+        return Err(unsupported("masks"));
+    }
+    if !group.filters().is_empty() {
+        return Err(unsupported("filters"));
+    }
+    if group.blend_mode() != usvg::BlendMode::Normal {
+        return Err(unsupported("blend modes"));
+    }
+
+    for node in group.children() {
+        match node {
+            Node::Group(group) => collect_paths(group, paths)?,
+            Node::Path(path) => {
+                if !path.is_visible() {
+                    continue;
+                }
+                let fill = path
+                    .fill()
+                    .map(|fill| {
+                        if fill.opacity().get() < 1.0 {
+                            return Err(unsupported("transparency"));
+                        }
+                        solid_color(fill.paint())
+                    })
+                    .transpose()?;
+                let fill_rule = match path.fill().map(|fill| fill.rule()) {
+                    Some(usvg::FillRule::EvenOdd) => FillRule::EvenOdd,
+                    _ => FillRule::NonZero,
+                };
+                let stroke = path.stroke().map(convert_stroke).transpose()?;
+
+                let ts = path.abs_transform();
+                let vector = VectorPath {
+                    transform: [ts.sx, ts.ky, ts.kx, ts.sy, ts.tx, ts.ty],
+                    path: convert_path(path.data(), fill_rule),
+                    fill,
+                    stroke,
+                };
+                if path.paint_order() == PaintOrder::StrokeAndFill && vector.fill.is_some() {
+                    paths.push(VectorPath {
+                        fill: None,
+                        ..vector.clone()
+                    });
+                    paths.push(VectorPath {
+                        stroke: None,
+                        ..vector
+                    });
+                } else {
+                    paths.push(vector);
+                }
+            }
+            Node::Image(_) => return Err(unsupported("embedded images")),
+            Node::Text(_) => return Err(unsupported("text (convert it to paths first)")),
+        }
+    }
+    Ok(())
+}
+
+fn solid_color(paint: &Paint) -> Result<(u8, u8, u8), String> {
+    match paint {
+        Paint::Color(color) => Ok((color.red, color.green, color.blue)),
+        Paint::LinearGradient(_) | Paint::RadialGradient(_) => Err(unsupported("gradients")),
+        Paint::Pattern(_) => Err(unsupported("patterns")),
+    }
+}
+
+fn convert_stroke(stroke: &usvg::Stroke) -> Result<VectorStroke, String> {
+    if stroke.opacity().get() < 1.0 {
+        return Err(unsupported("transparency"));
+    }
+    Ok(VectorStroke {
+        color: solid_color(stroke.paint())?,
+        width: stroke.width().get(),
+        cap: match stroke.linecap() {
+            usvg::LineCap::Butt => 0,
+            usvg::LineCap::Round => 1,
+            usvg::LineCap::Square => 2,
+        },
+        join: match stroke.linejoin() {
+            usvg::LineJoin::Miter | usvg::LineJoin::MiterClip => 0,
+            usvg::LineJoin::Round => 1,
+            usvg::LineJoin::Bevel => 2,
+        },
+        miter_limit: stroke.miterlimit().get(),
+        dash: stroke
+            .dasharray()
+            .map(|dashes| (dashes.to_vec(), stroke.dashoffset())),
+    })
+}
+
+fn convert_path(data: &tiny_skia::Path, fill_rule: FillRule) -> Path {
+    let mut path = Path {
+        fill_rule,
+        ..Path::new()
+    };
+    for segment in data.segments() {
+        match segment {
+            PathSegment::MoveTo(p) => path.move_to(p.x, p.y),
+            PathSegment::LineTo(p) => path.line_to(p.x, p.y),
+            PathSegment::QuadTo(c, p) => path.quad_to(c.x, c.y, p.x, p.y),
+            PathSegment::CubicTo(c1, c2, p) => path.cubic_to(c1.x, c1.y, c2.x, c2.y, p.x, p.y),
+            PathSegment::Close => path.close(),
+        }
+    }
+    path
+}
@@ -0,0 +1,782 @@
+//! Rectangular Micro QR Code (rMQR) encoder, as described in ISO/IEC 23941.
+//!
+//! rMQR symbols are between 7 and 17 modules tall and between 27 and 139 modules wide,
+//! which makes them fit on narrow surfaces where a square symbol would waste space.
+//! Data segments are built with [`QrSegment`], the same as for regular QR codes.
+
+use std::fmt;
+
+use crate::core::qrgen::{BitBuffer, DataTooLong, QrCode, QrCodeEcc, QrSegment, QrSegmentMode};
+
+/// The heights (in modules) available for rMQR symbols.
+pub const RMQR_HEIGHTS: [u8; 6] = [7, 9, 11, 13, 15, 17];
+
+/// One of the 32 rMQR versions, each with a fixed height and width.
+/// Versions are ordered by height first, then by width (R7x43 is the first, R17x139 the last).
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct RmqrVersion(u8);
+
+impl RmqrVersion {
+    /// The smallest version, R7x43.
+    pub const MIN: RmqrVersion = RmqrVersion(0);
+
+    /// The largest version, R17x139.
+    pub const MAX: RmqrVersion = RmqrVersion(31);
+
+    /// Creates a version from its index in the range [0, 31].
+    ///
+    /// Panics if the index is out of range.
+    pub const fn new(index: u8) -> Self {
+        assert!(index <= RmqrVersion::MAX.0, "Version index out of range");
+        Self(index)
+    }
+
+    /// Returns the version with the given height and width, if such a version exists.
+    pub fn from_dimensions(height: u8, width: u8) -> Option<Self> {
+        Self::all().find(|v| v.height() == height && v.width() == width)
+    }
+
+    /// Returns an iterator over all versions, from R7x43 to R17x139.
+    pub fn all() -> impl Iterator<Item = Self> {
+        (RmqrVersion::MIN.0..=RmqrVersion::MAX.0).map(RmqrVersion)
+    }
+
+    /// Returns the index of this version, in the range [0, 31].
+    pub const fn index(self) -> u8 {
+        self.0
+    }
+
+    /// Returns the height of this version in modules.
+    pub fn height(self) -> u8 {
+        HEIGHT[usize::from(self.0)]
+    }
+
+    /// Returns the width of this version in modules.
+    pub fn width(self) -> u8 {
+        WIDTH[usize::from(self.0)]
+    }
+}
+
+impl fmt::Display for RmqrVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "R{}x{}", self.height(), self.width())
+    }
+}
+
+/// A Rectangular Micro QR Code symbol.
+///
+/// rMQR only defines the error correction levels M and H. When encoding,
+/// `Low` is treated as `Medium` and `Quartile` is treated as `High`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct RmqrCode {
+    version: RmqrVersion,
+    width: i32,
+    height: i32,
+    errorcorrectionlevel: QrCodeEcc,
+    modules: Vec<bool>,
+    isfunction: Vec<bool>,
+}
+
+impl RmqrCode {
+    /// Returns an rMQR code representing the given text at the given error correction level.
+    /// The version with the smallest area that fits the data is chosen.
+    pub fn encode_text(text: &str, ecl: QrCodeEcc) -> Result<Self, DataTooLong> {
+        let segs = QrSegment::make_segments(text);
+        RmqrCode::encode_segments(&segs, ecl)
+    }
+
+    /// Returns an rMQR code representing the given segments at the given error correction level.
+    /// The version with the smallest area that fits the data is chosen.
+    pub fn encode_segments(segs: &[QrSegment], ecl: QrCodeEcc) -> Result<Self, DataTooLong> {
+        RmqrCode::encode_segments_advanced(segs, ecl, None, true)
+    }
+
+    /// Returns an rMQR code representing the given segments with the given encoding parameters.
+    ///
+    /// If `height` is set, only versions of that height are considered; otherwise the
+    /// version with the smallest area that fits the data is chosen. Iff `boostecl` is
+    /// `true`, the level is raised to H if the data still fits in the chosen version.
+    ///
+    /// Panics if `height` is not one of [`RMQR_HEIGHTS`].
+    pub fn encode_segments_advanced(
+        segs: &[QrSegment],
+        ecl: QrCodeEcc,
+        height: Option<u8>,
+        boostecl: bool,
+    ) -> Result<Self, DataTooLong> {
+        assert!(
+            height.is_none_or(|h| RMQR_HEIGHTS.contains(&h)),
+            "Height out of range"
+        );
+
+        let mut ecl = normalize_ecl(ecl);
+
+        // Find the version with the smallest area that fits the data. The last version
+        // considered has the largest capacity, so it reports the error if none fit.
+        let area = |v: RmqrVersion| usize::from(v.height()) * usize::from(v.width());
+        let mut best: Option<(RmqrVersion, usize)> = None;
+        let mut error = DataTooLong::SegmentTooLong;
+        for version in RmqrVersion::all() {
+            if height.is_some_and(|h| h != version.height()) {
+                continue;
+            }
+            let capacity = get_num_data_codewords(version, ecl) * 8;
+            match get_total_bits(segs, version) {
+                Some(n) if n <= capacity => {
+                    if best.is_none_or(|(b, _)| area(version) < area(b)) {
+                        best = Some((version, n));
+                    }
+                }
+                Some(n) => error = DataTooLong::DataOverCapacity(n, capacity),
+                None => error = DataTooLong::SegmentTooLong,
+            }
+        }
+        let (version, datausedbits) = best.ok_or(error)?;
+
+        // Increase the error correction level if the data still fits
+        if boostecl && datausedbits <= get_num_data_codewords(version, QrCodeEcc::High) * 8 {
+            ecl = QrCodeEcc::High;
+        }
+
+        // Concatenate all segments to create the data bit string
+        let mut bb = BitBuffer(Vec::new());
+        for seg in segs {
+            bb.append_bits(mode_bits(seg.mode()).unwrap(), 3);
+            bb.append_bits(
+                u32::try_from(seg.num_chars()).unwrap(),
+                num_char_count_bits(seg.mode(), version).unwrap(),
+            );
+            bb.0.extend_from_slice(seg.data());
+        }
+        debug_assert_eq!(bb.0.len(), datausedbits);
+
+        // Add terminator and pad up to a byte if applicable
+        let capacity = get_num_data_codewords(version, ecl) * 8;
+        let terminator = std::cmp::min(3, capacity - bb.0.len());
+        bb.append_bits(0, terminator as u8);
+        let padding = bb.0.len().wrapping_neg() & 7;
+        bb.append_bits(0, padding as u8);
+
+        // Pad with alternating bytes until data capacity is reached
+        for &padbyte in [0xEC, 0x11].iter().cycle() {
+            if bb.0.len() >= capacity {
+                break;
+            }
+            bb.append_bits(padbyte, 8);
+        }
+
+        // Pack bits into bytes in big endian
+        let mut datacodewords = vec![0u8; bb.0.len() / 8];
+        for (i, &bit) in bb.0.iter().enumerate() {
+            datacodewords[i >> 3] |= u8::from(bit) << (7 - (i & 7));
+        }
+
+        Ok(RmqrCode::encode_codewords(version, ecl, &datacodewords))
+    }
+
+    /// Creates a new rMQR code with the given version, error correction level and data codewords.
+    ///
+    /// Panics if the number of data codewords doesn't match the version and level.
+    pub fn encode_codewords(ver: RmqrVersion, ecl: QrCodeEcc, datacodewords: &[u8]) -> Self {
+        let ecl = normalize_ecl(ecl);
+        let width = usize::from(ver.width());
+        let height = usize::from(ver.height());
+        let mut result = Self {
+            version: ver,
+            width: width as i32,
+            height: height as i32,
+            errorcorrectionlevel: ecl,
+            modules: vec![false; width * height],
+            isfunction: vec![false; width * height],
+        };
+
+        result.draw_function_patterns();
+        let allcodewords = result.add_ecc_and_interleave(datacodewords);
+        result.draw_codewords(&allcodewords);
+        result.apply_mask();
+        result.draw_format_bits();
+
+        result.isfunction.clear();
+        result.isfunction.shrink_to_fit();
+        result
+    }
+
+    /// Returns this code's version.
+    pub fn version(&self) -> RmqrVersion {
+        self.version
+    }
+
+    /// Returns the width of this code in modules.
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    /// Returns the height of this code in modules.
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// Returns the columns of the alignment pattern centres, which also hold vertical
+    /// timing patterns.
+    pub fn alignment_columns(&self) -> &'static [u8] {
+        alignment_columns(self.version.width())
+    }
+
+    /// Returns the error correction level of this code, which is either `Medium` or `High`.
+    pub fn error_correction_level(&self) -> QrCodeEcc {
+        self.errorcorrectionlevel
+    }
+
+    /// Returns the color of the module at the given coordinates (`true` for dark).
+    /// Coordinates out of bounds are light.
+    pub fn get_module(&self, x: i32, y: i32) -> bool {
+        (0..self.width).contains(&x) && (0..self.height).contains(&y) && self.module(x, y)
+    }
+
+    fn module(&self, x: i32, y: i32) -> bool {
+        self.modules[(y * self.width + x) as usize]
+    }
+
+    fn set_function_module(&mut self, x: i32, y: i32, isdark: bool) {
+        let index = (y * self.width + x) as usize;
+        self.modules[index] = isdark;
+        self.isfunction[index] = true;
+    }
+
+    /// Draws the timing patterns, finder, sub-finder, corner patterns,
+    /// alignment patterns and the reserved format areas.
+    fn draw_function_patterns(&mut self) {
+        let (w, h) = (self.width, self.height);
+
+        // Timing patterns along all four edges
+        for x in 0..w {
+            self.set_function_module(x, 0, x % 2 == 0);
+            self.set_function_module(x, h - 1, x % 2 == 0);
+        }
+        for y in 0..h {
+            self.set_function_module(0, y, y % 2 == 0);
+            self.set_function_module(w - 1, y, y % 2 == 0);
+        }
+
+        // Finder pattern in the top left corner
+        for dy in 0..7 {
+            for dx in 0..7 {
+                let dist = std::cmp::max((dx - 3i32).abs(), (dy - 3i32).abs());
+                self.set_function_module(dx, dy, dist != 2);
+            }
+        }
+
+        // Sub-finder pattern in the bottom right corner
+        for dy in 0..5 {
+            for dx in 0..5 {
+                let dist = std::cmp::max((dx - 2i32).abs(), (dy - 2i32).abs());
+                self.set_function_module(w - 5 + dx, h - 5 + dy, dist != 1);
+            }
+        }
+
+        // Corner finder patterns in the bottom left and top right corners
+        self.set_function_module(0, h - 2, true);
+        self.set_function_module(1, h - 2, false);
+        self.set_function_module(1, h - 1, true);
+        self.set_function_module(w - 2, 0, true);
+        self.set_function_module(w - 2, 1, false);
+        self.set_function_module(w - 1, 1, true);
+
+        // Separator next to the finder pattern
+        for y in 0..7 {
+            self.set_function_module(7, y, false);
+        }
+        if h > 7 {
+            for x in 0..8 {
+                self.set_function_module(x, 7, false);
+            }
+        }
+
+        // Alignment patterns: a vertical timing line with a 3x3 square at each end
+        for &cx in alignment_columns(self.version.width()) {
+            let cx = i32::from(cx);
+            for y in 0..h {
+                self.set_function_module(cx, y, y % 2 == 0);
+            }
+            for &cy in &[1, h - 2] {
+                for dy in -1..=1 {
+                    for dx in -1..=1 {
+                        let dark = dx != 0 || dy != 0;
+                        self.set_function_module(cx + dx, cy + dy, dark);
+                    }
+                }
+            }
+        }
+
+        // Reserve the format areas; overwritten after masking
+        self.draw_format_bits();
+    }
+
+    /// Draws both copies of the format information, which encode the version and level.
+    fn draw_format_bits(&mut self) {
+        let data = u32::from(self.version.index())
+            + if self.errorcorrectionlevel == QrCodeEcc::High {
+                32
+            } else {
+                0
+            };
+        let mut rem = data;
+        for _ in 0..12 {
+            rem = (rem << 1) ^ ((rem >> 11) * 0x1F25);
+        }
+        let bits = data << 12 | rem;
+        let left = bits ^ 0x1FAB2;
+        let right = bits ^ 0x20A7B;
+
+        let (w, h) = (self.width, self.height);
+        for i in 0..5 {
+            for j in 0..3 {
+                let bit = (j * 5 + i) as u32;
+                self.set_function_module(8 + j, 1 + i, get_bit(left, bit));
+                self.set_function_module(w - 8 + j, h - 6 + i, get_bit(right, bit));
+            }
+        }
+        for i in 0..3 {
+            self.set_function_module(11, 1 + i, get_bit(left, 15 + i as u32));
+            self.set_function_module(w - 5 + i, h - 6, get_bit(right, 15 + i as u32));
+        }
+    }
+
+    /// Splits the data into blocks, appends the ECC to each block and interleaves them.
+    fn add_ecc_and_interleave(&self, data: &[u8]) -> Vec<u8> {
+        let ver = self.version;
+        let ecl = self.errorcorrectionlevel;
+        assert_eq!(
+            data.len(),
+            get_num_data_codewords(ver, ecl),
+            "Illegal argument"
+        );
+
+        let numblocks = usize::from(table_get(&BLOCKS, ver, ecl));
+        let rawcodewords = usize::from(TOTAL_CODEWORDS[usize::from(ver.index())]);
+        let blockecclen = (rawcodewords - data.len()) / numblocks;
+        let numshortblocks = numblocks - rawcodewords % numblocks;
+        let shortblocklen = rawcodewords / numblocks;
+
+        let rsdiv = QrCode::reed_solomon_compute_divisor(blockecclen);
+        let mut blocks = Vec::<Vec<u8>>::with_capacity(numblocks);
+        let mut k = 0;
+        for i in 0..numblocks {
+            let datlen = shortblocklen - blockecclen + usize::from(i >= numshortblocks);
+            let mut dat = data[k..k + datlen].to_vec();
+            k += datlen;
+            let ecc = QrCode::reed_solomon_compute_remainder(&dat, &rsdiv);
+            if i < numshortblocks {
+                dat.push(0);
+            }
+            dat.extend_from_slice(&ecc);
+            blocks.push(dat);
+        }
+
+        let mut result = Vec::<u8>::with_capacity(rawcodewords);
+        for i in 0..=shortblocklen {
+            for (j, block) in blocks.iter().enumerate() {
+                // Skip the padding byte in short blocks
+                if i != shortblocklen - blockecclen || j >= numshortblocks {
+                    result.push(block[i]);
+                }
+            }
+        }
+        result
+    }
+
+    /// Places the codewords in two-module wide columns, zigzagging up and down
+    /// from the right edge. Remainder modules are left light.
+    fn draw_codewords(&mut self, data: &[u8]) {
+        let mut i: usize = 0;
+        let mut upward = true;
+        let mut right = self.width - 2;
+        while right >= 1 {
+            for vert in 0..self.height {
+                for j in 0..2 {
+                    let x = right - j;
+                    let y = if upward { self.height - 1 - vert } else { vert };
+                    let index = (y * self.width + x) as usize;
+                    if !self.isfunction[index] && i < data.len() * 8 {
+                        self.modules[index] = get_bit(u32::from(data[i >> 3]), 7 - (i as u32 & 7));
+                        i += 1;
+                    }
+                }
+            }
+            upward = !upward;
+            right -= 2;
+        }
+        debug_assert_eq!(i, data.len() * 8);
+    }
+
+    /// Applies the single rMQR data mask to all non-function modules.
+    fn apply_mask(&mut self) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let index = (y * self.width + x) as usize;
+                if !self.isfunction[index] && (y / 2 + x / 3) % 2 == 0 {
+                    self.modules[index] ^= true;
+                }
+            }
+        }
+    }
+}
+
+/// Maps the QR code levels to the two levels defined by rMQR.
+fn normalize_ecl(ecl: QrCodeEcc) -> QrCodeEcc {
+    match ecl {
+        QrCodeEcc::Low | QrCodeEcc::Medium => QrCodeEcc::Medium,
+        QrCodeEcc::Quartile | QrCodeEcc::High => QrCodeEcc::High,
+    }
+}
+
+/// Returns the number of bits needed to encode the segments in the given version,
+/// or None if a segment can't be represented in it.
+fn get_total_bits(segs: &[QrSegment], version: RmqrVersion) -> Option<usize> {
+    let mut result: usize = 0;
+    for seg in segs {
+        mode_bits(seg.mode())?;
+        let ccbits = num_char_count_bits(seg.mode(), version)?;
+        if seg.num_chars() >= 1usize << ccbits {
+            return None;
+        }
+        result = result.checked_add(3 + usize::from(ccbits))?;
+        result = result.checked_add(seg.data().len())?;
+    }
+    Some(result)
+}
+
+/// Returns the 3-bit mode indicator, or None for modes that rMQR doesn't support.
+fn mode_bits(mode: QrSegmentMode) -> Option<u32> {
+    match mode {
+        QrSegmentMode::Numeric => Some(1),
+        QrSegmentMode::Alphanumeric => Some(2),
+        QrSegmentMode::Byte => Some(3),
+        QrSegmentMode::Kanji => Some(4),
+        QrSegmentMode::Eci
+        | QrSegmentMode::StructuredAppend
+        | QrSegmentMode::Fnc1First
+        | QrSegmentMode::Fnc1Second => None,
+    }
+}
+
+/// Returns the width of the character count field for the mode in the given version.
+fn num_char_count_bits(mode: QrSegmentMode, version: RmqrVersion) -> Option<u8> {
+    let table = match mode {
+        QrSegmentMode::Numeric => &NUMERIC_CCI,
+        QrSegmentMode::Alphanumeric => &ALPHANUMERIC_CCI,
+        QrSegmentMode::Byte => &BYTE_CCI,
+        QrSegmentMode::Kanji => &KANJI_CCI,
+        QrSegmentMode::Eci
+        | QrSegmentMode::StructuredAppend
+        | QrSegmentMode::Fnc1First
+        | QrSegmentMode::Fnc1Second => return None,
+    };
+    Some(table[usize::from(version.index())])
+}
+
+fn get_num_data_codewords(version: RmqrVersion, ecl: QrCodeEcc) -> usize {
+    usize::from(table_get(&DATA_CODEWORDS, version, ecl))
+}
+
+fn table_get(table: &'static [[u8; 32]; 2], version: RmqrVersion, ecl: QrCodeEcc) -> u8 {
+    let level = usize::from(normalize_ecl(ecl) == QrCodeEcc::High);
+    table[level][usize::from(version.index())]
+}
+
+/// Returns the column coordinates of the alignment pattern centres for the given width.
+fn alignment_columns(width: u8) -> &'static [u8] {
+    match width {
+        43 => &[21],
+        59 => &[19, 39],
+        77 => &[25, 51],
+        99 => &[23, 49, 75],
+        139 => &[27, 55, 83, 111],
+        _ => &[],
+    }
+}
+
+fn get_bit(x: u32, i: u32) -> bool {
+    (x >> i) & 1 != 0
+}
+
+// Tables from ISO/IEC 23941, indexed by version.
+
+#[rustfmt::skip]
+static HEIGHT: [u8; 32] = [
+    7, 7, 7, 7, 7,
+    9, 9, 9, 9, 9,
+    11, 11, 11, 11, 11, 11,
+    13, 13, 13, 13, 13, 13,
+    15, 15, 15, 15, 15,
+    17, 17, 17, 17, 17,
+];
+
+#[rustfmt::skip]
+static WIDTH: [u8; 32] = [
+    43, 59, 77, 99, 139,
+    43, 59, 77, 99, 139,
+    27, 43, 59, 77, 99, 139,
+    27, 43, 59, 77, 99, 139,
+    43, 59, 77, 99, 139,
+    43, 59, 77, 99, 139,
+];
+
+#[rustfmt::skip]
+static TOTAL_CODEWORDS: [u8; 32] = [
+    13, 21, 32, 44, 68,
+    21, 33, 49, 66, 99,
+    15, 31, 47, 67, 89, 132,
+    21, 41, 60, 85, 113, 166,
+    51, 74, 103, 136, 199,
+    61, 88, 122, 160, 232,
+];
+
+// Medium, then High
+#[rustfmt::skip]
+static DATA_CODEWORDS: [[u8; 32]; 2] = [
+    [
+        6, 12, 20, 28, 44,
+        12, 21, 31, 42, 63,
+        7, 19, 31, 43, 57, 84,
+        12, 27, 38, 53, 73, 106,
+        33, 48, 67, 88, 127,
+        39, 56, 78, 100, 152,
+    ],
+    [
+        3, 7, 10, 14, 24,
+        7, 11, 17, 22, 33,
+        5, 11, 15, 23, 29, 42,
+        7, 13, 20, 29, 35, 54,
+        15, 26, 31, 48, 69,
+        21, 28, 38, 56, 76,
+    ],
+];
+
+// Medium, then High
+#[rustfmt::skip]
+static BLOCKS: [[u8; 32]; 2] = [
+    [
+        1, 1, 1, 1, 1,
+        1, 1, 1, 1, 2,
+        1, 1, 1, 1, 2, 2,
+        1, 1, 1, 2, 2, 3,
+        1, 1, 2, 2, 3,
+        1, 2, 2, 3, 4,
+    ],
+    [
+        1, 1, 1, 1, 2,
+        1, 1, 2, 2, 3,
+        1, 1, 2, 2, 2, 3,
+        1, 1, 2, 2, 3, 4,
+        2, 2, 3, 4, 5,
+        2, 2, 3, 4, 6,
+    ],
+];
+
+#[rustfmt::skip]
+static NUMERIC_CCI: [u8; 32] = [
+    4, 5, 6, 7, 7,
+    5, 6, 7, 7, 8,
+    4, 6, 7, 7, 8, 8,
+    5, 6, 7, 7, 8, 8,
+    7, 7, 8, 8, 9,
+    7, 8, 8, 8, 9,
+];
+
+#[rustfmt::skip]
+static ALPHANUMERIC_CCI: [u8; 32] = [
+    3, 5, 5, 6, 6,
+    5, 5, 6, 6, 7,
+    4, 5, 6, 6, 7, 7,
+    5, 6, 6, 7, 7, 8,
+    6, 7, 7, 7, 8,
+    6, 7, 7, 8, 8,
+];
+
+#[rustfmt::skip]
+static BYTE_CCI: [u8; 32] = [
+    3, 4, 5, 5, 6,
+    4, 5, 5, 6, 6,
+    3, 5, 5, 6, 6, 7,
+    4, 5, 6, 6, 7, 7,
+    6, 6, 7, 7, 7,
+    6, 6, 7, 7, 8,
+];
+
+#[rustfmt::skip]
+static KANJI_CCI: [u8; 32] = [
+    2, 3, 4, 5, 5,
+    3, 4, 5, 5, 6,
+    2, 4, 5, 5, 6, 6,
+    3, 5, 5, 6, 6, 7,
+    5, 5, 6, 6, 7,
+    5, 6, 6, 6, 7,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::renderer::{AlignmentPattern, FinderCorner, FinderPattern, QrGrid};
+
+    /// FNV-1a hash of the modules, row by row, to pin down whole symbols.
+    fn module_hash(qr: &RmqrCode) -> u64 {
+        let mut hash = 0xCBF2_9CE4_8422_2325u64;
+        for y in 0..qr.height() {
+            for x in 0..qr.width() {
+                hash = (hash ^ u64::from(qr.get_module(x, y))).wrapping_mul(0x0000_0100_0000_01B3);
+            }
+        }
+        hash
+    }
+
+    fn encode(text: &str, ecl: QrCodeEcc, height: u8) -> RmqrCode {
+        RmqrCode::encode_segments_advanced(
+            &QrSegment::make_segments(text),
+            ecl,
+            Some(height),
+            false,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn known_answer_symbols() {
+        let expected: [(u8, QrCodeEcc, i32, u64); 12] = [
+            (7, QrCodeEcc::Medium, 59, 0xEA56_43FD_0616_15AA),
+            (7, QrCodeEcc::High, 77, 0xEB67_7B81_5FF3_3FE7),
+            (9, QrCodeEcc::Medium, 43, 0x03B5_134D_AD14_03DB),
+            (9, QrCodeEcc::High, 59, 0x2987_D0DC_9865_B77A),
+            (11, QrCodeEcc::Medium, 43, 0x26A1_826E_6234_9D32),
+            (11, QrCodeEcc::High, 43, 0xB7C1_D9C1_1300_95FE),
+            (13, QrCodeEcc::Medium, 27, 0x2BFB_FED6_3845_6B05),
+            (13, QrCodeEcc::High, 43, 0x33B5_36C7_D7FA_CABE),
+            (15, QrCodeEcc::Medium, 43, 0x9584_BC83_4D21_01D6),
+            (15, QrCodeEcc::High, 43, 0xD19B_8355_5258_B8B2),
+            (17, QrCodeEcc::Medium, 43, 0x2632_BBDF_1F97_A458),
+            (17, QrCodeEcc::High, 43, 0x971F_CF78_6372_E500),
+        ];
+        for (height, ecl, width, hash) in expected {
+            let qr = encode("RMQR-1234", ecl, height);
+            assert_eq!(qr.height(), i32::from(height));
+            assert_eq!(qr.error_correction_level(), ecl);
+            assert_eq!(
+                (qr.width(), module_hash(&qr)),
+                (width, hash),
+                "R{}x{} {:?}",
+                height,
+                width,
+                ecl
+            );
+        }
+    }
+
+    #[test]
+    fn numeric_capacity_of_r7x43() {
+        let fits = |digits: usize, ecl: QrCodeEcc| encode(&"1".repeat(digits), ecl, 7).width();
+        assert_eq!(fits(12, QrCodeEcc::Medium), 43);
+        assert_eq!(fits(13, QrCodeEcc::Medium), 59);
+        assert_eq!(fits(5, QrCodeEcc::High), 43);
+        assert_eq!(fits(6, QrCodeEcc::High), 59);
+    }
+
+    #[test]
+    fn picks_the_smallest_symbol_without_a_height() {
+        let segs = QrSegment::make_segments("RMQR");
+        let qr = RmqrCode::encode_segments_advanced(&segs, QrCodeEcc::Medium, None, false).unwrap();
+        assert_eq!((qr.width(), qr.height()), (27, 11));
+        assert!(qr.alignment_columns().is_empty());
+    }
+
+    #[test]
+    fn finder_alignment_and_timing_patterns() {
+        let qr = encode("RMQR", QrCodeEcc::Medium, 17);
+        assert_eq!((qr.width(), qr.height()), (43, 17));
+        assert_eq!(qr.alignment_columns(), [21]);
+        assert_eq!(
+            qr.finder_patterns(),
+            [
+                FinderPattern {
+                    x: 0,
+                    y: 0,
+                    size: 7,
+                    corner: FinderCorner::TopLeft,
+                },
+                FinderPattern {
+                    x: 38,
+                    y: 12,
+                    size: 5,
+                    corner: FinderCorner::BottomRight,
+                },
+            ]
+        );
+        assert_eq!(
+            qr.alignment_patterns(),
+            [
+                AlignmentPattern {
+                    x: 20,
+                    y: 0,
+                    size: 3,
+                },
+                AlignmentPattern {
+                    x: 20,
+                    y: 14,
+                    size: 3,
+                },
+            ]
+        );
+
+        // Dark rings around a light ring and a dark center
+        let ring =
+            |x: usize, y: usize, size: usize| x.min(y).min(size - 1 - x).min(size - 1 - y) != 1;
+        for y in 0..7 {
+            for x in 0..7 {
+                assert_eq!(
+                    QrGrid::get_module(&qr, x, y),
+                    ring(x, y, 7),
+                    "finder ({x}, {y})"
+                );
+                assert!(qr.is_finder(x, y) && !qr.is_timing(x, y));
+            }
+        }
+        for y in 0..5 {
+            for x in 0..5 {
+                assert_eq!(
+                    QrGrid::get_module(&qr, 38 + x, 12 + y),
+                    ring(x, y, 5),
+                    "sub-finder ({x}, {y})"
+                );
+                assert!(qr.is_finder(38 + x, 12 + y) && !qr.is_timing(38 + x, 12 + y));
+            }
+        }
+        for y in [0, 14] {
+            for x in 20..23 {
+                assert!(qr.is_alignment(x, y) && !qr.is_timing(x, y));
+            }
+        }
+
+        // Timing patterns alternate along the edges and the alignment column
+        for x in 7..38 {
+            if qr.is_timing(x, 0) {
+                assert_eq!(QrGrid::get_module(&qr, x, 0), x % 2 == 0, "top ({x}, 0)");
+            }
+            if qr.is_timing(x, 16) {
+                assert_eq!(
+                    QrGrid::get_module(&qr, x, 16),
+                    x % 2 == 0,
+                    "bottom ({x}, 16)"
+                );
+            }
+        }
+        assert!((8..20).all(|x| qr.is_timing(x, 0) && qr.is_timing(x, 16)));
+        for y in 3..14 {
+            assert!(qr.is_timing(21, y));
+            assert_eq!(
+                QrGrid::get_module(&qr, 21, y),
+                y % 2 == 0,
+                "column (21, {y})"
+            );
+        }
+        assert!(!qr.is_timing(10, 8));
+    }
+}
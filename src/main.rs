@@ -6,7 +6,7 @@ use qrosity::modes::gui::run as run_gui_app;
 #[cfg(feature = "cli")]
 use qrosity::{
     models::{QrData, TextQr},
-    modes::cli::run as run_cli_app,
+    modes::{cli::run as run_cli_app, decode::run as run_decode_app},
 };
 
 #[cfg(feature = "batch")]
@@ -42,6 +42,18 @@ struct CliOptions {
         default_value_t = chrono::Local::now().format("qr_%Y-%m-%d_%H:%M:%S").to_string(),
     )]
     output: String,
+
+    #[arg(
+        long,
+        help = "Split the content across up to 16 linked QR codes (Structured Append), saved as numbered files"
+    )]
+    split: bool,
+
+    #[arg(
+        long,
+        help = "Refuse to save QR codes that are unlikely to scan, instead of only warning"
+    )]
+    strict: bool,
 }
 
 /// A second set of command-line only options for the generate subcommand.
@@ -58,6 +70,20 @@ struct GenerateOptions {
         global = true,
     )]
     output: String,
+
+    #[arg(
+        long,
+        help = "Split the content across up to 16 linked QR codes (Structured Append), saved as numbered files",
+        global = true
+    )]
+    split: bool,
+
+    #[arg(
+        long,
+        help = "Refuse to save QR codes that are unlikely to scan, instead of only warning",
+        global = true
+    )]
+    strict: bool,
 }
 
 /// Enum representing the different application modes: CLI, GUI, and Batch processing.
@@ -67,11 +93,22 @@ enum AppMode {
     /// A set of subcommands for generating different types of QR codes.
     Generate {
         #[command(subcommand)]
-        data: QrData,
+        data: Box<QrData>,
         #[command(flatten)]
         options: GenerateOptions,
     },
 
+    /// Read QR codes from image files (PNG, JPEG, SVG, ...) and print their content.
+    /// Micro QR and rMQR codes cannot be decoded.
+    #[cfg(feature = "cli")]
+    Decode {
+        #[arg(required = true, help = "Image files to decode")]
+        files: Vec<String>,
+
+        #[arg(long, help = "Print the results as JSON")]
+        json: bool,
+    },
+
     /// Launch the graphical user interface for QR code generation.
     #[cfg(feature = "gui")]
     Gui,
@@ -106,11 +143,20 @@ fn main() {
         }
         #[cfg(feature = "cli")]
         Some(AppMode::Generate { data, options }) => {
-            run_cli_app(data, options.output);
+            run_cli_app(*data, options.output, options.split, options.strict);
+        }
+        #[cfg(feature = "cli")]
+        Some(AppMode::Decode { files, json }) => {
+            run_decode_app(files, json);
         }
         None => {
             #[cfg(feature = "cli")]
-            run_cli_app(QrData::Text(app.text), app.options.output);
+            run_cli_app(
+                QrData::Text(app.text),
+                app.options.output,
+                app.options.split,
+                app.options.strict,
+            );
         }
     }
 }
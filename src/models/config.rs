@@ -1,18 +1,22 @@
 use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
 
 use super::QrImage;
-use crate::core::QrCodeEcc;
+use crate::core::renderer::{FinderShapeProvider, ModuleShapeProvider};
+use crate::core::{QrCodeEcc, eci};
 
 #[cfg(feature = "cli")]
 use clap::{Args, ValueEnum};
 
 /// QR code module shapes.
 /// These shapes determine how each module (square) of the QR code is rendered.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 #[cfg_attr(feature = "cli", derive(ValueEnum))]
 #[cfg_attr(feature = "batch", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "batch", serde(rename_all = "kebab-case"))]
 pub enum ModuleShape {
+    #[default]
     Square,
     Dots,
     Gapped,
@@ -20,32 +24,837 @@ pub enum ModuleShape {
     Diamond,
     HorizontalBars,
     VerticalBars,
+    /// Join neighbouring modules into smooth blobs, rounding their outer corners
+    /// and filling in the inner ones.
+    Liquid,
 }
 
 // QR code finder pattern shapes.
-/// These shapes determine how the position detection patterns ("eyes") of the QR code are rendered.
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// These shapes determine how the outer ring of the position detection patterns is rendered.
+/// The light ring inside it follows the same shape.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 #[cfg_attr(feature = "cli", derive(ValueEnum))]
 #[cfg_attr(feature = "batch", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "batch", serde(rename_all = "kebab-case"))]
 pub enum FinderShape {
+    #[default]
     Square,
     Circle,
     Rounded,
+    /// Round the top left and bottom right corners fully.
+    Leaf,
+    /// Cut the corners at 45 degrees.
+    CutCorner,
 }
 
-/// QR code gradient directions.
-/// These directions determine how color gradients are applied to the QR code.
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// Shapes of the 3x3 center ("eye") of the finder patterns.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 #[cfg_attr(feature = "cli", derive(ValueEnum))]
 #[cfg_attr(feature = "batch", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "batch", serde(rename_all = "kebab-case"))]
+pub enum EyeShape {
+    #[default]
+    Square,
+    Rounded,
+    Circle,
+    /// Round the top left and bottom right corners fully.
+    Leaf,
+    Diamond,
+    Star,
+}
+
+/// Shapes of the alignment patterns, the smaller squares that help scanners correct distortion.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+#[cfg_attr(feature = "batch", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "batch", serde(rename_all = "kebab-case"))]
+pub enum AlignmentShape {
+    /// Draw each module with the module shape, like the data modules.
+    #[default]
+    Modules,
+    /// Draw the pattern as solid squares.
+    Square,
+    /// Draw the pattern as a rounded ring around a dot.
+    Rounded,
+    /// Draw the pattern as a circle around a dot.
+    Circle,
+}
+
+impl From<FinderShape> for EyeShape {
+    /// The eye shape that matches a finder shape, used when no eye shape is set.
+    fn from(shape: FinderShape) -> Self {
+        match shape {
+            FinderShape::Square | FinderShape::CutCorner => EyeShape::Square,
+            FinderShape::Circle => EyeShape::Circle,
+            FinderShape::Rounded => EyeShape::Rounded,
+            FinderShape::Leaf => EyeShape::Leaf,
+        }
+    }
+}
+
+impl FinderShape {
+    const NAMES: [(&'static str, FinderShape); 5] = [
+        ("square", FinderShape::Square),
+        ("circle", FinderShape::Circle),
+        ("rounded", FinderShape::Rounded),
+        ("leaf", FinderShape::Leaf),
+        ("cut-corner", FinderShape::CutCorner),
+    ];
+}
+
+impl FromStr for FinderShape {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FinderShape::NAMES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(s.trim()))
+            .map(|&(_, shape)| shape)
+            .ok_or_else(|| format!("Unknown finder shape: {}", s))
+    }
+}
+
+impl fmt::Display for FinderShape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, _) = FinderShape::NAMES
+            .iter()
+            .find(|(_, shape)| shape == self)
+            .unwrap();
+        write!(f, "{}", name)
+    }
+}
+
+impl EyeShape {
+    const NAMES: [(&'static str, EyeShape); 6] = [
+        ("square", EyeShape::Square),
+        ("rounded", EyeShape::Rounded),
+        ("circle", EyeShape::Circle),
+        ("leaf", EyeShape::Leaf),
+        ("diamond", EyeShape::Diamond),
+        ("star", EyeShape::Star),
+    ];
+}
+
+impl FromStr for EyeShape {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        EyeShape::NAMES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(s.trim()))
+            .map(|&(_, shape)| shape)
+            .ok_or_else(|| format!("Unknown finder eye shape: {}", s))
+    }
+}
+
+impl fmt::Display for EyeShape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, _) = EyeShape::NAMES
+            .iter()
+            .find(|(_, shape)| shape == self)
+            .unwrap();
+        write!(f, "{}", name)
+    }
+}
+
+/// Style of a single finder pattern, overriding the finder options for it.
+/// Parsed from comma separated "key=value" pairs, for example "shape=leaf,rotate=270":
+/// `shape` and `eye` set the shapes, `color` and `eye-color` the colors, and
+/// `rotate` turns the pattern clockwise by 0, 90, 180 or 270 degrees.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "batch", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "batch", serde(try_from = "String", into = "String"))]
+pub struct FinderStyle {
+    pub shape: Option<FinderShape>,
+    pub eye: Option<EyeShape>,
+    pub color: Option<Color>,
+    pub eye_color: Option<Color>,
+    /// Clockwise rotation in degrees.
+    pub rotate: u16,
+}
+
+impl FromStr for FinderStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut style = FinderStyle::default();
+        // Commas inside parentheses belong to colors like "rgb(0, 0, 0)"
+        let mut pairs = Vec::new();
+        let (mut depth, mut start) = (0usize, 0);
+        for (i, c) in s.char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth = depth.saturating_sub(1),
+                ',' if depth == 0 => {
+                    pairs.push(&s[start..i]);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        pairs.push(&s[start..]);
+
+        for pair in pairs
+            .into_iter()
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+        {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid finder style: {}, expected key=value pairs", s))?;
+            let value = value.trim();
+            match key.trim() {
+                "shape" => style.shape = Some(value.parse()?),
+                "eye" => style.eye = Some(value.parse()?),
+                "color" => style.color = Some(value.parse()?),
+                "eye-color" => style.eye_color = Some(value.parse()?),
+                "rotate" => {
+                    style.rotate = value
+                        .parse()
+                        .ok()
+                        .filter(|degrees| degrees % 90 == 0 && *degrees < 360)
+                        .ok_or_else(|| {
+                            format!("Finder rotation must be 0, 90, 180 or 270, got {}", value)
+                        })?
+                }
+                other => return Err(format!("Unknown finder style key: {}", other)),
+            }
+        }
+        Ok(style)
+    }
+}
+
+impl fmt::Display for FinderStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut pairs = Vec::new();
+        if let Some(shape) = self.shape {
+            pairs.push(format!("shape={}", shape));
+        }
+        if let Some(eye) = self.eye {
+            pairs.push(format!("eye={}", eye));
+        }
+        if let Some(color) = &self.color {
+            pairs.push(format!("color={}", color));
+        }
+        if let Some(color) = &self.eye_color {
+            pairs.push(format!("eye-color={}", color));
+        }
+        if self.rotate != 0 {
+            pairs.push(format!("rotate={}", self.rotate));
+        }
+        write!(f, "{}", pairs.join(","))
+    }
+}
+
+impl TryFrom<String> for FinderStyle {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<FinderStyle> for String {
+    fn from(value: FinderStyle) -> Self {
+        value.to_string()
+    }
+}
+
+/// Ways of clearing the modules behind the icon.
+/// Cleared modules are left out instead of being drawn under the icon,
+/// so that no module is left partly covered.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+#[cfg_attr(feature = "batch", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "batch", serde(rename_all = "kebab-case"))]
+pub enum Excavation {
+    /// Draw the icon over the modules.
+    #[default]
+    None,
+    /// Clear the modules under the bounding box of the icon.
+    Box,
+    /// Clear the modules under a circle around the icon, for round logos.
+    Circle,
+    /// Clear the modules under a rounded rectangle around the icon.
+    Rounded,
+}
+
+/// Shapes the icon can be clipped to.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+#[cfg_attr(feature = "batch", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "batch", serde(rename_all = "kebab-case"))]
+pub enum IconShape {
+    /// Draw the whole icon.
+    #[default]
+    Square,
+    /// Round the corners of the icon by the icon radius.
+    Rounded,
+    /// Clip the icon to the largest circle that fits in it.
+    Circle,
+}
+
+/// Offset of the icon from the center of the symbol, in modules.
+/// Parsed from "X,Y", where positive values move the icon right and down.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "batch", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "batch", serde(try_from = "String", into = "String"))]
+pub struct IconOffset {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl FromStr for IconOffset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |value: &str| {
+            value
+                .trim()
+                .parse::<f32>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .ok_or_else(|| format!("Invalid icon offset: {}, expected X,Y in modules", s))
+        };
+        let (x, y) = s
+            .split_once(',')
+            .ok_or_else(|| format!("Invalid icon offset: {}, expected X,Y in modules", s))?;
+        Ok(IconOffset {
+            x: parse(x)?,
+            y: parse(y)?,
+        })
+    }
+}
+
+impl fmt::Display for IconOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+impl TryFrom<String> for IconOffset {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<IconOffset> for String {
+    fn from(value: IconOffset) -> Self {
+        value.to_string()
+    }
+}
+
+/// An RGB color with an alpha channel.
+/// Parsed from a hex color like "#RGB", "#RGBA", "#RRGGBB" or "#RRGGBBAA" (the '#' can be
+/// left out), "rgb()", "rgba()", "hsl()" or "hsla()" in CSS syntax, a CSS color name,
+/// or "transparent".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "batch", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "batch", serde(try_from = "String", into = "String"))]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    /// Opacity, from 0 for fully transparent to 255 for opaque.
+    pub a: u8,
+}
+
+impl Color {
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+    pub const WHITE: Color = Color::rgb(255, 255, 255);
+    pub const TRANSPARENT: Color = Color::rgba(0, 0, 0, 0);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
+    }
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    pub fn is_opaque(self) -> bool {
+        self.a == 255
+    }
+
+    pub fn is_transparent(self) -> bool {
+        self.a == 0
+    }
+
+    /// Opacity, from 0 to 1.
+    pub fn opacity(self) -> f32 {
+        self.a as f32 / 255.0
+    }
+
+    /// Red, green and blue components, from 0 to 1.
+    pub fn rgb_f32(self) -> (f32, f32, f32) {
+        (
+            self.r as f32 / 255.0,
+            self.g as f32 / 255.0,
+            self.b as f32 / 255.0,
+        )
+    }
+
+    /// The color as "#RRGGBB", leaving out the alpha channel for formats
+    /// that take the opacity apart.
+    pub fn to_rgb_hex(self) -> String {
+        format!("#{:02X}{:02X}{:02X}", self.r, self.g, self.b)
+    }
+
+    /// The opaque color seen when this color is painted over the given one,
+    /// which is itself seen over white if it can be seen through.
+    pub fn over(self, backdrop: Color) -> Color {
+        let backdrop = if backdrop.is_opaque() {
+            backdrop
+        } else {
+            backdrop.over(Color::WHITE)
+        };
+        let blend = |top: u8, bottom: u8| {
+            ((top as u32 * self.a as u32 + bottom as u32 * (255 - self.a as u32) + 127) / 255) as u8
+        };
+        Color::rgb(
+            blend(self.r, backdrop.r),
+            blend(self.g, backdrop.g),
+            blend(self.b, backdrop.b),
+        )
+    }
+
+    fn from_hex(hex: &str) -> Option<Self> {
+        if !hex.is_ascii() {
+            return None;
+        }
+        let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok().map(|d| d * 17);
+        let pair = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        match hex.len() {
+            3 => Some(Color::rgb(digit(0)?, digit(1)?, digit(2)?)),
+            4 => Some(Color::rgba(digit(0)?, digit(1)?, digit(2)?, digit(3)?)),
+            6 => Some(Color::rgb(pair(0)?, pair(2)?, pair(4)?)),
+            8 => Some(Color::rgba(pair(0)?, pair(2)?, pair(4)?, pair(6)?)),
+            _ => None,
+        }
+    }
+
+    /// Parses the arguments of "rgb()", "rgba()", "hsl()" or "hsla()", separated by commas
+    /// or by spaces with the alpha after a slash.
+    fn from_function(name: &str, args: &str) -> Option<Self> {
+        let args: Vec<&str> = args
+            .split([',', ' ', '/'])
+            .filter(|arg| !arg.is_empty())
+            .collect();
+        if args.len() != 3 && args.len() != 4 {
+            return None;
+        }
+        // A number, or a percentage of the given maximum
+        let number = |arg: &str, max: f32| -> Option<f32> {
+            let value = match arg.strip_suffix('%') {
+                Some(percent) => percent.parse::<f32>().ok()? / 100.0 * max,
+                None => arg.parse::<f32>().ok()?,
+            };
+            value.is_finite().then_some(value)
+        };
+        let alpha = match args.get(3) {
+            Some(arg) => (number(arg, 1.0)?.clamp(0.0, 1.0) * 255.0).round() as u8,
+            None => 255,
+        };
+        let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        match name {
+            "rgb" | "rgba" => Some(Color::rgba(
+                channel(number(args[0], 255.0)? / 255.0),
+                channel(number(args[1], 255.0)? / 255.0),
+                channel(number(args[2], 255.0)? / 255.0),
+                alpha,
+            )),
+            "hsl" | "hsla" => {
+                let hue = args[0].strip_suffix("deg").unwrap_or(args[0]);
+                let hue = hue.parse::<f32>().ok().filter(|h| h.is_finite())?;
+                let saturation = (number(args[1], 100.0)? / 100.0).clamp(0.0, 1.0);
+                let lightness = (number(args[2], 100.0)? / 100.0).clamp(0.0, 1.0);
+                // As in the CSS Color specification
+                let k = |n: f32| (n + hue / 30.0).rem_euclid(12.0);
+                let a = saturation * lightness.min(1.0 - lightness);
+                let f = |n: f32| lightness - a * (k(n) - 3.0).min(9.0 - k(n)).clamp(-1.0, 1.0);
+                Some(Color::rgba(
+                    channel(f(0.0)),
+                    channel(f(8.0)),
+                    channel(f(4.0)),
+                    alpha,
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Color::BLACK
+    }
+}
+
+impl FromStr for Color {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s.trim().to_lowercase();
+        let color = if value == "transparent" {
+            Some(Color::TRANSPARENT)
+        } else if let Some(hex) = value.strip_prefix('#') {
+            Color::from_hex(hex)
+        } else if let Some((name, args)) = value.split_once('(') {
+            args.strip_suffix(')')
+                .and_then(|args| Color::from_function(name.trim(), args))
+        } else if let Some(&(_, rgb)) = NAMED_COLORS.iter().find(|(name, _)| *name == value) {
+            Some(Color::rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8))
+        } else {
+            Color::from_hex(&value)
+        };
+        color.ok_or_else(|| {
+            format!(
+                "Invalid color: {}, expected a hex color like #RRGGBB or #RRGGBBAA, \
+                 rgb(), rgba(), hsl(), hsla(), a CSS color name or transparent",
+                s
+            )
+        })
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_rgb_hex())?;
+        if !self.is_opaque() {
+            write!(f, "{:02X}", self.a)?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Color> for String {
+    fn from(value: Color) -> Self {
+        value.to_string()
+    }
+}
+
+/// A foreground color, with the position of its stop along a gradient if it is given.
+/// Parsed from a color, optionally followed by '@' and the position from 0 to 1
+/// or as a percentage, like "#FF0000@0.3".
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "batch", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "batch", serde(try_from = "String", into = "String"))]
+pub struct ColorStop {
+    pub color: Color,
+    pub offset: Option<f32>,
+}
+
+impl From<Color> for ColorStop {
+    fn from(color: Color) -> Self {
+        Self {
+            color,
+            offset: None,
+        }
+    }
+}
+
+impl FromStr for ColorStop {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((color, offset)) = s.rsplit_once('@') else {
+            return Ok(s.parse::<Color>()?.into());
+        };
+        let offset = offset.trim();
+        let parsed = match offset.strip_suffix('%') {
+            Some(percent) => percent.trim().parse::<f32>().map(|p| p / 100.0),
+            None => offset.parse::<f32>(),
+        };
+        let offset = parsed
+            .ok()
+            .filter(|o| o.is_finite())
+            .ok_or_else(|| format!("Invalid gradient stop position: {}", s))?;
+        Ok(ColorStop {
+            color: color.parse()?,
+            offset: Some(offset),
+        })
+    }
+}
+
+impl fmt::Display for ColorStop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.color)?;
+        if let Some(offset) = self.offset {
+            write!(f, "@{}", offset)?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<String> for ColorStop {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<ColorStop> for String {
+    fn from(value: ColorStop) -> Self {
+        value.to_string()
+    }
+}
+
+/// QR code gradient directions.
+/// These directions determine how color gradients are applied to the QR code.
+/// Parsed from one of the named directions, "radial", or the angle of a linear
+/// gradient in degrees like "30" or "30deg".
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "batch", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "batch", serde(try_from = "String", into = "String"))]
 pub enum GradientDirection {
     TopToBottom,
     LeftToRight,
+    #[default]
     TopLeftToBottomRight,
     BottomLeftToTopRight,
     Radial,
+    /// A linear gradient at the given angle in degrees, going clockwise from pointing up
+    /// like in CSS: 90 runs from left to right, 180 from top to bottom.
+    Angle(f32),
+}
+
+impl GradientDirection {
+    /// Angle of a linear gradient in degrees, or `None` for a radial one.
+    pub fn angle(self) -> Option<f32> {
+        match self {
+            GradientDirection::TopToBottom => Some(180.0),
+            GradientDirection::LeftToRight => Some(90.0),
+            GradientDirection::TopLeftToBottomRight => Some(135.0),
+            GradientDirection::BottomLeftToTopRight => Some(45.0),
+            GradientDirection::Radial => None,
+            GradientDirection::Angle(angle) => Some(angle),
+        }
+    }
+}
+
+impl FromStr for GradientDirection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "top-to-bottom" => Ok(GradientDirection::TopToBottom),
+            "left-to-right" => Ok(GradientDirection::LeftToRight),
+            "top-left-to-bottom-right" => Ok(GradientDirection::TopLeftToBottomRight),
+            "bottom-left-to-top-right" => Ok(GradientDirection::BottomLeftToTopRight),
+            "radial" => Ok(GradientDirection::Radial),
+            other => other
+                .strip_suffix("deg")
+                .unwrap_or(other)
+                .trim()
+                .parse::<f32>()
+                .ok()
+                .filter(|angle| angle.is_finite())
+                .map(GradientDirection::Angle)
+                .ok_or_else(|| {
+                    format!(
+                        "Invalid gradient direction: {}, expected a direction like \
+                         top-to-bottom, radial or an angle in degrees",
+                        s
+                    )
+                }),
+        }
+    }
+}
+
+impl fmt::Display for GradientDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GradientDirection::TopToBottom => write!(f, "top-to-bottom"),
+            GradientDirection::LeftToRight => write!(f, "left-to-right"),
+            GradientDirection::TopLeftToBottomRight => write!(f, "top-left-to-bottom-right"),
+            GradientDirection::BottomLeftToTopRight => write!(f, "bottom-left-to-top-right"),
+            GradientDirection::Radial => write!(f, "radial"),
+            GradientDirection::Angle(angle) => write!(f, "{}deg", angle),
+        }
+    }
+}
+
+impl TryFrom<String> for GradientDirection {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<GradientDirection> for String {
+    fn from(value: GradientDirection) -> Self {
+        value.to_string()
+    }
+}
+
+/// Areas a gradient can be spread over.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+#[cfg_attr(feature = "batch", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "batch", serde(rename_all = "kebab-case"))]
+pub enum GradientSpan {
+    /// The whole image, quiet zone included.
+    Canvas,
+    /// The symbol, without the quiet zone.
+    #[default]
+    Symbol,
+    /// Every module on its own, repeating the gradient in each of them.
+    Module,
+}
+
+/// Center of a radial gradient, as shares of the width and height of the area it spans.
+/// Parsed from "X,Y", where "0,0" is the top left corner and "0.5,0.5" the center.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "batch", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "batch", serde(try_from = "String", into = "String"))]
+pub struct GradientCenter {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Default for GradientCenter {
+    fn default() -> Self {
+        Self { x: 0.5, y: 0.5 }
+    }
+}
+
+impl FromStr for GradientCenter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |value: &str| {
+            value
+                .trim()
+                .parse::<f32>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .ok_or_else(|| format!("Invalid gradient center: {}, expected X,Y", s))
+        };
+        let (x, y) = s
+            .split_once(',')
+            .ok_or_else(|| format!("Invalid gradient center: {}, expected X,Y", s))?;
+        Ok(GradientCenter {
+            x: parse(x)?,
+            y: parse(y)?,
+        })
+    }
+}
+
+impl fmt::Display for GradientCenter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+impl TryFrom<String> for GradientCenter {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<GradientCenter> for String {
+    fn from(value: GradientCenter) -> Self {
+        value.to_string()
+    }
+}
+
+/// Barcode symbologies.
+/// These determine which kind of symbol the data is encoded into.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+#[cfg_attr(feature = "batch", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "batch", serde(rename_all = "kebab-case"))]
+pub enum Symbology {
+    /// Regular QR code (versions 1 to 40).
+    #[default]
+    Qr,
+    /// Micro QR code (versions M1 to M4), for very short data.
+    Micro,
+    /// Rectangular Micro QR code (R7x43 to R17x139), for long and narrow print areas.
+    Rmqr,
+}
+
+/// Character sets for byte mode data.
+/// The character set is declared to scanners with an ECI designator, which
+/// strict scanners need to read anything but ISO-8859-1 text correctly.
+/// Parsed from "auto", "none", "utf-8", a character set name like "iso-8859-5",
+/// or an ECI assignment value.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "batch", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "batch", serde(try_from = "String", into = "String"))]
+pub enum Charset {
+    /// UTF-8, declared only when byte mode data contains non-ASCII characters.
+    #[default]
+    Auto,
+    /// UTF-8 without a designator, for scanners that don't understand ECI.
+    None,
+    /// UTF-8, always declared (ECI 26).
+    Utf8,
+    /// The character set of the given ECI assignment value.
+    /// Text is transcoded to it when the character set is known.
+    Eci(u32),
+}
+
+impl FromStr for Charset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "auto" => Ok(Charset::Auto),
+            "none" => Ok(Charset::None),
+            "utf-8" | "utf8" => Ok(Charset::Utf8),
+            other => match other.parse::<u32>() {
+                Ok(value) if value <= eci::MAX => Ok(Charset::Eci(value)),
+                Ok(value) => Err(format!(
+                    "ECI assignment value must be at most {}, got {}",
+                    eci::MAX,
+                    value
+                )),
+                Err(_) => eci::from_label(other)
+                    .map(Charset::Eci)
+                    .ok_or_else(|| format!("Unknown character set: {}", s)),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Charset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Charset::Auto => write!(f, "auto"),
+            Charset::None => write!(f, "none"),
+            Charset::Utf8 => write!(f, "utf-8"),
+            Charset::Eci(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl TryFrom<String> for Charset {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Charset> for String {
+    fn from(value: Charset) -> Self {
+        value.to_string()
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -117,9 +926,33 @@ pub struct QrConfig {
     )]
     pub quiet_zone: u32,
 
+    /// Symbology to encode the data with.
+    /// Micro QR and rMQR codes only need a quiet zone of 2 modules.
+    #[cfg_attr(
+        feature = "cli",
+        arg(
+            long,
+            value_enum,
+            default_value_t = Symbology::Qr,
+        )
+    )]
+    pub symbology: Symbology,
+
+    /// Height of the rMQR code in modules.
+    /// Legal values are 7, 9, 11, 13, 15 and 17. If unset, the smallest symbol is chosen.
+    #[cfg_attr(
+        feature = "cli",
+        arg(
+            long,
+            value_parser = clap::value_parser!(u8).range(7..=17)
+        )
+    )]
+    pub rmqr_height: Option<u8>,
+
     /// Maximum QR code version.
     /// Limits the size of the QR code.
-    /// Legal values are 1 to 40.
+    /// Legal values are 1 to 40, or 1 to 4 for Micro QR codes.
+    /// rMQR codes are limited with the height instead.
     #[cfg_attr(
         feature = "cli",
         arg(
@@ -131,10 +964,12 @@ pub struct QrConfig {
 
     /// Error correction level.
     /// Higher levels increase redundancy but reduce data capacity.
+    /// With an icon, a higher level is used if needed to recover what the icon hides.
     #[cfg_attr(feature = "cli", arg(long, default_value = "medium",))]
     pub ecl: QrCodeEcc,
 
-    /// Mask pattern to use (0-7).
+    /// Mask pattern to use (0-7, or 0-3 for Micro QR codes).
+    /// rMQR codes always use the same mask.
     /// If not set, it will be chosen automatically.
     /// Usually you don't need to set this.
     #[cfg_attr(
@@ -146,39 +981,65 @@ pub struct QrConfig {
     )]
     pub mask: Option<u8>,
 
+    /// Character set for text encoded in byte mode.
+    /// One of "auto", "none", "utf-8", a character set name like "iso-8859-5",
+    /// or an ECI assignment value. Only regular QR codes support ECI designators.
+    #[cfg_attr(feature = "cli", arg(long, default_value = "auto"))]
+    pub charset: Charset,
+
     /// Foreground color(s). If multiple colors are provided, a gradient is created.
+    /// A color can be followed by the position of its stop along the gradient,
+    /// like "#FF0000@0.3", from 0 to 1. Stops without one are spread evenly.
     #[cfg_attr(
         feature = "cli",
         arg(
             long,
-            help = "Foreground color(s). If multiple colors are provided, a gradient is created.",
+            help = "Foreground color(s). If multiple colors are provided, a gradient is created. \
+                    Stop positions can be given like #FF0000@0.3. Colors can be hex with an \
+                    optional alpha (#RRGGBBAA), rgb(), rgba(), hsl(), hsla(), CSS names or transparent",
             default_value = "#000000",
             num_args = 1..,
         )
     )]
-    #[cfg_attr(
-        feature = "batch",
-        serde(deserialize_with = "deserialize_string_or_vec")
-    )]
-    pub foreground: Vec<String>,
+    #[cfg_attr(feature = "batch", serde(deserialize_with = "deserialize_one_or_vec"))]
+    pub foreground: Vec<ColorStop>,
 
-    /// Background color.
+    /// Background color. Can be "transparent" or have an alpha channel,
+    /// which formats without one blend with white.
     #[cfg_attr(feature = "cli", arg(long, default_value = "#FFFFFF",))]
-    pub background: String,
+    pub background: Color,
 
     /// Gradient direction.
     /// Determines the direction of the color gradient when multiple foreground colors are used.
+    /// One of the named directions, "radial", or an angle in degrees going clockwise
+    /// from pointing up, like "30".
     /// Only applicable if multiple foreground colors are specified.
     #[cfg_attr(
         feature = "cli",
         arg(
             long,
-            value_enum,
-            default_value_t = GradientDirection::TopLeftToBottomRight,
+            default_value = "top-left-to-bottom-right",
+            allow_hyphen_values = true,
         )
     )]
     pub gradient_direction: GradientDirection,
 
+    /// Center of a radial gradient, as "X,Y" shares of the area it spans.
+    #[cfg_attr(feature = "cli", arg(long, default_value = "0.5,0.5"))]
+    pub gradient_center: GradientCenter,
+
+    /// Radius of a radial gradient, as a share of half the diagonal of the area it spans.
+    /// At 1, the last color reaches the corners of the area when the gradient is centered.
+    #[cfg_attr(feature = "cli", arg(long, default_value = "1"))]
+    pub gradient_radius: f32,
+
+    /// Area the gradient is spread over.
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, value_enum, default_value_t = GradientSpan::Symbol)
+    )]
+    pub gradient_span: GradientSpan,
+
     /// Pixels per module.
     /// Determines the size of each module (square) in the QR code in pixels.
     #[cfg_attr(
@@ -210,7 +1071,7 @@ pub struct QrConfig {
     pub shape: ModuleShape,
 
     /// Finder pattern shape.
-    /// Determines how the position detection patterns ("eyes") of the QR code are rendered.
+    /// Determines how the outer ring of the position detection patterns is rendered.
     #[cfg_attr(
         feature = "cli",
         arg(long,
@@ -220,11 +1081,123 @@ pub struct QrConfig {
     )]
     pub finder: FinderShape,
 
+    /// Shape of the center ("eye") of the finder patterns.
+    /// If not set, it matches the finder shape.
+    #[cfg_attr(feature = "cli", arg(long, value_enum))]
+    pub finder_eye: Option<EyeShape>,
+
+    /// Color of the outer ring of the finder patterns.
+    /// If not set, it is painted like the other modules.
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub finder_color: Option<Color>,
+
+    /// Color of the center of the finder patterns.
+    /// If not set, it is painted like the other modules.
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub finder_eye_color: Option<Color>,
+
+    /// Color(s) of the finder patterns, painted apart from the data modules.
+    /// If multiple colors are provided, a gradient is created, laid out like the foreground one.
+    /// If not set, the finder patterns are painted like the other modules.
+    /// The ring and eye colors, also of single corners, take precedence.
+    #[cfg_attr(feature = "cli", arg(long, num_args = 1..))]
+    #[cfg_attr(feature = "batch", serde(deserialize_with = "deserialize_one_or_vec"))]
+    pub finder_foreground: Vec<ColorStop>,
+
+    /// Direction of the gradient of the finder patterns.
+    /// If not set, it matches the gradient direction of the other modules.
+    #[cfg_attr(feature = "cli", arg(long, allow_hyphen_values = true))]
+    pub finder_gradient_direction: Option<GradientDirection>,
+
+    /// Style of the top left finder pattern, as "key=value" pairs like "shape=leaf,rotate=90".
+    /// Keys are shape, eye, color, eye-color and rotate (clockwise, in degrees).
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub finder_top_left: Option<FinderStyle>,
+
+    /// Style of the top right finder pattern, with the same keys as the top left one.
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub finder_top_right: Option<FinderStyle>,
+
+    /// Style of the bottom left finder pattern, with the same keys as the top left one.
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub finder_bottom_left: Option<FinderStyle>,
+
+    /// Style of the bottom right sub-finder of rMQR codes, with the same keys as the top left one.
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub finder_bottom_right: Option<FinderStyle>,
+
+    /// Alignment pattern shape.
+    /// Unless it is `modules`, the timing patterns are drawn with square modules too,
+    /// keeping the patterns scanners rely on solid with decorative module shapes.
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, value_enum, default_value_t = AlignmentShape::Modules)
+    )]
+    pub alignment: AlignmentShape,
+
+    /// Custom shape of the modules, drawn instead of `shape` if set.
+    /// Timing patterns kept square along with whole alignment patterns stay square.
+    #[cfg_attr(feature = "cli", arg(skip))]
+    #[cfg_attr(feature = "batch", serde(skip))]
+    pub module_provider: Option<Arc<dyn ModuleShapeProvider>>,
+
+    /// Custom shape of the finder patterns, drawn instead of `finder` and `finder_eye` if set.
+    /// Shapes set for a single corner still take precedence.
+    #[cfg_attr(feature = "cli", arg(skip))]
+    #[cfg_attr(feature = "batch", serde(skip))]
+    pub finder_provider: Option<Arc<dyn FinderShapeProvider>>,
+
     /// Path to an icon image to embed in the QR code.
-    /// The icon will be placed at the center of the QR code.
+    /// The icon will be placed at the center of the QR code. Only regular QR codes can have one.
     #[cfg_attr(feature = "cli", arg(long, short,))]
     pub icon: Option<String>,
 
+    /// Size of the icon, as a share of the symbol's shorter side (0.05-0.5).
+    /// The longer side of the icon gets this size.
+    #[cfg_attr(feature = "cli", arg(long, default_value = "0.25"))]
+    pub icon_size: f32,
+
+    /// Shape the icon is clipped to.
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, value_enum, default_value_t = IconShape::Square)
+    )]
+    pub icon_shape: IconShape,
+
+    /// Corner radius of the rounded icon shape, in modules.
+    #[cfg_attr(feature = "cli", arg(long, default_value = "1"))]
+    pub icon_radius: f32,
+
+    /// Offset of the icon from the center of the symbol, as "X,Y" in modules.
+    /// Positive values move the icon right and down.
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, default_value = "0,0", allow_hyphen_values = true)
+    )]
+    pub icon_offset: IconOffset,
+
+    /// Color of a border drawn around the icon, on the edge of its padding.
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub icon_border: Option<Color>,
+
+    /// How to clear the modules behind the icon.
+    /// Modules partly covered by the icon are removed along with the hidden ones.
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, value_enum, default_value_t = Excavation::None)
+    )]
+    pub excavate: Excavation,
+
+    /// Space around the icon, in modules.
+    /// Widens the cleared area, the plate and the border.
+    #[cfg_attr(feature = "cli", arg(long, default_value = "1"))]
+    pub icon_padding: f32,
+
+    /// Whether to draw a plate in the background color behind the icon.
+    /// The plate takes the shape of the cleared area, or of the padded box around the icon.
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub icon_plate: bool,
+
     /// Image data to embed in the QR code.
     /// If provided, this takes precedence over `icon`.
     #[cfg_attr(feature = "cli", arg(skip))]
@@ -243,61 +1216,323 @@ pub struct QrConfig {
     pub format: OutputFormat,
 }
 
-impl Default for ModuleShape {
-    fn default() -> Self {
-        Self::Square
-    }
-}
-
-impl Default for FinderShape {
-    fn default() -> Self {
-        Self::Square
-    }
-}
-
-impl Default for GradientDirection {
-    fn default() -> Self {
-        Self::TopLeftToBottomRight
-    }
-}
-
 impl Default for QrConfig {
     fn default() -> Self {
         Self {
             quiet_zone: 4,
+            symbology: Symbology::default(),
+            rmqr_height: None,
             max_version: None,
             ecl: QrCodeEcc::Medium,
             mask: None,
-            foreground: vec!["#000000".to_string()],
-            background: "#FFFFFF".to_string(),
+            charset: Charset::default(),
+            foreground: vec![Color::BLACK.into()],
+            background: Color::WHITE,
             gradient_direction: GradientDirection::default(),
+            gradient_center: GradientCenter::default(),
+            gradient_radius: 1.0,
+            gradient_span: GradientSpan::default(),
             ppm: 20,
             boost_error_correction: true,
             shape: ModuleShape::default(),
             finder: FinderShape::default(),
+            finder_eye: None,
+            finder_color: None,
+            finder_eye_color: None,
+            finder_foreground: Vec::new(),
+            finder_gradient_direction: None,
+            finder_top_left: None,
+            finder_top_right: None,
+            finder_bottom_left: None,
+            finder_bottom_right: None,
+            alignment: AlignmentShape::default(),
+            module_provider: None,
+            finder_provider: None,
             icon: None,
+            icon_size: 0.25,
+            icon_shape: IconShape::default(),
+            icon_radius: 1.0,
+            icon_offset: IconOffset::default(),
+            icon_border: None,
+            excavate: Excavation::default(),
+            icon_padding: 1.0,
+            icon_plate: false,
             image: None,
             format: OutputFormat::Png,
         }
     }
 }
 
+/// Deserializes a single value or a list of them. The value is parsed as it is, so that
+/// its own error is reported rather than a generic mismatch of an untagged enum.
 #[cfg(feature = "batch")]
-fn deserialize_string_or_vec<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+fn deserialize_one_or_vec<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
 where
     D: serde::Deserializer<'de>,
+    T: serde::Deserialize<'de>,
 {
     use serde::Deserialize;
+    use serde::de::{self, IntoDeserializer, value};
+    use std::marker::PhantomData;
+
+    struct OneOrVec<T>(PhantomData<T>);
+
+    impl<'de, T: serde::Deserialize<'de>> de::Visitor<'de> for OneOrVec<T> {
+        type Value = Vec<T>;
 
-    #[derive(Deserialize)]
-    #[serde(untagged)]
-    enum StringOrVec {
-        String(String),
-        Vec(Vec<String>),
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a value or a list of values")
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            T::deserialize(v.into_deserializer()).map(|one| vec![one])
+        }
+
+        fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+            T::deserialize(value::MapAccessDeserializer::new(map)).map(|one| vec![one])
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+            Vec::deserialize(value::SeqAccessDeserializer::new(seq))
+        }
+    }
+
+    deserializer.deserialize_any(OneOrVec(PhantomData))
+}
+
+/// The named colors of CSS, with their RGB values.
+const NAMED_COLORS: &[(&str, u32)] = &[
+    ("aliceblue", 0xF0F8FF),
+    ("antiquewhite", 0xFAEBD7),
+    ("aqua", 0x00FFFF),
+    ("aquamarine", 0x7FFFD4),
+    ("azure", 0xF0FFFF),
+    ("beige", 0xF5F5DC),
+    ("bisque", 0xFFE4C4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xFFEBCD),
+    ("blue", 0x0000FF),
+    ("blueviolet", 0x8A2BE2),
+    ("brown", 0xA52A2A),
+    ("burlywood", 0xDEB887),
+    ("cadetblue", 0x5F9EA0),
+    ("chartreuse", 0x7FFF00),
+    ("chocolate", 0xD2691E),
+    ("coral", 0xFF7F50),
+    ("cornflowerblue", 0x6495ED),
+    ("cornsilk", 0xFFF8DC),
+    ("crimson", 0xDC143C),
+    ("cyan", 0x00FFFF),
+    ("darkblue", 0x00008B),
+    ("darkcyan", 0x008B8B),
+    ("darkgoldenrod", 0xB8860B),
+    ("darkgray", 0xA9A9A9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xA9A9A9),
+    ("darkkhaki", 0xBDB76B),
+    ("darkmagenta", 0x8B008B),
+    ("darkolivegreen", 0x556B2F),
+    ("darkorange", 0xFF8C00),
+    ("darkorchid", 0x9932CC),
+    ("darkred", 0x8B0000),
+    ("darksalmon", 0xE9967A),
+    ("darkseagreen", 0x8FBC8F),
+    ("darkslateblue", 0x483D8B),
+    ("darkslategray", 0x2F4F4F),
+    ("darkslategrey", 0x2F4F4F),
+    ("darkturquoise", 0x00CED1),
+    ("darkviolet", 0x9400D3),
+    ("deeppink", 0xFF1493),
+    ("deepskyblue", 0x00BFFF),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1E90FF),
+    ("firebrick", 0xB22222),
+    ("floralwhite", 0xFFFAF0),
+    ("forestgreen", 0x228B22),
+    ("fuchsia", 0xFF00FF),
+    ("gainsboro", 0xDCDCDC),
+    ("ghostwhite", 0xF8F8FF),
+    ("gold", 0xFFD700),
+    ("goldenrod", 0xDAA520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xADFF2F),
+    ("grey", 0x808080),
+    ("honeydew", 0xF0FFF0),
+    ("hotpink", 0xFF69B4),
+    ("indianred", 0xCD5C5C),
+    ("indigo", 0x4B0082),
+    ("ivory", 0xFFFFF0),
+    ("khaki", 0xF0E68C),
+    ("lavender", 0xE6E6FA),
+    ("lavenderblush", 0xFFF0F5),
+    ("lawngreen", 0x7CFC00),
+    ("lemonchiffon", 0xFFFACD),
+    ("lightblue", 0xADD8E6),
+    ("lightcoral", 0xF08080),
+    ("lightcyan", 0xE0FFFF),
+    ("lightgoldenrodyellow", 0xFAFAD2),
+    ("lightgray", 0xD3D3D3),
+    ("lightgreen", 0x90EE90),
+    ("lightgrey", 0xD3D3D3),
+    ("lightpink", 0xFFB6C1),
+    ("lightsalmon", 0xFFA07A),
+    ("lightseagreen", 0x20B2AA),
+    ("lightskyblue", 0x87CEFA),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xB0C4DE),
+    ("lightyellow", 0xFFFFE0),
+    ("lime", 0x00FF00),
+    ("limegreen", 0x32CD32),
+    ("linen", 0xFAF0E6),
+    ("magenta", 0xFF00FF),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66CDAA),
+    ("mediumblue", 0x0000CD),
+    ("mediumorchid", 0xBA55D3),
+    ("mediumpurple", 0x9370DB),
+    ("mediumseagreen", 0x3CB371),
+    ("mediumslateblue", 0x7B68EE),
+    ("mediumspringgreen", 0x00FA9A),
+    ("mediumturquoise", 0x48D1CC),
+    ("mediumvioletred", 0xC71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xF5FFFA),
+    ("mistyrose", 0xFFE4E1),
+    ("moccasin", 0xFFE4B5),
+    ("navajowhite", 0xFFDEAD),
+    ("navy", 0x000080),
+    ("oldlace", 0xFDF5E6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6B8E23),
+    ("orange", 0xFFA500),
+    ("orangered", 0xFF4500),
+    ("orchid", 0xDA70D6),
+    ("palegoldenrod", 0xEEE8AA),
+    ("palegreen", 0x98FB98),
+    ("paleturquoise", 0xAFEEEE),
+    ("palevioletred", 0xDB7093),
+    ("papayawhip", 0xFFEFD5),
+    ("peachpuff", 0xFFDAB9),
+    ("peru", 0xCD853F),
+    ("pink", 0xFFC0CB),
+    ("plum", 0xDDA0DD),
+    ("powderblue", 0xB0E0E6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xFF0000),
+    ("rosybrown", 0xBC8F8F),
+    ("royalblue", 0x4169E1),
+    ("saddlebrown", 0x8B4513),
+    ("salmon", 0xFA8072),
+    ("sandybrown", 0xF4A460),
+    ("seagreen", 0x2E8B57),
+    ("seashell", 0xFFF5EE),
+    ("sienna", 0xA0522D),
+    ("silver", 0xC0C0C0),
+    ("skyblue", 0x87CEEB),
+    ("slateblue", 0x6A5ACD),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xFFFAFA),
+    ("springgreen", 0x00FF7F),
+    ("steelblue", 0x4682B4),
+    ("tan", 0xD2B48C),
+    ("teal", 0x008080),
+    ("thistle", 0xD8BFD8),
+    ("tomato", 0xFF6347),
+    ("turquoise", 0x40E0D0),
+    ("violet", 0xEE82EE),
+    ("wheat", 0xF5DEB3),
+    ("white", 0xFFFFFF),
+    ("whitesmoke", 0xF5F5F5),
+    ("yellow", 0xFFFF00),
+    ("yellowgreen", 0x9ACD32),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_colors() {
+        let cases = [
+            ("#F00", Color::rgb(255, 0, 0)),
+            ("#f008", Color::rgba(255, 0, 0, 0x88)),
+            ("#1E90FF", Color::rgb(0x1E, 0x90, 0xFF)),
+            ("#1E90FF80", Color::rgba(0x1E, 0x90, 0xFF, 0x80)),
+            ("1e90ff", Color::rgb(0x1E, 0x90, 0xFF)),
+            (" DodgerBlue ", Color::rgb(0x1E, 0x90, 0xFF)),
+            ("transparent", Color::TRANSPARENT),
+            ("rgb(30, 144, 255)", Color::rgb(30, 144, 255)),
+            ("rgba(30, 144, 255, 0.5)", Color::rgba(30, 144, 255, 128)),
+            ("rgb(100% 0% 50% / 25%)", Color::rgba(255, 0, 128, 64)),
+            ("rgb(300, -5, 0)", Color::rgb(255, 0, 0)),
+            ("hsl(120, 100%, 25%)", Color::rgb(0, 128, 0)),
+            ("hsl(-120deg 100% 50%)", Color::rgb(0, 0, 255)),
+            ("hsla(0 0% 100% / 0)", Color::rgba(255, 255, 255, 0)),
+        ];
+        for (text, color) in cases {
+            assert_eq!(text.parse::<Color>(), Ok(color), "{}", text);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_colors() {
+        for text in [
+            "",
+            "nope",
+            "#12",
+            "#12345",
+            "#GGGGGG",
+            "#ÀÀÀ",
+            "rgb(1, 2)",
+            "rgb(1, 2, 3",
+            "hsl(x, 1%, 1%)",
+            "cmyk(0, 0, 0, 0)",
+        ] {
+            let error = text.parse::<Color>().unwrap_err();
+            assert!(
+                error.starts_with(&format!("Invalid color: {},", text)),
+                "{}",
+                error
+            );
+        }
     }
 
-    match StringOrVec::deserialize(deserializer)? {
-        StringOrVec::String(s) => Ok(vec![s]),
-        StringOrVec::Vec(v) => Ok(v),
+    #[test]
+    fn parses_color_stops() {
+        let stop: ColorStop = "red@30%".parse().unwrap();
+        assert_eq!(stop.color, Color::rgb(255, 0, 0));
+        assert_eq!(stop.offset, Some(0.3));
+        let stop: ColorStop = "rgb(0 0 255)@0.5".parse().unwrap();
+        assert_eq!(stop.offset, Some(0.5));
+        assert_eq!("blue".parse::<ColorStop>().unwrap().offset, None);
+        assert!("blue@x".parse::<ColorStop>().is_err());
+    }
+
+    #[cfg(feature = "batch")]
+    #[test]
+    fn deserializes_one_or_many_colors() {
+        let config: QrConfig = serde_json::from_str(r##"{"foreground": "#FF0000"}"##).unwrap();
+        assert_eq!(config.foreground, vec![Color::rgb(255, 0, 0).into()]);
+
+        let config: QrConfig =
+            serde_json::from_str(r##"{"foreground": ["red", "blue@100%"]}"##).unwrap();
+        assert_eq!(config.foreground.len(), 2);
+
+        for json in [
+            r#"{"foreground": "nope"}"#,
+            r#"{"foreground": ["red", "nope"]}"#,
+        ] {
+            let error = serde_json::from_str::<QrConfig>(json).unwrap_err();
+            assert!(
+                error.to_string().starts_with("Invalid color: nope"),
+                "{}",
+                error
+            );
+        }
     }
 }
@@ -1,5 +1,6 @@
 use super::{QrConfig, QrItem};
 use std::fmt;
+use std::str::FromStr;
 
 #[cfg(feature = "cli")]
 use clap::Args;
@@ -57,6 +58,47 @@ impl fmt::Display for EmailQr {
     }
 }
 
+impl FromStr for EmailQr {
+    type Err = String;
+
+    /// Parses a `mailto:` URI, with the subject, body, cc and bcc fields of its query.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rest = s
+            .get(..7)
+            .filter(|scheme| scheme.eq_ignore_ascii_case("mailto:"))
+            .map(|_| &s[7..])
+            .ok_or("Not a mailto URI")?;
+        let (to, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+        let decode = |value: &str| {
+            urlencoding::decode(value)
+                .map(|decoded| decoded.into_owned())
+                .map_err(|e| format!("Invalid mailto URI: {}", e))
+        };
+
+        let mut email = EmailQr {
+            to: decode(to)?,
+            subject: None,
+            body: None,
+            cc: None,
+            bcc: None,
+            config: QrConfig::default(),
+        };
+        for pair in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = Some(decode(value)?);
+            match key.to_ascii_lowercase().as_str() {
+                "subject" => email.subject = value,
+                "body" => email.body = value,
+                "cc" => email.cc = value,
+                "bcc" => email.bcc = value,
+                _ => {}
+            }
+        }
+        Ok(email)
+    }
+}
+
 impl QrItem for EmailQr {
     fn config(&self) -> &QrConfig {
         &self.config
@@ -0,0 +1,522 @@
+use super::{QrConfig, QrItem};
+use std::fmt;
+
+#[cfg(feature = "cli")]
+use clap::Args;
+
+/// ASCII group separator, which ends variable-length fields in a GS1 element string.
+const GROUP_SEPARATOR: char = '\u{1D}';
+
+/// Represents the data needed to generate a GS1 QR code.
+/// The data is given in the bracketed human readable form, such as
+/// `(01)09506000134352(17)261231(10)ABC123`, and encoded in FNC1 mode.
+#[derive(Debug)]
+#[cfg_attr(feature = "cli", derive(Args))]
+#[cfg_attr(feature = "batch", derive(serde::Serialize, serde::Deserialize))]
+pub struct Gs1Qr {
+    #[cfg_attr(
+        feature = "cli",
+        arg(help = "GS1 data with bracketed AIs, e.g. \"(01)09506000134352(17)261231(10)ABC123\"")
+    )]
+    pub data: String,
+
+    #[cfg_attr(feature = "cli", command(flatten))]
+    #[cfg_attr(feature = "batch", serde(flatten))]
+    pub config: QrConfig,
+}
+
+impl Gs1Qr {
+    /// Parses and validates the data, returning its Application Identifiers and values in order.
+    pub fn elements(&self) -> Result<Vec<(String, String)>, String> {
+        let elements = parse_bracketed(&self.data)?;
+        for (ai, value) in &elements {
+            validate_element(ai, value)?;
+        }
+        Ok(elements)
+    }
+
+    /// Returns the element string to encode: the AIs and values concatenated, with a group
+    /// separator after each variable-length field except the last one.
+    pub fn element_string(&self) -> Result<String, String> {
+        let elements = self.elements()?;
+        let mut result = String::new();
+        for (i, (ai, value)) in elements.iter().enumerate() {
+            result.push_str(ai);
+            result.push_str(value);
+            if i + 1 < elements.len() && !is_predefined_length(ai) {
+                result.push(GROUP_SEPARATOR);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Reads an element string, as decoded from a symbol in FNC1 mode, back into the
+    /// bracketed form. Fields of predefined length are cut at their length, and the
+    /// others run up to the next group separator.
+    pub fn from_element_string(element_string: &str) -> Result<Self, String> {
+        let mut data = String::new();
+        let mut rest = element_string;
+        while !rest.is_empty() {
+            let format = AI_FORMATS
+                .iter()
+                .find(|f| {
+                    rest.get(..f.ai_len).is_some_and(|ai| {
+                        ai.starts_with(f.prefix) && ai.bytes().all(|b| b.is_ascii_digit())
+                    })
+                })
+                .ok_or_else(|| format!("Unknown GS1 Application Identifier at \"{}\"", rest))?;
+            let (ai, after_ai) = rest.split_at(format.ai_len);
+
+            let end = if is_predefined_length(ai) {
+                format.max.min(after_ai.len())
+            } else {
+                after_ai.find(GROUP_SEPARATOR).unwrap_or(after_ai.len())
+            };
+            let value = after_ai
+                .get(..end)
+                .ok_or_else(|| format!("Invalid value for AI ({})", ai))?;
+            data.push_str(&format!("({}){}", ai, value));
+
+            rest = &after_ai[end..];
+            rest = rest.strip_prefix(GROUP_SEPARATOR).unwrap_or(rest);
+        }
+
+        let gs1 = Gs1Qr {
+            data,
+            config: QrConfig::default(),
+        };
+        gs1.elements()?;
+        Ok(gs1)
+    }
+}
+
+impl fmt::Display for Gs1Qr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Invalid data is rejected by QrItem::validate before encoding
+        write!(f, "{}", self.element_string().unwrap_or_default())
+    }
+}
+
+impl QrItem for Gs1Qr {
+    fn config(&self) -> &QrConfig {
+        &self.config
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        self.elements().map(|_| ())
+    }
+
+    fn is_gs1(&self) -> bool {
+        true
+    }
+}
+
+/// Splits `(AI)value(AI)value...` into its AIs and values. Values may contain
+/// parentheses, so only a "(" followed by digits and ")" starts the next AI.
+fn parse_bracketed(data: &str) -> Result<Vec<(String, String)>, String> {
+    let mut elements = Vec::new();
+    let mut rest = data.trim();
+    if rest.is_empty() {
+        return Err("GS1 data is empty".into());
+    }
+
+    while !rest.is_empty() {
+        let Some((ai, after_ai)) = bracketed_ai(rest) else {
+            return Err(format!(
+                "Expected a bracketed AI such as \"(01)\" at \"{}\"",
+                rest
+            ));
+        };
+        let end = after_ai
+            .match_indices('(')
+            .map(|(i, _)| i)
+            .find(|&i| bracketed_ai(&after_ai[i..]).is_some())
+            .unwrap_or(after_ai.len());
+        elements.push((ai.to_string(), after_ai[..end].to_string()));
+        rest = &after_ai[end..];
+    }
+
+    Ok(elements)
+}
+
+/// Returns the AI and the text after it if the text starts with `(digits)`.
+fn bracketed_ai(text: &str) -> Option<(&str, &str)> {
+    let (ai, rest) = text.strip_prefix('(')?.split_once(')')?;
+    (!ai.is_empty() && ai.bytes().all(|b| b.is_ascii_digit())).then_some((ai, rest))
+}
+
+/// Format of the data field of an Application Identifier.
+struct AiFormat {
+    /// The AI, or its leading digits when the AI has a trailing variable digit (e.g. "310" for 3100 to 3109).
+    prefix: &'static str,
+    /// Number of digits in the AI.
+    ai_len: usize,
+    /// Minimum and maximum length of the data field.
+    min: usize,
+    max: usize,
+    /// Whether the field is all digits; otherwise it uses the GS1 82-character set.
+    numeric: bool,
+    /// Number of leading digits that end with a GS1 check digit, if any.
+    check_digit: Option<usize>,
+    /// Whether the field is a YYMMDD date.
+    date: bool,
+}
+
+const fn fixed(prefix: &'static str, ai_len: usize, len: usize, check_digit: bool) -> AiFormat {
+    AiFormat {
+        prefix,
+        ai_len,
+        min: len,
+        max: len,
+        numeric: true,
+        check_digit: if check_digit { Some(len) } else { None },
+        date: false,
+    }
+}
+
+const fn date(prefix: &'static str) -> AiFormat {
+    AiFormat {
+        prefix,
+        ai_len: 2,
+        min: 6,
+        max: 6,
+        numeric: true,
+        check_digit: None,
+        date: true,
+    }
+}
+
+const fn numeric(prefix: &'static str, ai_len: usize, max: usize) -> AiFormat {
+    AiFormat {
+        prefix,
+        ai_len,
+        min: 1,
+        max,
+        numeric: true,
+        check_digit: None,
+        date: false,
+    }
+}
+
+const fn text(prefix: &'static str, ai_len: usize, max: usize) -> AiFormat {
+    AiFormat {
+        prefix,
+        ai_len,
+        min: 1,
+        max,
+        numeric: false,
+        check_digit: None,
+        date: false,
+    }
+}
+
+/// Text field starting with a number of digits that end with a check digit, like AI (253).
+const fn checked_text(prefix: &'static str, ai_len: usize, digits: usize, max: usize) -> AiFormat {
+    AiFormat {
+        prefix,
+        ai_len,
+        min: digits,
+        max,
+        numeric: false,
+        check_digit: Some(digits),
+        date: false,
+    }
+}
+
+/// Numeric field starting with a number of digits that end with a check digit, like AI (255).
+const fn checked_numeric(
+    prefix: &'static str,
+    ai_len: usize,
+    digits: usize,
+    max: usize,
+) -> AiFormat {
+    AiFormat {
+        prefix,
+        ai_len,
+        min: digits,
+        max,
+        numeric: true,
+        check_digit: Some(digits),
+        date: false,
+    }
+}
+
+/// The commonly used Application Identifiers of the GS1 General Specifications.
+static AI_FORMATS: &[AiFormat] = &[
+    fixed("00", 2, 18, true),
+    fixed("01", 2, 14, true),
+    fixed("02", 2, 14, true),
+    text("10", 2, 20),
+    date("11"),
+    date("12"),
+    date("13"),
+    date("15"),
+    date("16"),
+    date("17"),
+    fixed("20", 2, 2, false),
+    text("21", 2, 20),
+    text("22", 2, 20),
+    text("235", 3, 28),
+    text("240", 3, 30),
+    text("241", 3, 30),
+    numeric("242", 3, 6),
+    text("243", 3, 20),
+    text("250", 3, 30),
+    text("251", 3, 30),
+    checked_text("253", 3, 13, 30),
+    text("254", 3, 20),
+    checked_numeric("255", 3, 13, 25),
+    numeric("30", 2, 8),
+    // Trade and logistic measures, the last AI digit gives the decimal point position
+    fixed("310", 4, 6, false),
+    fixed("311", 4, 6, false),
+    fixed("312", 4, 6, false),
+    fixed("313", 4, 6, false),
+    fixed("314", 4, 6, false),
+    fixed("315", 4, 6, false),
+    fixed("316", 4, 6, false),
+    fixed("320", 4, 6, false),
+    fixed("321", 4, 6, false),
+    fixed("322", 4, 6, false),
+    fixed("323", 4, 6, false),
+    fixed("324", 4, 6, false),
+    fixed("325", 4, 6, false),
+    fixed("326", 4, 6, false),
+    fixed("327", 4, 6, false),
+    fixed("328", 4, 6, false),
+    fixed("329", 4, 6, false),
+    fixed("330", 4, 6, false),
+    fixed("331", 4, 6, false),
+    fixed("332", 4, 6, false),
+    fixed("333", 4, 6, false),
+    fixed("334", 4, 6, false),
+    fixed("335", 4, 6, false),
+    fixed("336", 4, 6, false),
+    fixed("337", 4, 6, false),
+    fixed("340", 4, 6, false),
+    fixed("341", 4, 6, false),
+    fixed("342", 4, 6, false),
+    fixed("343", 4, 6, false),
+    fixed("344", 4, 6, false),
+    fixed("345", 4, 6, false),
+    fixed("346", 4, 6, false),
+    fixed("347", 4, 6, false),
+    fixed("348", 4, 6, false),
+    fixed("349", 4, 6, false),
+    fixed("350", 4, 6, false),
+    fixed("351", 4, 6, false),
+    fixed("352", 4, 6, false),
+    fixed("353", 4, 6, false),
+    fixed("354", 4, 6, false),
+    fixed("355", 4, 6, false),
+    fixed("356", 4, 6, false),
+    fixed("357", 4, 6, false),
+    fixed("360", 4, 6, false),
+    fixed("361", 4, 6, false),
+    fixed("362", 4, 6, false),
+    fixed("363", 4, 6, false),
+    fixed("364", 4, 6, false),
+    fixed("365", 4, 6, false),
+    fixed("366", 4, 6, false),
+    fixed("367", 4, 6, false),
+    fixed("368", 4, 6, false),
+    fixed("369", 4, 6, false),
+    numeric("37", 2, 8),
+    numeric("390", 4, 15),
+    numeric("391", 4, 18),
+    numeric("392", 4, 15),
+    numeric("393", 4, 18),
+    text("400", 3, 30),
+    text("401", 3, 30),
+    fixed("402", 3, 17, true),
+    text("403", 3, 30),
+    fixed("410", 3, 13, true),
+    fixed("411", 3, 13, true),
+    fixed("412", 3, 13, true),
+    fixed("413", 3, 13, true),
+    fixed("414", 3, 13, true),
+    fixed("415", 3, 13, true),
+    fixed("416", 3, 13, true),
+    fixed("417", 3, 13, true),
+    text("420", 3, 20),
+    text("421", 3, 12),
+    fixed("422", 3, 3, false),
+    fixed("7003", 4, 10, false),
+    checked_text("8003", 4, 14, 30),
+    text("8004", 4, 30),
+    fixed("8005", 4, 6, false),
+    AiFormat {
+        prefix: "8006",
+        ai_len: 4,
+        min: 18,
+        max: 18,
+        numeric: true,
+        check_digit: Some(14),
+        date: false,
+    },
+    fixed("8017", 4, 18, true),
+    fixed("8018", 4, 18, true),
+    text("8020", 4, 25),
+    text("90", 2, 30),
+    text("91", 2, 90),
+    text("92", 2, 90),
+    text("93", 2, 90),
+    text("94", 2, 90),
+    text("95", 2, 90),
+    text("96", 2, 90),
+    text("97", 2, 90),
+    text("98", 2, 90),
+    text("99", 2, 90),
+];
+
+/// Leading digits of the AIs whose data fields have a predefined length, so no
+/// separator is needed after them (GS1 General Specifications, figure 7.8.5-2).
+const PREDEFINED_LENGTH_PREFIXES: [&str; 22] = [
+    "00", "01", "02", "03", "04", "11", "12", "13", "14", "15", "16", "17", "18", "19", "20", "31",
+    "32", "33", "34", "35", "36", "41",
+];
+
+/// Tests whether the AI's data field has a predefined length.
+fn is_predefined_length(ai: &str) -> bool {
+    PREDEFINED_LENGTH_PREFIXES
+        .iter()
+        .any(|prefix| ai.starts_with(prefix))
+}
+
+/// Checks that the AI is known and its value matches its format.
+fn validate_element(ai: &str, value: &str) -> Result<(), String> {
+    let format = AI_FORMATS
+        .iter()
+        .find(|f| {
+            ai.len() == f.ai_len
+                && ai.starts_with(f.prefix)
+                && ai.bytes().all(|b| b.is_ascii_digit())
+        })
+        .ok_or_else(|| format!("Unknown GS1 Application Identifier ({})", ai))?;
+
+    let len = value.len();
+    if len < format.min || len > format.max {
+        return Err(if format.min == format.max {
+            format!(
+                "AI ({}) must be {} characters long, got {}",
+                ai, format.min, len
+            )
+        } else {
+            format!(
+                "AI ({}) must be {} to {} characters long, got {}",
+                ai, format.min, format.max, len
+            )
+        });
+    }
+
+    if format.numeric {
+        if !value.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(format!("AI ({}) must only contain digits: {}", ai, value));
+        }
+    } else if let Some(c) = value.chars().find(|&c| !is_cset82(c)) {
+        return Err(format!(
+            "AI ({}) contains an invalid character: {:?}",
+            ai, c
+        ));
+    }
+
+    if let Some(digits) = format.check_digit {
+        let number = &value[..digits];
+        if !number.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(format!(
+                "AI ({}) must start with {} digits: {}",
+                ai, digits, value
+            ));
+        }
+        let expected = check_digit(&number[..digits - 1]);
+        if number.as_bytes()[digits - 1] - b'0' != expected {
+            return Err(format!(
+                "AI ({}) has an invalid check digit: {}, expected {}",
+                ai, value, expected
+            ));
+        }
+    }
+
+    if format.date {
+        let month: u32 = value[2..4].parse().unwrap_or(0);
+        let day: u32 = value[4..6].parse().unwrap_or(0);
+        // A day of 00 means the end of the month
+        if !(1..=12).contains(&month) || day > 31 {
+            return Err(format!("AI ({}) is not a valid YYMMDD date: {}", ai, value));
+        }
+    }
+
+    Ok(())
+}
+
+/// Computes the GS1 mod-10 check digit of the given digits.
+fn check_digit(digits: &str) -> u8 {
+    let sum: u32 = digits
+        .bytes()
+        .rev()
+        .enumerate()
+        .map(|(i, b)| u32::from(b - b'0') * if i % 2 == 0 { 3 } else { 1 })
+        .sum();
+    ((10 - sum % 10) % 10) as u8
+}
+
+/// Tests whether the character is in the GS1 AI encodable character set 82.
+fn is_cset82(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!\"%&'()*+,-./:;<=>?_".contains(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gs1(data: &str) -> Gs1Qr {
+        Gs1Qr {
+            data: data.into(),
+            config: QrConfig::default(),
+        }
+    }
+
+    #[test]
+    fn parentheses_in_values_do_not_start_an_ai() {
+        let elements = parse_bracketed("(10)AB(C)D(21)X(1-2)").unwrap();
+        assert_eq!(
+            elements,
+            vec![
+                ("10".to_string(), "AB(C)D".to_string()),
+                ("21".to_string(), "X(1-2)".to_string()),
+            ]
+        );
+        assert!(parse_bracketed("(AB)123").is_err());
+        assert!(parse_bracketed("()123").is_err());
+    }
+
+    #[test]
+    fn only_defined_measure_ais_are_known() {
+        assert!(validate_element("3103", "001250").is_ok());
+        assert!(validate_element("3379", "001250").is_ok());
+        assert!(validate_element("3695", "001250").is_ok());
+        for ai in ["3170", "3199", "3380", "3580"] {
+            assert!(validate_element(ai, "001250").is_err(), "({}) accepted", ai);
+        }
+    }
+
+    #[test]
+    fn ai_255_is_numeric_with_check_digit() {
+        assert!(validate_element("255", "9521234543213").is_ok());
+        assert!(validate_element("255", "95212345432130042").is_ok());
+        assert!(validate_element("255", "9521234543214").is_err());
+        assert!(validate_element("255", "9521234543213AB").is_err());
+    }
+
+    #[test]
+    fn element_string_round_trips() {
+        let qr = gs1("(01)09506000134352(10)AB(C)(3103)001250(21)XYZ");
+        let element_string = qr.element_string().unwrap();
+        assert_eq!(
+            element_string,
+            "010950600013435210AB(C)\u{1D}310300125021XYZ"
+        );
+        let decoded = Gs1Qr::from_element_string(&element_string).unwrap();
+        assert_eq!(decoded.data, qr.data);
+    }
+}
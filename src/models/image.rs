@@ -30,4 +30,19 @@ impl QrImage {
             Ok(QrImage::Raster(img))
         }
     }
+
+    /// Width divided by height of the image, if it has a size.
+    /// SVG images are measured the way resvg lays them out.
+    pub fn aspect_ratio(&self) -> Option<f64> {
+        let (width, height) = match self {
+            QrImage::Raster(img) => (f64::from(img.width()), f64::from(img.height())),
+            QrImage::Svg(content) => {
+                let tree =
+                    resvg::usvg::Tree::from_str(content, &resvg::usvg::Options::default()).ok()?;
+                let size = tree.size();
+                (f64::from(size.width()), f64::from(size.height()))
+            }
+        };
+        (width > 0.0 && height > 0.0).then(|| width / height)
+    }
 }
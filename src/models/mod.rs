@@ -1,4 +1,5 @@
 mod email;
+mod gs1;
 mod text;
 mod wifi;
 
@@ -6,15 +7,29 @@ mod config;
 mod image;
 mod wrapper;
 
+pub use config::AlignmentShape;
+pub use config::Charset;
+pub use config::Color;
+pub use config::ColorStop;
+pub use config::Excavation;
+pub use config::EyeShape;
 pub use config::FinderShape;
+pub use config::FinderStyle;
+pub use config::GradientCenter;
 pub use config::GradientDirection;
+pub use config::GradientSpan;
+pub use config::IconOffset;
+pub use config::IconShape;
 pub use config::ModuleShape;
 pub use config::OutputFormat;
 pub use config::QrConfig;
+pub use config::Symbology;
 pub use email::EmailQr;
+pub use gs1::Gs1Qr;
 pub use image::QrImage;
 pub use text::TextQr;
 pub use wifi::WifiQr;
+pub use wifi::WifiSecurity;
 pub use wrapper::QrData;
 
 /// Trait representing a QR code item that can provide its configuration
@@ -23,4 +38,15 @@ pub use wrapper::QrData;
 /// Useful if you want to add more QR code types.
 pub trait QrItem: std::fmt::Display {
     fn config(&self) -> &QrConfig;
+
+    /// Checks that the data can be encoded, for types whose input has a format to follow.
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Whether the content is a GS1 element string, to be encoded in FNC1 mode.
+    /// Group separators (ASCII 29) in the content end variable-length fields.
+    fn is_gs1(&self) -> bool {
+        false
+    }
 }
@@ -1,5 +1,6 @@
 use super::{QrConfig, QrItem};
 use std::fmt;
+use std::str::FromStr;
 
 #[cfg(feature = "cli")]
 use clap::{Args, ValueEnum};
@@ -8,6 +9,7 @@ use clap::{Args, ValueEnum};
 #[cfg_attr(feature = "cli", derive(ValueEnum))]
 #[cfg_attr(feature = "batch", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "batch", serde(rename_all = "lowercase"))]
+#[allow(clippy::upper_case_acronyms)]
 pub enum WifiSecurity {
     WPA,
     WEP,
@@ -57,6 +59,66 @@ impl fmt::Display for WifiQr {
     }
 }
 
+impl FromStr for WifiQr {
+    type Err = String;
+
+    /// Parses a `WIFI:T:...;S:...;P:...;H:...;;` payload. Fields may come in any order,
+    /// and `\`, `;`, `,`, `:` and `"` can be escaped with a backslash.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rest = s
+            .strip_prefix("WIFI:")
+            .ok_or("Not a Wi-Fi network payload")?;
+
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut chars = rest.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => field.extend(chars.next()),
+                ';' => fields.push(std::mem::take(&mut field)),
+                _ => field.push(c),
+            }
+        }
+
+        let mut wifi = WifiQr {
+            ssid: String::new(),
+            security: WifiSecurity::NoPass,
+            password: None,
+            hidden: false,
+            config: QrConfig::default(),
+        };
+        let mut has_ssid = false;
+        for field in fields.iter().filter(|f| !f.is_empty()) {
+            let (key, value) = field
+                .split_once(':')
+                .ok_or_else(|| format!("Invalid Wi-Fi field \"{}\"", field))?;
+            match key {
+                "S" => {
+                    wifi.ssid = value.to_string();
+                    has_ssid = true;
+                }
+                "T" => {
+                    wifi.security = match value.to_ascii_uppercase().as_str() {
+                        "WPA" | "WPA2" | "WPA3" => WifiSecurity::WPA,
+                        "WEP" => WifiSecurity::WEP,
+                        "NOPASS" | "" => WifiSecurity::NoPass,
+                        _ => return Err(format!("Unknown Wi-Fi security type \"{}\"", value)),
+                    }
+                }
+                "P" if !value.is_empty() => wifi.password = Some(value.to_string()),
+                "H" => wifi.hidden = value.eq_ignore_ascii_case("true"),
+                // Empty passwords, and fields from newer revisions such as WPA3's R and I
+                _ => {}
+            }
+        }
+
+        if !has_ssid {
+            return Err("Wi-Fi network payload has no SSID".into());
+        }
+        Ok(wifi)
+    }
+}
+
 impl QrItem for WifiQr {
     fn config(&self) -> &QrConfig {
         &self.config
@@ -1,4 +1,4 @@
-use super::{EmailQr, QrConfig, QrItem, TextQr, WifiQr};
+use super::{EmailQr, Gs1Qr, QrConfig, QrItem, TextQr, WifiQr};
 use std::fmt;
 
 #[cfg(feature = "cli")]
@@ -18,6 +18,28 @@ pub enum QrData {
     Wifi(WifiQr),
     /// Generate a QR code for sending an email.
     Email(EmailQr),
+    /// Generate a GS1 QR code from Application Identifiers, for supply-chain labels.
+    Gs1(Gs1Qr),
+}
+
+impl QrData {
+    /// Recognises the kind of a decoded payload. `gs1` tells whether the symbol was
+    /// encoded in FNC1 mode. Payloads that fit no other type are plain text.
+    pub fn recognize(payload: &str, gs1: bool) -> Self {
+        if gs1 && let Ok(data) = Gs1Qr::from_element_string(payload) {
+            return QrData::Gs1(data);
+        }
+        if let Ok(wifi) = payload.parse() {
+            return QrData::Wifi(wifi);
+        }
+        if let Ok(email) = payload.parse() {
+            return QrData::Email(email);
+        }
+        QrData::Text(TextQr {
+            text: Some(payload.to_string()),
+            config: QrConfig::default(),
+        })
+    }
 }
 
 impl QrItem for QrData {
@@ -26,6 +48,25 @@ impl QrItem for QrData {
             QrData::Text(t) => t.config(),
             QrData::Wifi(w) => w.config(),
             QrData::Email(e) => e.config(),
+            QrData::Gs1(g) => g.config(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            QrData::Text(t) => t.validate(),
+            QrData::Wifi(w) => w.validate(),
+            QrData::Email(e) => e.validate(),
+            QrData::Gs1(g) => g.validate(),
+        }
+    }
+
+    fn is_gs1(&self) -> bool {
+        match self {
+            QrData::Text(t) => t.is_gs1(),
+            QrData::Wifi(w) => w.is_gs1(),
+            QrData::Email(e) => e.is_gs1(),
+            QrData::Gs1(g) => g.is_gs1(),
         }
     }
 }
@@ -36,6 +77,7 @@ impl fmt::Display for QrData {
             QrData::Wifi(wifi) => write!(f, "{}", wifi),
             QrData::Text(text) => write!(f, "{}", text),
             QrData::Email(email) => write!(f, "{}", email),
+            QrData::Gs1(gs1) => write!(f, "{}", gs1),
         }
     }
 }
@@ -8,7 +8,7 @@ use std::io::BufReader;
 struct BatchItem {
     #[serde(flatten)]
     data: QrData,
-    output: String,
+    output: Option<String>,
 }
 
 /// Runs the batch processing mode.
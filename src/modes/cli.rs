@@ -1,35 +1,64 @@
-use crate::{core::generate_qr, models::QrData};
+use crate::{
+    core::{
+        analysis::ScanReport, analyze_qr, analyze_qr_sequence, generate_qr, generate_qr_sequence,
+    },
+    models::{QrData, QrItem},
+};
 use std::io::{self, IsTerminal, Read};
+use std::path::Path;
 
 /// Runs the CLI mode.
 /// Processes the provided QrData, reading from stdin if necessary, and generates the QR code.
-pub fn run(mut data: QrData, output: String) {
-    if let QrData::Text(ref mut text_qr) = data {
-        if text_qr.text.is_none() {
-            if !io::stdin().is_terminal() {
-                let mut buffer = String::new();
-                match io::stdin().read_to_string(&mut buffer) {
-                    Ok(_) => {
-                        text_qr.text = Some(buffer.trim().to_string());
-                    }
-                    Err(e) => {
-                        eprintln!("Error reading from stdin: {}", e);
-                        std::process::exit(1);
-                    }
+/// With `split`, the content is spread across a sequence of QR codes saved as numbered files.
+/// Warns about styling that makes the code hard to scan, and with `strict`, refuses to save
+/// codes that are unlikely to scan.
+pub fn run(mut data: QrData, output: String, split: bool, strict: bool) {
+    if let QrData::Text(ref mut text_qr) = data
+        && text_qr.text.is_none()
+    {
+        if !io::stdin().is_terminal() {
+            let mut buffer = String::new();
+            match io::stdin().read_to_string(&mut buffer) {
+                Ok(_) => {
+                    text_qr.text = Some(buffer.trim().to_string());
+                }
+                Err(e) => {
+                    eprintln!("Error reading from stdin: {}", e);
+                    std::process::exit(1);
                 }
-            } else {
-                eprintln!("Error: Text argument is required or provide input via stdin.");
-                std::process::exit(1);
             }
+        } else {
+            eprintln!("Error: Text argument is required or provide input via stdin.");
+            std::process::exit(1);
         }
     }
 
+    if let Err(e) = data.validate() {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+
     let content = data.to_string();
     if content.is_empty() {
         eprintln!("Error: No content to encode.");
         std::process::exit(1);
     }
 
+    // Content that cannot be encoded fails below with the same error
+    let reports = if split {
+        analyze_qr_sequence(&data)
+    } else {
+        analyze_qr(&data).map(|report| vec![report])
+    };
+    if let Ok(reports) = reports {
+        check_scannability(&reports, strict);
+    }
+
+    if split {
+        run_split(&data, &output);
+        return;
+    }
+
     match generate_qr(&data) {
         Ok(renderer) => match renderer.save(&output) {
             Ok(final_path) => println!("QR code saved to {}", final_path),
@@ -41,3 +70,67 @@ pub fn run(mut data: QrData, output: String) {
         }
     }
 }
+
+/// Prints the scannability warnings of every symbol once, with the lowest score, and exits
+/// if any symbol is unlikely to scan in strict mode.
+fn check_scannability(reports: &[ScanReport], strict: bool) {
+    let mut messages: Vec<&str> = Vec::new();
+    for warning in reports.iter().flat_map(|report| &report.warnings) {
+        if !messages.contains(&warning.message.as_str()) {
+            messages.push(&warning.message);
+        }
+    }
+    if messages.is_empty() {
+        return;
+    }
+    for message in messages {
+        eprintln!("Warning: {}", message);
+    }
+    let score = reports
+        .iter()
+        .map(|report| report.score)
+        .min()
+        .unwrap_or(100);
+    eprintln!("Scannability score: {}/100", score);
+
+    if strict && !reports.iter().all(ScanReport::is_readable) {
+        eprintln!("Error: The QR code is unlikely to scan, not saving it in strict mode.");
+        std::process::exit(1);
+    }
+}
+
+/// Generates a Structured Append sequence and saves each symbol as `<name>_<n>.<ext>`.
+fn run_split(data: &QrData, output: &str) {
+    let renderers = match generate_qr_sequence(data) {
+        Ok(renderers) => renderers,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    for (i, renderer) in renderers.iter().enumerate() {
+        let path = numbered_path(output, i + 1);
+        match renderer.save(&path) {
+            Ok(final_path) => println!("QR code saved to {}", final_path),
+            Err(e) => eprintln!("Error saving QR: {}", e),
+        }
+    }
+}
+
+/// Inserts the sequence number before the extension of the output path, if any.
+fn numbered_path(output: &str, number: usize) -> String {
+    let path = Path::new(output);
+    match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(ext)) => path
+            .with_file_name(format!(
+                "{}_{}.{}",
+                stem.to_string_lossy(),
+                number,
+                ext.to_string_lossy()
+            ))
+            .to_string_lossy()
+            .into_owned(),
+        _ => format!("{}_{}", output, number),
+    }
+}
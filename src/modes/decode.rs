@@ -0,0 +1,172 @@
+use crate::{
+    core::{
+        decoder::{DecodedQr, decode_image, decode_svg},
+        eci,
+    },
+    models::{QrData, WifiSecurity},
+};
+use serde_json::{Value, json};
+
+/// Runs the decode mode.
+/// Reads the QR code in each file and prints its content and symbol parameters,
+/// either as text or, with `json`, as a JSON array with one object per file.
+/// Exits with an error status if any file could not be decoded.
+pub fn run(files: Vec<String>, json: bool) {
+    let results: Vec<(String, Result<DecodedQr, String>)> = files
+        .into_iter()
+        .map(|file| {
+            let result = decode_file(&file);
+            (file, result)
+        })
+        .collect();
+    let failed = results.iter().any(|(_, result)| result.is_err());
+
+    if json {
+        let entries: Vec<Value> = results
+            .iter()
+            .map(|(file, result)| match result {
+                Ok(decoded) => to_json(file, decoded),
+                Err(e) => json!({ "file": file, "error": e }),
+            })
+            .collect();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&entries).unwrap_or_default()
+        );
+    } else {
+        for (i, (file, result)) in results.iter().enumerate() {
+            if i > 0 {
+                println!();
+            }
+            match result {
+                Ok(decoded) => print_decoded(file, decoded),
+                Err(e) => eprintln!("Error: {}: {}", file, e),
+            }
+        }
+    }
+
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+/// Decodes an image file. The format is recognised from the content, and files in
+/// no raster format are read as SVG documents.
+fn decode_file(file: &str) -> Result<DecodedQr, String> {
+    let data = std::fs::read(file).map_err(|e| format!("Failed to read file: {}", e))?;
+    if image::guess_format(&data).is_ok() {
+        let image =
+            image::load_from_memory(&data).map_err(|e| format!("Failed to open image: {}", e))?;
+        decode_image(&image)
+    } else {
+        decode_svg(&data)
+    }
+}
+
+/// Name of the kind of payload, as used by the generate subcommands.
+fn kind(data: &QrData) -> &'static str {
+    match data {
+        QrData::Text(_) => "text",
+        QrData::Wifi(_) => "wifi",
+        QrData::Email(_) => "email",
+        QrData::Gs1(_) => "gs1",
+    }
+}
+
+/// The fields of a recognised payload, in the order they are shown. Plain text has none.
+fn fields(data: &QrData) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+    match data {
+        QrData::Text(_) => {}
+        QrData::Wifi(wifi) => {
+            let security = match wifi.security {
+                WifiSecurity::WPA => "WPA",
+                WifiSecurity::WEP => "WEP",
+                WifiSecurity::NoPass => "nopass",
+            };
+            fields.push(("ssid".into(), wifi.ssid.clone()));
+            fields.push(("security".into(), security.into()));
+            if let Some(password) = &wifi.password {
+                fields.push(("password".into(), password.clone()));
+            }
+            fields.push(("hidden".into(), wifi.hidden.to_string()));
+        }
+        QrData::Email(email) => {
+            fields.push(("to".into(), email.to.clone()));
+            for (name, value) in [
+                ("subject", &email.subject),
+                ("body", &email.body),
+                ("cc", &email.cc),
+                ("bcc", &email.bcc),
+            ] {
+                if let Some(value) = value {
+                    fields.push((name.into(), value.clone()));
+                }
+            }
+        }
+        QrData::Gs1(gs1) => {
+            for (ai, value) in gs1.elements().unwrap_or_default() {
+                fields.push((format!("({})", ai), value));
+            }
+        }
+    }
+    fields
+}
+
+fn print_decoded(file: &str, decoded: &DecodedQr) {
+    let data = QrData::recognize(&decoded.text, decoded.gs1);
+
+    println!("{}", file);
+    println!("  Payload:   {}", decoded.text);
+    println!("  Type:      {}", kind(&data));
+    for (name, value) in fields(&data) {
+        println!("    {}: {}", name, value);
+    }
+    println!("  Version:   {}", decoded.version.value());
+    println!("  ECC level: {:?}", decoded.ecl);
+    println!("  Mask:      {}", decoded.mask.value());
+    match decoded.eci {
+        Some(value) => match eci::label(value) {
+            Some(label) => println!("  ECI:       {} ({})", value, label),
+            None => println!("  ECI:       {}", value),
+        },
+        None => println!("  ECI:       none"),
+    }
+    if let Some(sa) = decoded.structured_append {
+        println!(
+            "  Sequence:  symbol {} of {} (parity {:#04x})",
+            sa.index + 1,
+            sa.total,
+            sa.parity
+        );
+    }
+    if decoded.corrected > 0 {
+        println!("  Corrected: {} codewords", decoded.corrected);
+    }
+}
+
+fn to_json(file: &str, decoded: &DecodedQr) -> Value {
+    let data = QrData::recognize(&decoded.text, decoded.gs1);
+    let fields: serde_json::Map<String, Value> = fields(&data)
+        .into_iter()
+        .map(|(name, value)| (name, Value::String(value)))
+        .collect();
+
+    json!({
+        "file": file,
+        "payload": decoded.text,
+        "type": kind(&data),
+        "fields": fields,
+        "version": decoded.version.value(),
+        "ecc": format!("{:?}", decoded.ecl).to_lowercase(),
+        "mask": decoded.mask.value(),
+        "eci": decoded.eci,
+        "charset": decoded.eci.and_then(eci::label),
+        "structured_append": decoded.structured_append.map(|sa| json!({
+            "index": sa.index,
+            "total": sa.total,
+            "parity": sa.parity,
+        })),
+        "corrected": decoded.corrected,
+    })
+}
@@ -6,3 +6,6 @@ pub mod batch;
 
 #[cfg(feature = "cli")]
 pub mod cli;
+
+#[cfg(feature = "cli")]
+pub mod decode;